@@ -28,11 +28,15 @@ impl Operations {
     async fn login(ctx: &Context<'_>) -> async_graphql::Result<customer::Model> {
         use sea_orm::EntityTrait;
 
-        let db = ctx.data::<DatabaseConnection>().unwrap();
-        Ok(customer::Entity::find()
-            .one(db)
-            .await?
-            .ok_or_else(|| DbErr::RecordNotFound("Customer not found".to_owned()))?)
+        // a mutation of the request may hold the request transaction
+        let customer = match seaography::open_request_transaction(ctx) {
+            Some(transaction) => customer::Entity::find().one(transaction.as_ref()).await?,
+            None => {
+                let db = ctx.data::<DatabaseConnection>().unwrap();
+                customer::Entity::find().one(db).await?
+            }
+        };
+        Ok(customer.ok_or_else(|| DbErr::RecordNotFound("Customer not found".to_owned()))?)
     }
 
     async fn rental_request(
@@ -0,0 +1,141 @@
+use async_graphql::dynamic::*;
+use seaography::{
+    async_graphql, lazy_static, BuilderContext, LifecycleHooks, LifecycleHooksInterface,
+};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+use std::any::Any;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        BuilderContext {
+            hooks: LifecycleHooks::new(DecryptHooks),
+            ..Default::default()
+        }
+    };
+}
+
+struct DecryptHooks;
+
+impl LifecycleHooksInterface for DecryptHooks {
+    fn after_read(&self, _ctx: &ResolverContext, entity: &str, models: &mut dyn Any) {
+        if entity != "Language" {
+            return;
+        }
+        let languages = models.downcast_mut::<Vec<language::Model>>().unwrap();
+
+        // stands in for a check that cannot be expressed as a condition
+        languages.retain(|language| !language.name.starts_with("enc:Secret"));
+        for language in languages {
+            language.name = language.name.trim_start_matches("enc:").to_owned();
+        }
+    }
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_table(&database, language::Entity).await;
+    create_table(&database, film::Entity).await;
+    execute_sql(
+        &database,
+        r#"
+        INSERT INTO language (language_id, name, last_update) VALUES
+            (1, 'enc:English', '2030-01-01 00:00:00'),
+            (2, 'enc:Secret', '2030-01-01 00:00:00'),
+            (3, 'enc:German', '2030-01-01 00:00:00');
+        INSERT INTO film (film_id, title, language_id, rental_duration, rental_rate, replacement_cost, last_update) VALUES
+            (1, 'Alien', 1, 3, 0.99, 9.99, '2030-01-01 00:00:00'),
+            (2, 'Heat', 2, 3, 0.99, 9.99, '2030-01-01 00:00:00');
+        "#,
+    )
+    .await;
+
+    common::schema(&CONTEXT, database)
+}
+
+#[tokio::test]
+async fn after_read_transforms_and_removes_models() {
+    let schema = schema().await;
+
+    assert_eq!(
+        data(schema.execute("{ language { nodes { name } } }").await),
+        json!({ "language": { "nodes": [{ "name": "English" }, { "name": "German" }] } })
+    );
+
+    // the removed model is dropped from the page, the cursors are those of the database page
+    let page = data(
+        schema
+            .execute(
+                r#"
+                {
+                  language(pagination: { cursor: { limit: 2 } }) {
+                    edges { cursor node { name } }
+                    pageInfo { startCursor endCursor hasNextPage }
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+    let cursor = &page["language"]["edges"][0]["cursor"];
+    let end_cursor = &page["language"]["pageInfo"]["endCursor"];
+    assert_ne!(end_cursor, cursor);
+    assert_eq!(
+        page,
+        json!({
+            "language": {
+                "edges": [{ "cursor": cursor, "node": { "name": "English" } }],
+                "pageInfo": { "startCursor": cursor, "endCursor": end_cursor, "hasNextPage": true }
+            }
+        })
+    );
+
+    let next_page = |cursor: &serde_json::Value| {
+        let query = format!(
+            r#"
+            {{
+              language(pagination: {{ cursor: {{ limit: 1, cursor: {cursor} }} }}) {{
+                nodes {{ name }}
+                pageInfo {{ startCursor endCursor hasNextPage }}
+              }}
+            }}
+            "#
+        );
+        let schema = &schema;
+        async move { data(schema.execute(query).await)["language"].clone() }
+    };
+
+    assert_eq!(
+        next_page(end_cursor).await["nodes"],
+        json!([{ "name": "German" }])
+    );
+
+    // a page emptied by the hook keeps its cursors, so the following pages can be reached
+    assert_eq!(
+        next_page(cursor).await,
+        json!({
+            "nodes": [],
+            "pageInfo": { "startCursor": end_cursor, "endCursor": end_cursor, "hasNextPage": true }
+        })
+    );
+
+    // relations are loaded through the same hook
+    assert_eq!(
+        data(
+            schema
+                .execute("{ film { nodes { title language1 { name } } } }")
+                .await
+        ),
+        json!({
+            "film": {
+                "nodes": [
+                    { "title": "Alien", "language1": { "name": "English" } },
+                    { "title": "Heat", "language1": null }
+                ]
+            }
+        })
+    );
+}
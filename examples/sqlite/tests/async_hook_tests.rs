@@ -0,0 +1,278 @@
+use async_graphql::dynamic::*;
+use sea_orm::{entity::prelude::async_trait, ColumnTrait, Condition};
+use seaography::{
+    async_graphql, lazy_static, BuilderContext, GuardAction, LifecycleHooks,
+    LifecycleHooksInterface, OperationType,
+};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        BuilderContext {
+            hooks: LifecycleHooks::new(AsyncHooks),
+            ..Default::default()
+        }
+    };
+    static ref COUNTING_CONTEXT : BuilderContext = {
+        BuilderContext {
+            hooks: LifecycleHooks::new(CountingHooks),
+            ..Default::default()
+        }
+    };
+}
+
+static READ_LOOKUPS: AtomicUsize = AtomicUsize::new(0);
+static READ_FIELDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// counts the lookups of the field guards of rows read and records their fields, replacing names
+struct CountingHooks;
+
+#[async_trait::async_trait]
+impl LifecycleHooksInterface for CountingHooks {
+    async fn field_guards_async(
+        &self,
+        _ctx: &ResolverContext,
+        _entity: &str,
+        fields: &[&str],
+        action: OperationType,
+    ) -> Vec<GuardAction> {
+        if action == OperationType::Read {
+            READ_LOOKUPS.fetch_add(1, Ordering::SeqCst);
+            READ_FIELDS
+                .lock()
+                .unwrap()
+                .extend(fields.iter().map(|field| field.to_string()));
+        }
+        lookup(
+            fields
+                .iter()
+                .map(|field| {
+                    if action == OperationType::Read && *field == "name" {
+                        GuardAction::Replace("***".into())
+                    } else {
+                        GuardAction::Allow
+                    }
+                })
+                .collect(),
+        )
+        .await
+    }
+}
+
+/// stands in for permissions looked up in a database or cache
+async fn lookup<T>(value: T) -> T {
+    tokio::task::yield_now().await;
+    value
+}
+
+struct AsyncHooks;
+
+#[async_trait::async_trait]
+impl LifecycleHooksInterface for AsyncHooks {
+    async fn entity_guard_async(
+        &self,
+        _ctx: &ResolverContext,
+        entity: &str,
+        action: OperationType,
+    ) -> GuardAction {
+        lookup(if entity == "Language" && action == OperationType::Delete {
+            GuardAction::Block(Some("Languages are never deleted".into()))
+        } else {
+            GuardAction::Allow
+        })
+        .await
+    }
+
+    async fn field_guards_async(
+        &self,
+        _ctx: &ResolverContext,
+        entity: &str,
+        fields: &[&str],
+        action: OperationType,
+    ) -> Vec<GuardAction> {
+        // one lookup for all fields of the input object or the row
+        lookup(
+            fields
+                .iter()
+                .map(|field| {
+                    if entity == "Language"
+                        && action == OperationType::Update
+                        && *field == "languageId"
+                    {
+                        GuardAction::Block(Some("Ids are immutable".into()))
+                    } else if entity == "Language"
+                        && *field == "lastUpdate"
+                        && action == OperationType::Read
+                    {
+                        GuardAction::Block(None)
+                    } else {
+                        GuardAction::Allow
+                    }
+                })
+                .collect(),
+        )
+        .await
+    }
+
+    async fn field_guard_async(
+        &self,
+        _ctx: &ResolverContext,
+        entity: &str,
+        field: &str,
+        action: OperationType,
+    ) -> GuardAction {
+        lookup(
+            if entity == "Language" && field == "lastUpdate" && action == OperationType::Read {
+                GuardAction::Block(None)
+            } else {
+                GuardAction::Allow
+            },
+        )
+        .await
+    }
+
+    async fn entity_filter_async(
+        &self,
+        _ctx: &ResolverContext,
+        entity: &str,
+        _action: OperationType,
+    ) -> Option<Condition> {
+        lookup(
+            (entity == "Language")
+                .then(|| Condition::all().add(language::Column::Name.ne("Hidden"))),
+        )
+        .await
+    }
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_table(&database, language::Entity).await;
+
+    common::schema(&CONTEXT, database)
+}
+
+#[tokio::test]
+async fn async_hooks_are_awaited() {
+    let schema = schema().await;
+
+    data(
+        schema
+            .execute(
+                r#"
+                mutation {
+                  languageCreateBatch(data: [
+                    { languageId: 1, name: "English", lastUpdate: "2030-01-01 00:00:00 UTC" }
+                    { languageId: 2, name: "Hidden", lastUpdate: "2030-01-01 00:00:00 UTC" }
+                  ]) {
+                    languageId
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+
+    // async entity filter
+    assert_eq!(
+        data(
+            schema
+                .execute("{ language { nodes { languageId name } } }")
+                .await
+        ),
+        json!({ "language": { "nodes": [{ "languageId": 1, "name": "English" }] } })
+    );
+
+    // async field guard on output fields
+    assert_eq!(
+        error(
+            schema
+                .execute("{ language { nodes { lastUpdate } } }")
+                .await
+        ),
+        "Field guard triggered."
+    );
+
+    // batched field guards on input objects
+    assert_eq!(
+        error(
+            schema
+                .execute(
+                    r#"
+                    mutation {
+                      languageUpdate(data: { languageId: 3, name: "Deutsch" }, filter: { languageId: { eq: 1 } }) {
+                        languageId
+                      }
+                    }
+                    "#,
+                )
+                .await
+        ),
+        "Ids are immutable"
+    );
+
+    // async entity guard
+    assert_eq!(
+        error(
+            schema
+                .execute("mutation { languageDelete(filter: { languageId: { eq: 1 } }) }")
+                .await
+        ),
+        "Languages are never deleted"
+    );
+}
+
+#[tokio::test]
+async fn read_field_guards_are_evaluated_once_per_row() {
+    let database = database().await;
+    create_table(&database, language::Entity).await;
+    execute_sql(
+        &database,
+        r#"
+        INSERT INTO language (language_id, name, last_update) VALUES
+            (1, 'English', '2030-01-01 00:00:00'),
+            (2, 'Deutsch', '2030-01-01 00:00:00');
+        "#,
+    )
+    .await;
+    let schema = common::schema(&COUNTING_CONTEXT, database);
+
+    assert_eq!(
+        data(
+            schema
+                .execute("{ language { nodes { languageId name lastUpdate } } }")
+                .await
+        )["language"]["nodes"],
+        json!([
+            { "languageId": 1, "name": "***", "lastUpdate": "2030-01-01 00:00:00 UTC" },
+            { "languageId": 2, "name": "***", "lastUpdate": "2030-01-01 00:00:00 UTC" }
+        ])
+    );
+    assert_eq!(READ_LOOKUPS.load(Ordering::SeqCst), 2);
+
+    // only the fields selected, also through fragments, are looked up
+    READ_FIELDS.lock().unwrap().clear();
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    r#"
+                    { language { nodes { ...names } } }
+                    fragment names on Language { name }
+                    "#
+                )
+                .await
+        )["language"]["nodes"],
+        json!([{ "name": "***" }, { "name": "***" }])
+    );
+    assert_eq!(READ_LOOKUPS.load(Ordering::SeqCst), 4);
+    assert_eq!(*READ_FIELDS.lock().unwrap(), ["name", "name"]);
+}
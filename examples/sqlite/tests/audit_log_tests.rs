@@ -0,0 +1,145 @@
+use async_graphql::{dynamic::*, Request};
+use sea_orm::{ConnectionTrait, DbBackend};
+use seaography::{async_graphql, lazy_static, BuilderContext, UserContext};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        context.audit_log.enabled = true;
+        context.audit_log.table = "audit_trail".into();
+        context.audit_log.query = true;
+        context
+    };
+    static ref WITHOUT_QUERY_CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        context.audit_log.enabled = true;
+        context.audit_log.table = "audit_trail".into();
+        context
+    };
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_table(&database, language::Entity).await;
+    database
+        .execute(&CONTEXT.audit_log.table_create_statement(DbBackend::Sqlite))
+        .await
+        .unwrap();
+
+    common::schema(&CONTEXT, database)
+}
+
+#[tokio::test]
+async fn mutations_are_audited() {
+    let schema = schema().await;
+
+    data(
+        schema
+            .execute(
+                Request::new(
+                    r#"
+                    mutation {
+                      languageCreateOne(data: { languageId: 100, name: "Klingon", lastUpdate: "2030-01-01 00:00:00 UTC" }) {
+                        languageId
+                      }
+                    }
+                    "#,
+                )
+                .data(UserContext::new(7i64)),
+            )
+            .await,
+    );
+
+    data(
+        schema
+            .execute(
+                r#"
+                mutation {
+                  languageUpdate(data: { name: "tlhIngan" }, filter: { languageId: { eq: 100 } }) {
+                    languageId
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+
+    // user ids of any type, e.g. the subject of a token
+    data(
+        schema
+            .execute(
+                Request::new(
+                    r#"
+                    mutation {
+                      languageDelete(filter: { languageId: { eq: 100 } })
+                    }
+                    "#,
+                )
+                .data(UserContext::new("auth0|7")),
+            )
+            .await,
+    );
+
+    let log = data(
+        schema
+            .execute(
+                r#"
+                {
+                  auditTrail(filters: { entity: { eq: "Language" } }, orderBy: { id: ASC }) {
+                    nodes {
+                      actor
+                      entity
+                      primaryKey
+                      operation
+                      diff
+                    }
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+
+    let nodes = log["auditTrail"]["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 3);
+
+    assert_eq!(nodes[0]["actor"], "7");
+    assert_eq!(nodes[1]["actor"], json!(null));
+    assert_eq!(nodes[2]["actor"], "auth0|7");
+    assert!(nodes
+        .iter()
+        .all(|node| node["primaryKey"] == nodes[0]["primaryKey"]));
+    assert_eq!(
+        nodes
+            .iter()
+            .map(|node| node["operation"].as_str().unwrap())
+            .collect::<Vec<_>>(),
+        vec!["CREATE", "UPDATE", "DELETE"]
+    );
+
+    let diffs: Vec<serde_json::Value> = nodes
+        .iter()
+        .map(|node| serde_json::from_str(node["diff"].as_str().unwrap()).unwrap())
+        .collect();
+
+    assert_eq!(diffs[0]["languageId"], json!({ "after": 100 }));
+    assert_eq!(diffs[0]["name"], json!({ "after": "Klingon" }));
+    assert_eq!(
+        diffs[1],
+        json!({ "name": { "before": "Klingon", "after": "tlhIngan" } })
+    );
+    assert_eq!(diffs[2]["name"], json!({ "before": "tlhIngan" }));
+}
+
+#[tokio::test]
+async fn audit_log_query_is_added_on_request() {
+    let schema = common::schema(&WITHOUT_QUERY_CONTEXT, database().await);
+
+    let response = schema.execute("{ auditTrail { nodes { id } } }").await;
+    assert!(!response.errors.is_empty());
+}
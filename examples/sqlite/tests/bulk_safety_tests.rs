@@ -0,0 +1,166 @@
+use async_graphql::dynamic::*;
+use seaography::{
+    async_graphql, lazy_static, BuilderContext, EntityDeleteMutationConfig,
+    EntityUpdateMutationConfig, ErrorConfig,
+};
+use serde_json::json;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        BuilderContext {
+            entity_update_mutation: EntityUpdateMutationConfig {
+                reject_empty_filter: true,
+                max_affected_rows: Some(2),
+                ..Default::default()
+            },
+            entity_delete_mutation: EntityDeleteMutationConfig {
+                require_filter: true,
+                reject_empty_filter: true,
+                max_affected_rows: Some(2),
+                ..Default::default()
+            },
+            errors: ErrorConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    };
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_customers(&database).await;
+    execute_sql(
+        &database,
+        r#"
+        INSERT INTO customer (customer_id, store_id, first_name, last_name, address_id, active, create_date, last_update) VALUES
+            (3, 1, 'Patricia', 'Johnson', 1, 1, '2030-01-01 00:00:00', '2030-01-01 00:00:00');
+        "#,
+    )
+    .await;
+
+    common::schema(&CONTEXT, database)
+}
+
+async fn active(schema: &Schema) -> serde_json::Value {
+    data(
+        schema
+            .execute("{ customer(orderBy: { customerId: ASC }) { nodes { active } } }")
+            .await,
+    )["customer"]["nodes"]
+        .clone()
+}
+
+#[tokio::test]
+async fn unfiltered_changes_are_rejected() {
+    let schema = schema().await;
+
+    let (message, _) = error_with_extensions(schema.execute("mutation { customerDelete }").await);
+    assert!(message.contains(r#"argument "filter""#), "{message}");
+
+    for mutation in [
+        "mutation { customerDelete(filter: {}) }",
+        "mutation { customerUpdate(data: { active: 0 }) { customerId } }",
+        "mutation { customerUpdate(data: { active: 0 }, filter: { and: [{}] }) { customerId } }",
+        "mutation { customerUpdate(data: { active: 0 }, filter: { customerId: {} }) { customerId } }",
+    ] {
+        let (message, extensions) = error_with_extensions(schema.execute(mutation).await);
+        assert_eq!(
+            message,
+            "A filter with at least one condition is required to change Customer."
+        );
+        assert_eq!(extensions["code"], "BAD_USER_INPUT");
+    }
+
+    assert_eq!(
+        active(&schema).await,
+        json!([{ "active": 1 }, { "active": 1 }, { "active": 1 }])
+    );
+}
+
+#[tokio::test]
+async fn too_many_affected_rows_are_rolled_back() {
+    let schema = schema().await;
+
+    let (message, extensions) = error_with_extensions(
+        schema
+            .execute(
+                "mutation { customerUpdate(data: { active: 0 }, filter: { customerId: { gte: 1 } }) { customerId } }",
+            )
+            .await,
+    );
+    assert_eq!(
+        message,
+        "The mutation would affect 3 rows of Customer, more than the maximum of 2."
+    );
+    assert_eq!(
+        extensions,
+        json!({
+            "code": "TOO_MANY_AFFECTED_ROWS",
+            "affectedCount": 3,
+            "maxAffectedRows": 2,
+            "entity": "Customer"
+        })
+    );
+    assert_eq!(
+        active(&schema).await,
+        json!([{ "active": 1 }, { "active": 1 }, { "active": 1 }])
+    );
+
+    let (_, extensions) = error_with_extensions(
+        schema
+            .execute("mutation { customerDelete(filter: { active: { eq: 1 } }) }")
+            .await,
+    );
+    assert_eq!(extensions["code"], "TOO_MANY_AFFECTED_ROWS");
+    assert_eq!(active(&schema).await.as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn dry_runs_are_rolled_back() {
+    let schema = schema().await;
+
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    "mutation { customerUpdate(data: { active: 0 }, filter: { storeId: { eq: 1 } }, dryRun: true) { customerId active } }",
+                )
+                .await
+        ),
+        json!({
+            "customerUpdate": [
+                { "customerId": 1, "active": 0 },
+                { "customerId": 3, "active": 0 }
+            ]
+        })
+    );
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    "mutation { customerDelete(filter: { storeId: { eq: 1 } }, dryRun: true) }"
+                )
+                .await
+        ),
+        json!({ "customerDelete": 2 })
+    );
+    assert_eq!(
+        active(&schema).await,
+        json!([{ "active": 1 }, { "active": 1 }, { "active": 1 }])
+    );
+
+    assert_eq!(
+        data(
+            schema
+                .execute("mutation { customerDelete(filter: { storeId: { eq: 1 } }) }")
+                .await
+        ),
+        json!({ "customerDelete": 2 })
+    );
+    assert_eq!(active(&schema).await, json!([{ "active": 1 }]));
+}
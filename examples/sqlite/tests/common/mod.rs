@@ -0,0 +1,83 @@
+//! The in-memory database and the helpers shared by the tests not using `sakila.db`
+#![allow(dead_code)]
+
+use async_graphql::{dynamic::Schema, Response};
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbBackend, EntityTrait,
+};
+use seaography::{async_graphql, BuilderContext};
+use seaography_sqlite_example::entities::*;
+
+/// used to open an empty in-memory database. It has a single connection,
+/// every connection to an in-memory database opens a new one
+pub async fn database() -> DatabaseConnection {
+    let mut options = ConnectOptions::new("sqlite::memory:");
+    options.max_connections(1);
+    Database::connect(options).await.unwrap()
+}
+
+/// used to stop enforcing foreign keys, so referenced rows can be left out of the fixtures
+pub async fn disable_foreign_keys(database: &DatabaseConnection) {
+    execute_sql(database, "PRAGMA foreign_keys = OFF").await;
+}
+
+/// used to create the table of an entity
+pub async fn create_table<E: EntityTrait>(database: &DatabaseConnection, entity: E) {
+    database
+        .execute(&sea_orm::Schema::new(DbBackend::Sqlite).create_table_from_entity(entity))
+        .await
+        .unwrap();
+}
+
+/// used to run SQL statements, e.g. to insert fixtures
+pub async fn execute_sql(database: &DatabaseConnection, sql: &str) {
+    database.execute_unprepared(sql).await.unwrap();
+}
+
+/// used to create the customer table, with Mary of store 1 and Linda of store 2.
+/// Foreign keys are disabled, the stores and addresses are left out
+pub async fn create_customers(database: &DatabaseConnection) {
+    disable_foreign_keys(database).await;
+    create_table(database, customer::Entity).await;
+    execute_sql(
+        database,
+        r#"
+        INSERT INTO customer (customer_id, store_id, first_name, last_name, address_id, active, create_date, last_update) VALUES
+            (1, 1, 'Mary', 'Smith', 1, 1, '2030-01-01 00:00:00', '2030-01-01 00:00:00'),
+            (2, 2, 'Linda', 'Williams', 1, 1, '2030-01-01 00:00:00', '2030-01-01 00:00:00');
+        "#,
+    )
+    .await;
+}
+
+/// used to build the schema of the example entities
+pub fn schema(context: &'static BuilderContext, database: DatabaseConnection) -> Schema {
+    seaography_sqlite_example::query_root::schema_builder(context, database, None, None)
+        .finish()
+        .unwrap()
+}
+
+/// used to get the data of a response without errors
+pub fn data(response: Response) -> serde_json::Value {
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    response.data.into_json().unwrap()
+}
+
+/// used to get the message of the only error of a response
+pub fn error(response: Response) -> String {
+    error_with_extensions(response).0
+}
+
+/// used to get the message and the extensions of the only error of a response
+pub fn error_with_extensions(response: Response) -> (String, serde_json::Value) {
+    assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+    let error = &response.errors[0];
+    (
+        error.message.clone(),
+        error
+            .extensions
+            .as_ref()
+            .map(|extensions| serde_json::to_value(extensions).unwrap())
+            .unwrap_or_default(),
+    )
+}
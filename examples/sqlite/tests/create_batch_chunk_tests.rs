@@ -0,0 +1,71 @@
+use async_graphql::dynamic::*;
+use sea_orm::Database;
+use seaography::{async_graphql, lazy_static, BuilderContext};
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        // three columns per film_text row, so at most two rows per statement
+        context.entity_create_batch_mutation.max_parameters = Some(6);
+        context
+    };
+}
+
+async fn schema() -> Schema {
+    let database = Database::connect("sqlite://sakila.db").await.unwrap();
+    seaography_sqlite_example::query_root::schema_builder(&CONTEXT, database, None, None)
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn create_batch_is_chunked_in_input_order() {
+    let schema = schema().await;
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              filmTextCreateBatch(
+                data: [
+                  { filmId: 105, title: "TEST 105", description: "TEST DESC 105" }
+                  { filmId: 101, title: "TEST 101", description: "TEST DESC 101" }
+                  { filmId: 104, title: "TEST 104", description: "TEST DESC 104" }
+                  { filmId: 102, title: "TEST 102" }
+                  { filmId: 103, title: "TEST 103", description: "TEST DESC 103" }
+                ]
+              ) {
+                filmId
+                description
+              }
+            }
+            "#,
+        )
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "filmTextCreateBatch": [
+                { "filmId": 105, "description": "TEST DESC 105" },
+                { "filmId": 101, "description": "TEST DESC 101" },
+                { "filmId": 104, "description": "TEST DESC 104" },
+                { "filmId": 102, "description": null },
+                { "filmId": 103, "description": "TEST DESC 103" },
+            ]
+        })
+    );
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              filmTextDelete(filter: { filmId: { gte: 101, lte: 105 } })
+            }
+            "#,
+        )
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+}
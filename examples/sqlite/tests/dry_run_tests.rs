@@ -0,0 +1,138 @@
+use async_graphql::{dynamic::*, Request};
+use seaography::{async_graphql, lazy_static, BuilderContext, DryRun};
+use serde_json::json;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        context.entity_update_batch_mutation.enabled = true;
+        context
+    };
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_customers(&database).await;
+
+    common::schema(&CONTEXT, database)
+}
+
+async fn customers(schema: &Schema) -> serde_json::Value {
+    data(
+        schema
+            .execute(
+                "{ customer(orderBy: { customerId: ASC }) { nodes { customerId firstName } } }",
+            )
+            .await,
+    )["customer"]["nodes"]
+        .clone()
+}
+
+fn unchanged() -> serde_json::Value {
+    json!([
+        { "customerId": 1, "firstName": "Mary" },
+        { "customerId": 2, "firstName": "Linda" }
+    ])
+}
+
+#[tokio::test]
+async fn dry_run_argument() {
+    let schema = schema().await;
+
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    r#"mutation {
+                      customerCreateOne(
+                        data: { customerId: 3, storeId: 1, firstName: "Barbara", lastName: "Jones", addressId: 1, active: 1, createDate: "2030-01-01T00:00:00Z", lastUpdate: "2030-01-01T00:00:00Z" }
+                        dryRun: true
+                      ) { customerId firstName }
+                    }"#,
+                )
+                .await
+        ),
+        json!({ "customerCreateOne": { "customerId": 3, "firstName": "Barbara" } })
+    );
+
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    r#"mutation {
+                      customerCreateBatch(
+                        data: [
+                          { customerId: 3, storeId: 1, firstName: "Barbara", lastName: "Jones", addressId: 1, active: 1, createDate: "2030-01-01T00:00:00Z", lastUpdate: "2030-01-01T00:00:00Z" }
+                          { customerId: 4, storeId: 1, firstName: "Susan", lastName: "Wilson", addressId: 1, active: 1, createDate: "2030-01-01T00:00:00Z", lastUpdate: "2030-01-01T00:00:00Z" }
+                        ]
+                        dryRun: true
+                      ) { firstName }
+                    }"#,
+                )
+                .await
+        ),
+        json!({ "customerCreateBatch": [{ "firstName": "Barbara" }, { "firstName": "Susan" }] })
+    );
+
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    r#"mutation {
+                      customerUpdateBatch(
+                        data: [{ customerId: 2, changes: { firstName: "Lynn" } }]
+                        dryRun: true
+                      ) { customerId firstName }
+                    }"#,
+                )
+                .await
+        ),
+        json!({ "customerUpdateBatch": [{ "customerId": 2, "firstName": "Lynn" }] })
+    );
+
+    assert_eq!(customers(&schema).await, unchanged());
+}
+
+#[tokio::test]
+async fn dry_run_request() {
+    let schema = schema().await;
+
+    let mutation = r#"mutation {
+      customerUpdate(data: { firstName: "Anne" }, filter: { customerId: { eq: 1 } }) { firstName }
+      customerDelete(filter: { customerId: { eq: 2 } })
+    }"#;
+
+    assert_eq!(
+        data(
+            schema
+                .execute(Request::new(mutation).data(DryRun(true)))
+                .await
+        ),
+        json!({ "customerUpdate": [{ "firstName": "Anne" }], "customerDelete": 1 })
+    );
+    assert_eq!(customers(&schema).await, unchanged());
+
+    // the argument overrides the request
+    data(
+        schema
+            .execute(
+                Request::new(
+                    r#"mutation {
+                      customerUpdate(data: { firstName: "Anne" }, filter: { customerId: { eq: 1 } }, dryRun: false) { firstName }
+                    }"#,
+                )
+                .data(DryRun(true)),
+            )
+            .await,
+    );
+    assert_eq!(
+        customers(&schema).await,
+        json!([
+            { "customerId": 1, "firstName": "Anne" },
+            { "customerId": 2, "firstName": "Linda" }
+        ])
+    );
+}
@@ -0,0 +1,162 @@
+use async_graphql::{dynamic::*, Response};
+use sea_orm::{entity::prelude::async_trait, ActiveValue, ColumnTrait, Condition};
+use seaography::{
+    async_graphql, lazy_static, Builder, BuilderContext, EntityHooks, GuardAction, LifecycleHooks,
+    LifecycleHooksInterface, MultiLifecycleHooks, OperationType,
+};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+use std::any::Any;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        BuilderContext {
+            hooks: LifecycleHooks::new(MultiLifecycleHooks::default().add(GlobalHooks)),
+            ..Default::default()
+        }
+    };
+}
+
+struct GlobalHooks;
+
+impl LifecycleHooksInterface for GlobalHooks {
+    fn entity_filter(
+        &self,
+        _ctx: &ResolverContext,
+        entity: &str,
+        action: OperationType,
+    ) -> Option<Condition> {
+        (entity == "Language" && action == OperationType::Read)
+            .then(|| Condition::all().add(language::Column::LanguageId.ne(3)))
+    }
+
+    fn before_active_model_save(
+        &self,
+        _ctx: &ResolverContext,
+        _entity: &str,
+        _action: OperationType,
+        active_model: &mut dyn Any,
+    ) -> GuardAction {
+        match active_model.downcast_ref::<language::ActiveModel>() {
+            Some(language) if language.language_id == ActiveValue::Set(99) => {
+                GuardAction::Block(Some("Reserved id".into()))
+            }
+            _ => GuardAction::Allow,
+        }
+    }
+}
+
+struct LanguageHooks;
+
+#[async_trait::async_trait]
+impl EntityHooks<language::Entity> for LanguageHooks {
+    async fn guard(&self, _ctx: &ResolverContext, action: OperationType) -> GuardAction {
+        if action == OperationType::Delete {
+            GuardAction::Block(Some("Languages are never deleted".into()))
+        } else {
+            GuardAction::Allow
+        }
+    }
+
+    async fn filter(&self, _ctx: &ResolverContext, action: OperationType) -> Option<Condition> {
+        (action == OperationType::Read)
+            .then(|| Condition::all().add(language::Column::Name.ne("Hidden")))
+    }
+
+    fn before_insert(
+        &self,
+        _ctx: &ResolverContext,
+        active_model: &mut language::ActiveModel,
+    ) -> GuardAction {
+        if let ActiveValue::Set(name) = &active_model.name {
+            active_model.name = ActiveValue::Set(name.trim().to_owned());
+        }
+        GuardAction::Allow
+    }
+
+    fn before_update(
+        &self,
+        _ctx: &ResolverContext,
+        active_model: &mut language::ActiveModel,
+    ) -> GuardAction {
+        match &active_model.name {
+            ActiveValue::Set(name) if name.is_empty() => {
+                GuardAction::Block(Some("Name must not be empty".into()))
+            }
+            _ => GuardAction::Allow,
+        }
+    }
+
+    fn after_read(&self, _ctx: &ResolverContext, model: &mut language::Model) {
+        model.name = model.name.to_uppercase();
+    }
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_table(&database, language::Entity).await;
+
+    let builder = register_entity_modules(Builder::new(&CONTEXT, database.clone()))
+        .register_entity_hooks::<language::Entity, _>(LanguageHooks);
+
+    builder.schema_builder().data(database).finish().unwrap()
+}
+
+async fn create(schema: &Schema, id: i32, name: &str) -> Response {
+    schema
+        .execute(format!(
+            r#"
+            mutation {{
+              languageCreateOne(data: {{ languageId: {id}, name: "{name}", lastUpdate: "2030-01-01 00:00:00 UTC" }}) {{
+                languageId
+              }}
+            }}
+            "#
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn typed_entity_hooks() {
+    let schema = schema().await;
+
+    for (id, name) in [(1, "  English "), (2, "Hidden"), (3, "German")] {
+        data(create(&schema, id, name).await);
+    }
+
+    // the global hook is forwarded by MultiLifecycleHooks
+    assert_eq!(error(create(&schema, 99, "Klingon").await), "Reserved id");
+
+    // the name was trimmed before insert, the typed and the global filter both apply
+    assert_eq!(
+        data(
+            schema
+                .execute("{ language { nodes { languageId name } } }")
+                .await
+        ),
+        json!({ "language": { "nodes": [{ "languageId": 1, "name": "ENGLISH" }] } })
+    );
+
+    assert_eq!(
+        error(
+            schema
+                .execute(
+                    r#"mutation { languageUpdate(data: { name: "" }, filter: { languageId: { eq: 1 } }) { languageId } }"#
+                )
+                .await
+        ),
+        "Name must not be empty"
+    );
+
+    assert_eq!(
+        error(
+            schema
+                .execute("mutation { languageDelete(filter: { languageId: { eq: 1 } }) }")
+                .await
+        ),
+        "Languages are never deleted"
+    );
+}
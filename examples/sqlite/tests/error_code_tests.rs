@@ -0,0 +1,246 @@
+use async_graphql::dynamic::*;
+use sea_orm::{DbErr, RuntimeErr};
+use seaography::{
+    async_graphql, classify_db_error, lazy_static, BuilderContext, EntityColumnId, ErrorCode,
+    ErrorConfig, FilterType, OperationType, PaginationInputConfig, SeaographyError,
+    VisibilityConfig,
+};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref DEVELOPMENT : BuilderContext = context(false);
+    static ref PRODUCTION : BuilderContext = context(true);
+    static ref LOOKUP_FILTER : BuilderContext = {
+        let mut context = context(true);
+        let last_name = EntityColumnId::of::<customer::Entity>(&customer::Column::LastName);
+        context
+            .filter_types
+            .overwrites
+            .insert(last_name.clone(), Some(FilterType::Custom("StringFilterInput".into())));
+        // stands in for a filter looking up values in the database
+        context.filter_types.condition_functions.insert(
+            last_name,
+            Box::new(|_, _| {
+                Err(SeaographyError::AsyncGraphQLError(
+                    DbErr::Custom("SELECT last_name FROM blocked_name".into()).into(),
+                ))
+            }),
+        );
+        context
+    };
+}
+
+fn context(hide_internal: bool) -> BuilderContext {
+    BuilderContext {
+        errors: ErrorConfig {
+            enabled: true,
+            hide_internal,
+        },
+        pagination_input: PaginationInputConfig {
+            max_limit: Some(10),
+            ..Default::default()
+        },
+        visibility: VisibilityConfig::default().restrict_field(
+            "Customer",
+            "email",
+            &[OperationType::Read],
+            &["admin"],
+        ),
+        ..Default::default()
+    }
+}
+
+async fn schema(context: &'static BuilderContext) -> Schema {
+    let database = database().await;
+    execute_sql(
+        &database,
+        r#"
+        PRAGMA foreign_keys = ON;
+        CREATE TABLE store (store_id INTEGER PRIMARY KEY);
+        CREATE TABLE customer (
+            customer_id INTEGER PRIMARY KEY,
+            store_id INTEGER NOT NULL REFERENCES store (store_id),
+            first_name TEXT NOT NULL,
+            last_name TEXT NOT NULL,
+            email TEXT UNIQUE,
+            address_id INTEGER NOT NULL,
+            active INTEGER NOT NULL CONSTRAINT active_flag CHECK (active IN (0, 1)),
+            create_date TEXT NOT NULL,
+            last_update TEXT NOT NULL
+        );
+        INSERT INTO store (store_id) VALUES (1);
+        INSERT INTO customer VALUES
+            (1, 1, 'Mary', 'Smith', 'mary@example.com', 1, 1, '2030-01-01 00:00:00', '2030-01-01 00:00:00');
+        "#,
+    )
+    .await;
+
+    common::schema(context, database)
+}
+
+fn create(store_id: i64, email: &str, active: i64) -> String {
+    format!(
+        r#"
+        mutation {{
+          customerCreateOne(data: {{
+            customerId: 2, storeId: {store_id}, firstName: "Linda", lastName: "Williams",
+            email: "{email}", addressId: 1, active: {active},
+            createDate: "2030-01-01 00:00:00 UTC", lastUpdate: "2030-01-01 00:00:00 UTC"
+          }}) {{
+            customerId
+          }}
+        }}
+        "#
+    )
+}
+
+#[tokio::test]
+async fn constraint_violations_are_coded() {
+    let schema = schema(&DEVELOPMENT).await;
+
+    let (message, extensions) =
+        error_with_extensions(schema.execute(create(1, "mary@example.com", 1)).await);
+    assert!(message.contains("UNIQUE constraint failed"), "{message}");
+    assert_eq!(
+        extensions,
+        json!({ "code": "UNIQUE_VIOLATION", "constraint": "customer.email", "entity": "Customer" })
+    );
+
+    let (_, extensions) =
+        error_with_extensions(schema.execute(create(9, "linda@example.com", 1)).await);
+    assert_eq!(
+        extensions,
+        json!({ "code": "FOREIGN_KEY_VIOLATION", "entity": "Customer" })
+    );
+
+    let (_, extensions) =
+        error_with_extensions(schema.execute(create(1, "linda@example.com", 5)).await);
+    assert_eq!(
+        extensions,
+        json!({ "code": "CHECK_VIOLATION", "constraint": "active_flag", "entity": "Customer" })
+    );
+}
+
+#[tokio::test]
+async fn internal_messages_are_hidden() {
+    let schema = schema(&PRODUCTION).await;
+
+    let (message, extensions) =
+        error_with_extensions(schema.execute(create(1, "mary@example.com", 1)).await);
+    assert_eq!(message, "Unique constraint violated.");
+    assert_eq!(extensions["code"], "UNIQUE_VIOLATION");
+    assert_eq!(extensions["constraint"], "customer.email");
+}
+
+#[tokio::test]
+async fn wrapped_database_errors_are_hidden() {
+    let schema = schema(&LOOKUP_FILTER).await;
+
+    let (message, extensions) = error_with_extensions(
+        schema
+            .execute(
+                r#"{ customer(filters: { lastName: { eq: "Smith" } }) { nodes { customerId } } }"#,
+            )
+            .await,
+    );
+    assert_eq!(message, "Internal server error.");
+    assert_eq!(
+        extensions,
+        json!({ "code": "INTERNAL_SERVER_ERROR", "entity": "Customer" })
+    );
+}
+
+#[tokio::test]
+async fn guards_and_limits_are_coded() {
+    let schema = schema(&DEVELOPMENT).await;
+
+    let (_, extensions) =
+        error_with_extensions(schema.execute("{ customer { nodes { email } } }").await);
+    assert_eq!(
+        extensions,
+        json!({ "code": "FORBIDDEN", "entity": "Customer", "field": "email" })
+    );
+
+    let (message, extensions) = error_with_extensions(
+        schema
+            .execute("{ customer(pagination: { offset: { limit: 11, offset: 0 } }) { nodes { customerId } } }")
+            .await,
+    );
+    assert_eq!(
+        message,
+        "Query Error: Requested pagination limit (11) exceeds maximum allowed (10)"
+    );
+    assert_eq!(
+        extensions,
+        json!({ "code": "PAGINATION_LIMIT_EXCEEDED", "entity": "Customer" })
+    );
+}
+
+#[test]
+fn sql_errors_are_classified_across_backends() {
+    let classify =
+        |message: &str| classify_db_error(&DbErr::Exec(RuntimeErr::Internal(message.into())));
+
+    // Postgres
+    assert_eq!(
+        classify(r#"duplicate key value violates unique constraint "customer_email_key""#),
+        (
+            ErrorCode::UniqueViolation,
+            Some("customer_email_key".into())
+        )
+    );
+    assert_eq!(
+        classify(
+            r#"insert or update on table "customer" violates foreign key constraint "customer_store_id_fkey""#
+        ),
+        (
+            ErrorCode::ForeignKeyViolation,
+            Some("customer_store_id_fkey".into())
+        )
+    );
+    assert_eq!(
+        classify(
+            r#"null value in column "first_name" of relation "customer" violates not-null constraint"#
+        ),
+        (ErrorCode::NotNullViolation, Some("first_name".into()))
+    );
+
+    // MySQL
+    assert_eq!(
+        classify("Duplicate entry 'mary@example.com' for key 'customer.idx_email'"),
+        (
+            ErrorCode::UniqueViolation,
+            Some("customer.idx_email".into())
+        )
+    );
+    assert_eq!(
+        classify("Cannot add or update a child row: a foreign key constraint fails (`sakila`.`customer`, CONSTRAINT `fk_customer_store` FOREIGN KEY (`store_id`) REFERENCES `store` (`store_id`))"),
+        (ErrorCode::ForeignKeyViolation, Some("fk_customer_store".into()))
+    );
+    assert_eq!(
+        classify("Check constraint 'active_flag' is violated."),
+        (ErrorCode::CheckViolation, Some("active_flag".into()))
+    );
+
+    // SQLite
+    assert_eq!(
+        classify("NOT NULL constraint failed: customer.first_name"),
+        (
+            ErrorCode::NotNullViolation,
+            Some("customer.first_name".into())
+        )
+    );
+
+    assert_eq!(
+        classify("connection reset"),
+        (ErrorCode::InternalServerError, None)
+    );
+    assert_eq!(
+        classify_db_error(&DbErr::RecordNotFound("customer".into())),
+        (ErrorCode::NotFound, None)
+    );
+}
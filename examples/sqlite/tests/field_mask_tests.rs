@@ -0,0 +1,204 @@
+use async_graphql::{dynamic::*, Value};
+use seaography::{
+    async_graphql, lazy_static, BuilderContext, GuardAction, LifecycleHooks,
+    LifecycleHooksInterface, OperationType,
+};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext {
+            hooks: LifecycleHooks::new(MaskHooks),
+            ..Default::default()
+        };
+        context.entity_object.maskable_fields = vec!["Language.languageId".into()];
+        context
+    };
+    static ref UNDECLARED_CONTEXT : BuilderContext = {
+        BuilderContext {
+            hooks: LifecycleHooks::new(MaskHooks),
+            ..Default::default()
+        }
+    };
+}
+
+struct MaskHooks;
+
+impl LifecycleHooksInterface for MaskHooks {
+    fn field_guard(
+        &self,
+        _ctx: &ResolverContext,
+        entity: &str,
+        field: &str,
+        action: OperationType,
+    ) -> GuardAction {
+        // mutation results are resolved through the basic object
+        match (entity.trim_end_matches("Basic"), field, action) {
+            ("Language", "name", OperationType::Read) => GuardAction::Replace(Value::from("***")),
+            ("Language", "languageId", OperationType::Read) => GuardAction::Mask,
+            ("Customer", "firstName", OperationType::Read) => GuardAction::Mask,
+            ("Store", "customer", OperationType::Read) => GuardAction::Mask,
+            _ => GuardAction::Allow,
+        }
+    }
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_table(&database, language::Entity).await;
+
+    common::schema(&CONTEXT, database)
+}
+
+#[tokio::test]
+async fn masked_fields() {
+    let schema = schema().await;
+
+    // masking applies to reads only, the mutation still writes the masked fields
+    let created = data(
+        schema
+            .execute(
+                r#"
+                mutation {
+                  languageCreateOne(data: { languageId: 1, name: "English", lastUpdate: "2030-01-01 00:00:00 UTC" }) {
+                    name
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+    assert_eq!(created, json!({ "languageCreateOne": { "name": "***" } }));
+
+    assert_eq!(
+        data(schema.execute("{ language { nodes { name } } }").await),
+        json!({ "language": { "nodes": [{ "name": "***" }] } })
+    );
+
+    assert_eq!(
+        error(
+            schema
+                .execute(r#"{ language(filters: { name: { eq: "English" } }) { nodes { name } } }"#)
+                .await
+        ),
+        "Filtering or ordering on masked field `name` is not allowed."
+    );
+
+    assert_eq!(
+        error(
+            schema
+                .execute(
+                    r#"{ language(filters: { or: [{ languageId: { eq: 1 } }] }) { nodes { name } } }"#
+                )
+                .await
+        ),
+        "Filtering or ordering on masked field `languageId` is not allowed."
+    );
+
+    assert_eq!(
+        error(
+            schema
+                .execute("{ language(orderBy: { name: ASC }) { nodes { name } } }")
+                .await
+        ),
+        "Filtering or ordering on masked field `name` is not allowed."
+    );
+
+    assert_eq!(
+        error(
+            schema
+                .execute(r#"mutation { languageDelete(filter: { name: { eq: "English" } }) }"#)
+                .await
+        ),
+        "Filtering or ordering on masked field `name` is not allowed."
+    );
+
+    // unmasked fields can still be filtered on
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    r#"{ language(filters: { lastUpdate: { gt: "2000-01-01 00:00:00 UTC" } }) { nodes { name } } }"#
+                )
+                .await
+        ),
+        json!({ "language": { "nodes": [{ "name": "***" }] } })
+    );
+}
+
+#[tokio::test]
+async fn masked_non_null_fields() {
+    let schema = schema().await;
+    data(
+        schema
+            .execute(
+                r#"
+                mutation {
+                  languageCreateOne(data: { languageId: 1, name: "English", lastUpdate: "2030-01-01 00:00:00 UTC" }) {
+                    name
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+
+    // maskable fields are nullable
+    assert_eq!(
+        data(
+            schema
+                .execute("{ language { nodes { languageId name } } }")
+                .await
+        ),
+        json!({ "language": { "nodes": [{ "languageId": null, "name": "***" }] } })
+    );
+
+    let database = database().await;
+    create_table(&database, language::Entity).await;
+    execute_sql(
+        &database,
+        "INSERT INTO language (language_id, name, last_update) VALUES (1, 'English', '2030-01-01 00:00:00')",
+    )
+    .await;
+    let schema = common::schema(&UNDECLARED_CONTEXT, database);
+
+    assert_eq!(
+        error(
+            schema
+                .execute("{ language { nodes { languageId } } }")
+                .await
+        ),
+        "Field `Language.languageId` is not in `maskable_fields`."
+    );
+}
+
+#[tokio::test]
+async fn having_on_masked_fields() {
+    let schema = common::schema(&CONTEXT, database().await);
+
+    assert_eq!(
+        error(
+            schema
+                .execute(
+                    r#"{ store(having: { customer: { firstName: { eq: "Mary" } } }) { nodes { storeId } } }"#
+                )
+                .await
+        ),
+        "Filtering or ordering on masked field `firstName` is not allowed."
+    );
+
+    assert_eq!(
+        error(
+            schema
+                .execute(
+                    r#"{ store(having: { customer: { customerId: { eq: 1 } } }) { nodes { storeId } } }"#
+                )
+                .await
+        ),
+        "Filtering or ordering on masked field `customer` is not allowed."
+    );
+}
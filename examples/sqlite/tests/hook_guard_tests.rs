@@ -364,6 +364,8 @@ async fn field_guard_mutation() {
         log.calls(),
         vec![
             HookCall::entity_guard("Language", OperationType::Update),
+            // filtering on a masked field is rejected
+            HookCall::field_guard("Language", None, "languageId", OperationType::Read),
             HookCall::field_guard("Language", None, "name", OperationType::Update),
         ]
     );
@@ -406,7 +408,9 @@ async fn entity_watch_mutation() {
         log.calls(),
         vec![
             HookCall::entity_guard("Country", OperationType::Update),
+            HookCall::field_guard("Country", None, "countryId", OperationType::Read),
             HookCall::field_guard("Country", None, "country", OperationType::Update),
+            HookCall::before_active_model_save("Country", OperationType::Update),
             HookCall::entity_watch("Country", OperationType::Update),
             HookCall::field_guard("CountryBasic", None, "countryId", OperationType::Read),
             HookCall::field_guard("CountryBasic", None, "country", OperationType::Read),
@@ -0,0 +1,197 @@
+use async_graphql::dynamic::*;
+use seaography::{
+    async_graphql, lazy_static, BuilderContext, EntityColumnId, EntityCreateBatchMutationConfig,
+    EntityCreateOneMutationConfig, EntityDeleteMutationConfig, EntityUpdateMutationConfig,
+    ValidationRule,
+};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext {
+            entity_create_one_mutation: EntityCreateOneMutationConfig {
+                payload: true,
+                ..Default::default()
+            },
+            entity_create_batch_mutation: EntityCreateBatchMutationConfig {
+                payload: true,
+                ..Default::default()
+            },
+            entity_update_mutation: EntityUpdateMutationConfig {
+                payload: true,
+                ..Default::default()
+            },
+            entity_delete_mutation: EntityDeleteMutationConfig {
+                payload: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        context.validation.rules.insert(
+            EntityColumnId::of::<customer::Entity>(&customer::Column::FirstName),
+            vec![ValidationRule::Length {
+                min: Some(1),
+                max: None,
+            }],
+        );
+        context
+    };
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    execute_sql(
+        &database,
+        r#"
+        CREATE TABLE customer (
+            customer_id INTEGER PRIMARY KEY,
+            store_id INTEGER NOT NULL,
+            first_name TEXT NOT NULL,
+            last_name TEXT NOT NULL,
+            email TEXT UNIQUE,
+            address_id INTEGER NOT NULL,
+            active INTEGER NOT NULL,
+            create_date TEXT NOT NULL,
+            last_update TEXT NOT NULL
+        );
+        INSERT INTO customer VALUES
+            (1, 1, 'Mary', 'Smith', 'mary@example.com', 1, 1, '2030-01-01 00:00:00', '2030-01-01 00:00:00');
+        "#,
+    )
+    .await;
+
+    common::schema(&CONTEXT, database)
+}
+
+fn customer(id: i64, first_name: &str, email: &str) -> String {
+    format!(
+        r#"{{
+            customerId: {id}, storeId: 1, firstName: "{first_name}", lastName: "Williams",
+            email: "{email}", addressId: 1, active: 1,
+            createDate: "2030-01-01 00:00:00 UTC", lastUpdate: "2030-01-01 00:00:00 UTC"
+        }}"#
+    )
+}
+
+const PAYLOAD: &str = "{ records { customerId } affectedCount userErrors { field message code } }";
+
+async fn count(schema: &Schema) -> serde_json::Value {
+    data(
+        schema
+            .execute("{ customer { paginationInfo { total } } }")
+            .await,
+    )["customer"]["paginationInfo"]["total"]
+        .clone()
+}
+
+#[tokio::test]
+async fn create_returns_payload() {
+    let schema = schema().await;
+
+    assert_eq!(
+        data(
+            schema
+                .execute(format!(
+                    "mutation {{ customerCreateOne(data: {}) {PAYLOAD} }}",
+                    customer(2, "Linda", "linda@example.com")
+                ))
+                .await
+        ),
+        json!({
+            "customerCreateOne": {
+                "records": [{ "customerId": 2 }],
+                "affectedCount": 1,
+                "userErrors": []
+            }
+        })
+    );
+
+    assert_eq!(
+        data(
+            schema
+                .execute(format!(
+                    "mutation {{ customerCreateOne(data: {}) {PAYLOAD} }}",
+                    customer(3, "", "patricia@example.com")
+                ))
+                .await
+        ),
+        json!({
+            "customerCreateOne": {
+                "records": [],
+                "affectedCount": 0,
+                "userErrors": [{
+                    "field": "firstName",
+                    "message": "must be at least 1 characters long",
+                    "code": "VALIDATION_FAILED"
+                }]
+            }
+        })
+    );
+    assert_eq!(count(&schema).await, json!(2));
+}
+
+#[tokio::test]
+async fn failed_batch_is_rolled_back() {
+    let schema = schema().await;
+
+    let response = data(
+        schema
+            .execute(format!(
+                "mutation {{ customerCreateBatch(data: [{}, {}]) {PAYLOAD} }}",
+                customer(2, "Linda", "linda@example.com"),
+                customer(3, "Patricia", "mary@example.com")
+            ))
+            .await,
+    );
+    let payload = &response["customerCreateBatch"];
+    assert_eq!(payload["records"], json!([]));
+    assert_eq!(payload["affectedCount"], json!(0));
+    assert_eq!(payload["userErrors"][0]["code"], json!("UNIQUE_VIOLATION"));
+
+    assert_eq!(count(&schema).await, json!(1));
+}
+
+#[tokio::test]
+async fn update_and_delete_return_payloads() {
+    let schema = schema().await;
+
+    assert_eq!(
+        data(
+            schema
+                .execute(format!(
+                    r#"mutation {{
+                      customerUpdate(data: {{ lastName: "Jones" }}, filter: {{ customerId: {{ eq: 1 }} }}) {PAYLOAD}
+                    }}"#
+                ))
+                .await
+        ),
+        json!({
+            "customerUpdate": {
+                "records": [{ "customerId": 1 }],
+                "affectedCount": 1,
+                "userErrors": []
+            }
+        })
+    );
+
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    r#"mutation {
+                      customerDelete(filter: { customerId: { eq: 1 } }) {
+                        affectedCount
+                        userErrors { code }
+                      }
+                    }"#
+                )
+                .await
+        ),
+        json!({ "customerDelete": { "affectedCount": 1, "userErrors": [] } })
+    );
+    assert_eq!(count(&schema).await, json!(0));
+}
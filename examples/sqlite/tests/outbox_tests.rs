@@ -0,0 +1,120 @@
+use async_graphql::{dynamic::*, Response};
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, DbErr};
+use seaography::{async_graphql, drain_outbox, lazy_static, BuilderContext, OperationType};
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        context.outbox.table = Some("seaography_outbox".into());
+        context
+    };
+}
+
+async fn schema() -> (Schema, DatabaseConnection) {
+    let database = Database::connect("sqlite://sakila.db").await.unwrap();
+    database
+        .execute(&CONTEXT.outbox.table_create_statement().unwrap())
+        .await
+        .unwrap();
+    let schema = seaography_sqlite_example::query_root::schema_builder(
+        &CONTEXT,
+        database.clone(),
+        None,
+        None,
+    )
+    .finish()
+    .unwrap();
+    (schema, database)
+}
+
+fn data(response: Response) -> serde_json::Value {
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    response.data.into_json().unwrap()
+}
+
+#[tokio::test]
+async fn mutations_write_to_outbox() {
+    let (schema, database) = schema().await;
+
+    // start from an empty outbox
+    drain_outbox(&CONTEXT, &database, 100, |_| async { Ok::<_, DbErr>(()) })
+        .await
+        .unwrap();
+
+    let created = data(
+        schema
+            .execute(
+                r#"
+                mutation {
+                  languageCreateOne(data: { languageId: 1000, name: "Klingon", lastUpdate: "2030-01-01 00:00:00 UTC" }) {
+                    languageId
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+    let language_id = created["languageCreateOne"]["languageId"].as_i64().unwrap();
+
+    data(
+        schema
+            .execute(format!(
+                r#"
+                mutation {{
+                  languageUpdate(data: {{ name: "tlhIngan" }}, filter: {{ languageId: {{ eq: {language_id} }} }}) {{
+                    languageId
+                  }}
+                }}
+                "#
+            ))
+            .await,
+    );
+
+    data(
+        schema
+            .execute(format!(
+                r#"
+                mutation {{
+                  languageDelete(filter: {{ languageId: {{ eq: {language_id} }} }})
+                }}
+                "#
+            ))
+            .await,
+    );
+
+    // a failing handler stops the drain and keeps the event
+    let failed = drain_outbox(&CONTEXT, &database, 1, |_| async {
+        Err(DbErr::Custom("unavailable".into()))
+    })
+    .await;
+    assert!(failed.is_err());
+
+    let mut events = Vec::new();
+    let delivered = drain_outbox(&CONTEXT, &database, 2, |event| {
+        events.push(event);
+        async { Ok::<_, DbErr>(()) }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(delivered, 3);
+    assert!(events.windows(2).all(|pair| pair[0].id < pair[1].id));
+    assert!(events.iter().all(|event| event.event.entity == "Language"));
+    assert_eq!(
+        events
+            .iter()
+            .map(|event| event.event.operation)
+            .collect::<Vec<_>>(),
+        vec![
+            OperationType::Create,
+            OperationType::Update,
+            OperationType::Delete
+        ]
+    );
+    assert!(events.iter().all(|event| event.event.keys.len() == 1));
+
+    let delivered = drain_outbox(&CONTEXT, &database, 2, |_| async { Ok::<_, DbErr>(()) })
+        .await
+        .unwrap();
+    assert_eq!(delivered, 0);
+}
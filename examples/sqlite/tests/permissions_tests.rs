@@ -0,0 +1,184 @@
+use async_graphql::{dynamic::*, Request};
+use sea_orm::{sea_query::Condition, ColumnTrait};
+use seaography::{
+    async_graphql, lazy_static, BuilderContext, GuardAction, LifecycleHooks,
+    LifecycleHooksInterface, OperationType, PermissionsQueryConfig, UserContext,
+};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        BuilderContext {
+            hooks: LifecycleHooks::new(CustomerHooks),
+            permissions_query: PermissionsQueryConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    };
+}
+
+struct CustomerHooks;
+
+fn is_manager(ctx: &ResolverContext) -> bool {
+    ctx.data_opt::<UserContext>()
+        .is_some_and(|user| user.has_role("manager"))
+}
+
+impl LifecycleHooksInterface for CustomerHooks {
+    fn entity_guard(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        action: OperationType,
+    ) -> GuardAction {
+        if entity == "Customer" && action == OperationType::Delete && !is_manager(ctx) {
+            GuardAction::Block(None)
+        } else {
+            GuardAction::Allow
+        }
+    }
+
+    fn field_guard(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        field: &str,
+        action: OperationType,
+    ) -> GuardAction {
+        match (entity, field, action) {
+            ("Customer", "email", OperationType::Read) if !is_manager(ctx) => GuardAction::Mask,
+            ("Customer", "active", OperationType::Update) => GuardAction::Block(None),
+            _ => GuardAction::Allow,
+        }
+    }
+
+    fn entity_filter(
+        &self,
+        _ctx: &ResolverContext,
+        entity: &str,
+        action: OperationType,
+    ) -> Option<Condition> {
+        (entity == "Customer" && action == OperationType::Update)
+            .then(|| Condition::all().add(customer::Column::StoreId.eq(1)))
+    }
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_customers(&database).await;
+
+    common::schema(&CONTEXT, database)
+}
+
+fn field<'a>(permissions: &'a serde_json::Value, name: &str) -> &'a serde_json::Value {
+    permissions["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|field| field["name"] == name)
+        .unwrap()
+}
+
+const QUERY: &str = r#"
+    {
+      _permissions {
+        customer {
+          entity read create update delete
+          fields { name read create update }
+        }
+      }
+    }
+"#;
+
+#[tokio::test]
+async fn permissions_follow_guards() {
+    let schema = schema().await;
+
+    let permissions = data(schema.execute(QUERY).await);
+    let customer = &permissions["_permissions"]["customer"];
+    assert_eq!(customer["entity"], "Customer");
+    assert_eq!(
+        [
+            &customer["read"],
+            &customer["create"],
+            &customer["update"],
+            &customer["delete"]
+        ],
+        [&json!(true), &json!(true), &json!(true), &json!(false)]
+    );
+    assert_eq!(
+        field(customer, "firstName"),
+        &json!({ "name": "firstName", "read": true, "create": true, "update": true })
+    );
+    assert_eq!(
+        field(customer, "email"),
+        &json!({ "name": "email", "read": false, "create": true, "update": true })
+    );
+    assert_eq!(
+        field(customer, "active"),
+        &json!({ "name": "active", "read": true, "create": true, "update": false })
+    );
+
+    let manager = UserContext::new(1i64).with_roles(["manager"]);
+    let permissions = data(schema.execute(Request::new(QUERY).data(manager)).await);
+    let customer = &permissions["_permissions"]["customer"];
+    assert_eq!(customer["delete"], json!(true));
+    assert_eq!(field(customer, "email")["read"], json!(true));
+}
+
+#[tokio::test]
+async fn permissions_of_a_record() {
+    let schema = schema().await;
+
+    let record =
+        |id: i64| format!("{{ _permissions {{ customer(id: {id}) {{ read update delete }} }} }}");
+
+    // only customers of store 1 can be updated
+    assert_eq!(
+        data(schema.execute(record(1)).await),
+        json!({ "_permissions": { "customer": { "read": true, "update": true, "delete": false } } })
+    );
+    assert_eq!(
+        data(schema.execute(record(2)).await),
+        json!({ "_permissions": { "customer": { "read": true, "update": false, "delete": false } } })
+    );
+    assert_eq!(
+        data(schema.execute(record(3)).await),
+        json!({ "_permissions": { "customer": { "read": false, "update": false, "delete": false } } })
+    );
+}
+
+#[tokio::test]
+async fn permissions_of_a_record_of_a_composite_key() {
+    let schema = schema().await;
+
+    // composite primary keys cannot be given as `id`
+    assert_eq!(
+        data(
+            schema
+                .execute("{ _permissions { filmActor { read } } }")
+                .await
+        ),
+        json!({ "_permissions": { "filmActor": { "read": true } } })
+    );
+    assert!(error(
+        schema
+            .execute("{ _permissions { filmActor(id: 1) { read } } }")
+            .await
+    )
+    .starts_with(r#"Unknown argument "id""#));
+
+    // database errors are not taken as a missing record
+    assert!(error(
+        schema
+            .execute("{ _permissions { language(id: 1) { read } } }")
+            .await
+    )
+    .contains("no such table: language"));
+}
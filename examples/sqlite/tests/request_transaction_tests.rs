@@ -0,0 +1,243 @@
+use async_graphql::dynamic::*;
+use sea_orm::{entity::prelude::async_trait, DatabaseConnection, EntityTrait};
+use seaography::{
+    async_graphql, lazy_static, BuilderContext, EntityChanges, EntityUpdateMutationConfig,
+    ErrorConfig, LifecycleHooks, LifecycleHooksInterface, OperationType, RequestTransactionConfig,
+    WatchContext,
+};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+use std::sync::Mutex;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        BuilderContext {
+            request_transaction: RequestTransactionConfig { enabled: true },
+            errors: ErrorConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            hooks: LifecycleHooks::new(WatchHooks),
+            entity_update_mutation: EntityUpdateMutationConfig {
+                payload: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    };
+    static ref WATCHED: Mutex<Vec<(String, bool)>> = Mutex::new(Vec::new());
+}
+
+struct WatchHooks;
+
+#[async_trait::async_trait]
+impl LifecycleHooksInterface for WatchHooks {
+    async fn entity_watch_changes(
+        &self,
+        ctx: &WatchContext<'_>,
+        _entity: &str,
+        _action: OperationType,
+        changes: &EntityChanges<'_>,
+    ) {
+        let Some(created) = changes.after_models::<customer::Model>() else {
+            return;
+        };
+        // the single connection is free again once the request transaction is committed
+        let database = ctx.data_opt::<DatabaseConnection>().unwrap();
+        for customer in created {
+            let committed = customer::Entity::find_by_id(customer.customer_id)
+                .one(database)
+                .await
+                .unwrap()
+                .is_some();
+            WATCHED
+                .lock()
+                .unwrap()
+                .push((customer.first_name.clone(), committed));
+        }
+    }
+}
+
+fn watched(first_name: &str) -> Vec<bool> {
+    WATCHED
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(name, _)| name == first_name)
+        .map(|(_, committed)| *committed)
+        .collect()
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_customers(&database).await;
+    execute_sql(
+        &database,
+        "CREATE TABLE store (store_id INTEGER PRIMARY KEY, manager_staff_id INTEGER NOT NULL, address_id INTEGER NOT NULL, last_update TEXT NOT NULL);
+         INSERT INTO store VALUES (1, 1, 1, '2030-01-01 00:00:00');",
+    )
+    .await;
+
+    common::schema(&CONTEXT, database)
+}
+
+async fn customers(schema: &Schema) -> serde_json::Value {
+    data(
+        schema
+            .execute(
+                "{ customer(orderBy: { customerId: ASC }) { nodes { customerId firstName } } }",
+            )
+            .await,
+    )["customer"]["nodes"]
+        .clone()
+}
+
+fn create(alias: &str, customer_id: i32, first_name: &str, dry_run: bool) -> String {
+    format!(
+        r#"{alias}: customerCreateOne(
+          data: {{ customerId: {customer_id}, storeId: 1, firstName: "{first_name}", lastName: "Jones", addressId: 1, active: 1, createDate: "2030-01-01T00:00:00Z", lastUpdate: "2030-01-01T00:00:00Z" }}
+          dryRun: {dry_run}
+        ) {{ customerId }}"#
+    )
+}
+
+#[tokio::test]
+async fn mutation_fields_are_committed_together() {
+    let schema = schema().await;
+
+    assert_eq!(
+        data(
+            schema
+                .execute(format!(
+                    "mutation {{ {} {} {} }}",
+                    create("a", 3, "Barbara", false),
+                    create("b", 4, "Susan", true),
+                    create("c", 5, "Betty", false),
+                ))
+                .await
+        ),
+        json!({
+            "a": { "customerId": 3 },
+            "b": { "customerId": 4 },
+            "c": { "customerId": 5 }
+        })
+    );
+
+    // the dry run only rolled back its own savepoint
+    assert_eq!(
+        customers(&schema).await,
+        json!([
+            { "customerId": 1, "firstName": "Mary" },
+            { "customerId": 2, "firstName": "Linda" },
+            { "customerId": 3, "firstName": "Barbara" },
+            { "customerId": 5, "firstName": "Betty" }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn failing_field_rolls_back_the_request() {
+    let schema = schema().await;
+
+    let response = schema
+        .execute(format!(
+            "mutation {{ {} {} }}",
+            create("a", 3, "Barbara", false),
+            create("b", 1, "Susan", false),
+        ))
+        .await;
+    assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+    assert_eq!(
+        response.errors[0]
+            .extensions
+            .as_ref()
+            .and_then(|extensions| extensions.get("code"))
+            .cloned(),
+        Some(async_graphql::Value::from("UNIQUE_VIOLATION"))
+    );
+
+    let unchanged = json!([
+        { "customerId": 1, "firstName": "Mary" },
+        { "customerId": 2, "firstName": "Linda" }
+    ]);
+    assert_eq!(customers(&schema).await, unchanged);
+
+    // user errors of payloads fail the request as well
+    assert_eq!(
+        data(
+            schema
+                .execute(format!(
+                    r#"mutation {{
+                      {}
+                      b: customerUpdate(data: {{ customerId: 1 }}, filter: {{ customerId: {{ eq: 2 }} }}) {{
+                        affectedCount
+                        userErrors {{ code }}
+                      }}
+                    }}"#,
+                    create("a", 3, "Barbara", false),
+                ))
+                .await
+        ),
+        json!({
+            "a": { "customerId": 3 },
+            "b": { "affectedCount": 0, "userErrors": [{ "code": "UNIQUE_VIOLATION" }] }
+        })
+    );
+    assert_eq!(customers(&schema).await, unchanged);
+}
+
+#[tokio::test]
+async fn relations_of_mutation_results_are_read_in_the_transaction() {
+    let schema = schema().await;
+
+    // `login` returns the full customer object, its relations load after the first field
+    assert_eq!(
+        data(
+            schema
+                .execute(format!(
+                    "mutation {{ {} login {{ customerId store {{ storeId customer {{ nodes {{ customerId }} }} }} }} }}",
+                    create("a", 3, "Deborah", false),
+                ))
+                .await
+        ),
+        json!({
+            "a": { "customerId": 3 },
+            "login": {
+                "customerId": 1,
+                "store": {
+                    "storeId": 1,
+                    "customer": { "nodes": [{ "customerId": 1 }, { "customerId": 3 }] }
+                }
+            }
+        })
+    );
+}
+
+#[tokio::test]
+async fn watch_hooks_run_after_the_commit() {
+    let schema = schema().await;
+
+    data(
+        schema
+            .execute(format!(
+                "mutation {{ {} }}",
+                create("a", 3, "Dorothy", false)
+            ))
+            .await,
+    );
+    assert_eq!(watched("Dorothy"), vec![true]);
+
+    // the changes of a rolled back request are not watched
+    let response = schema
+        .execute(format!(
+            "mutation {{ {} {} }}",
+            create("a", 4, "Sandra", false),
+            create("b", 1, "Ashley", false),
+        ))
+        .await;
+    assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+    assert_eq!(watched("Sandra"), Vec::<bool>::new());
+}
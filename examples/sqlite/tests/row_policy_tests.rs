@@ -0,0 +1,244 @@
+use async_graphql::{dynamic::*, Request, Response};
+use seaography::{
+    async_graphql, lazy_static, policy_column, BuilderContext, OperationType, PolicyValue,
+    RowPolicies, UserContext,
+};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+
+mod common;
+use common::*;
+
+/// the store of the signed in staff member
+struct Store(i64);
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext {
+            row_policies: RowPolicies::default()
+                .add::<customer::Entity>(
+                    &[
+                        OperationType::Read,
+                        OperationType::Create,
+                        OperationType::Update,
+                        OperationType::Delete,
+                    ],
+                    policy_column::<customer::Entity>(customer::Column::StoreId).eq(
+                        PolicyValue::context(|ctx| ctx.data_opt::<Store>().map(|store| store.0.into())),
+                    ),
+                )
+                .add::<rental::Entity>(
+                    &[OperationType::Read],
+                    policy_column::<rental::Entity>(rental::Column::StaffId)
+                        .eq(PolicyValue::user_id())
+                        .or(policy_column::<rental::Entity>(rental::Column::ReturnDate).is_null()),
+                ),
+            ..Default::default()
+        };
+        context.entity_update_batch_mutation.enabled = true;
+        context
+    };
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_customers(&database).await;
+    create_table(&database, rental::Entity).await;
+    execute_sql(
+        &database,
+        r#"
+        INSERT INTO rental (rental_id, rental_date, inventory_id, customer_id, return_date, staff_id, last_update) VALUES
+            (1, '2030-01-01 00:00:00', 1, 1, '2030-01-02 00:00:00', 1, '2030-01-01 00:00:00'),
+            (2, '2030-01-01 00:00:00', 2, 1, '2030-01-02 00:00:00', 2, '2030-01-01 00:00:00'),
+            (3, '2030-01-01 00:00:00', 3, 2, NULL, 2, '2030-01-01 00:00:00');
+        "#,
+    )
+    .await;
+
+    common::schema(&CONTEXT, database)
+}
+
+async fn execute(schema: &Schema, query: &str) -> Response {
+    schema
+        .execute(
+            Request::new(query)
+                .data(Store(1))
+                .data(UserContext::new(1i64)),
+        )
+        .await
+}
+
+#[tokio::test]
+async fn row_policies_restrict_reads() {
+    let schema = schema().await;
+
+    // rental 2 was returned to another staff member
+    assert_eq!(
+        data(
+            execute(
+                &schema,
+                "{ customer { nodes { customerId rental { nodes { rentalId } } } } }"
+            )
+            .await
+        ),
+        json!({
+            "customer": {
+                "nodes": [{ "customerId": 1, "rental": { "nodes": [{ "rentalId": 1 }] } }]
+            }
+        })
+    );
+
+    // customer 2 belongs to another store
+    assert_eq!(
+        data(
+            execute(
+                &schema,
+                "{ rental { nodes { rentalId customer { customerId } } } }"
+            )
+            .await
+        ),
+        json!({
+            "rental": {
+                "nodes": [
+                    { "rentalId": 1, "customer": { "customerId": 1 } },
+                    { "rentalId": 3, "customer": null }
+                ]
+            }
+        })
+    );
+
+    // rows outside the policy can not be matched through `having` either
+    assert_eq!(
+        data(
+            execute(
+                &schema,
+                "{ customer(having: { rental: { rentalId: { eq: 2 } } }) { nodes { customerId } } }"
+            )
+            .await
+        ),
+        json!({ "customer": { "nodes": [] } })
+    );
+    assert_eq!(
+        data(
+            execute(
+                &schema,
+                "{ customer(having: { rental: { rentalId: { eq: 1 } } }) { nodes { customerId } } }"
+            )
+            .await
+        ),
+        json!({ "customer": { "nodes": [{ "customerId": 1 }] } })
+    );
+
+    // a missing context value matches no row
+    assert_eq!(
+        data(
+            schema
+                .execute("{ customer { nodes { customerId } } }")
+                .await
+        ),
+        json!({ "customer": { "nodes": [] } })
+    );
+}
+
+#[tokio::test]
+async fn row_policies_restrict_mutations() {
+    let schema = schema().await;
+
+    let create = |customer_id: i64, store_id: i64| {
+        format!(
+            r#"
+            mutation {{
+              customerCreateOne(data: {{
+                customerId: {customer_id}, storeId: {store_id}, firstName: "Patricia", lastName: "Johnson",
+                addressId: 1, active: 1, createDate: "2030-01-01 00:00:00 UTC", lastUpdate: "2030-01-01 00:00:00 UTC"
+              }}) {{
+                customerId
+              }}
+            }}
+            "#
+        )
+    };
+
+    let response = execute(&schema, &create(3, 2)).await;
+    assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+    assert_eq!(response.errors[0].message, "Row policy violated.");
+
+    assert_eq!(
+        data(execute(&schema, &create(3, 1)).await),
+        json!({ "customerCreateOne": { "customerId": 3 } })
+    );
+
+    assert_eq!(
+        data(
+            execute(
+                &schema,
+                r#"mutation { customerUpdate(data: { active: 0 }) { customerId } }"#
+            )
+            .await
+        ),
+        json!({ "customerUpdate": [{ "customerId": 1 }, { "customerId": 3 }] })
+    );
+
+    assert_eq!(
+        data(execute(&schema, "mutation { customerDelete }").await),
+        json!({ "customerDelete": 2 })
+    );
+
+    // customer 2 of the other store is left untouched
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    Request::new("{ customer { nodes { customerId active } } }").data(Store(2))
+                )
+                .await
+        ),
+        json!({ "customer": { "nodes": [{ "customerId": 2, "active": 1 }] } })
+    );
+}
+
+#[tokio::test]
+async fn row_policies_check_written_rows() {
+    let schema = schema().await;
+
+    // inserted rows have to be readable
+    let response = execute(
+        &schema,
+        r#"
+        mutation {
+          rentalCreateOne(data: {
+            rentalId: 4, rentalDate: "2030-01-01 00:00:00 UTC", inventoryId: 4, customerId: 1,
+            returnDate: "2030-01-02 00:00:00 UTC", staffId: 2, lastUpdate: "2030-01-01 00:00:00 UTC"
+          }) {
+            rentalId
+          }
+        }
+        "#,
+    )
+    .await;
+    assert_eq!(error(response), "Row policy violated.");
+
+    // updated rows cannot be moved out of the policies
+    let response = execute(
+        &schema,
+        r#"mutation { customerUpdate(data: { storeId: 2 }, filter: { customerId: { eq: 1 } }) { customerId } }"#,
+    )
+    .await;
+    assert_eq!(error(response), "Row policy violated.");
+
+    let response = execute(
+        &schema,
+        r#"mutation { customerUpdateBatch(data: [{ customerId: 1, changes: { storeId: 2 } }]) { customerId } }"#,
+    )
+    .await;
+    assert_eq!(error(response), "Row policy violated.");
+
+    assert_eq!(
+        data(execute(&schema, "{ customer { nodes { customerId storeId } } }").await),
+        json!({ "customer": { "nodes": [{ "customerId": 1, "storeId": 1 }] } })
+    );
+    assert_eq!(
+        data(execute(&schema, "{ rental { nodes { rentalId } } }").await),
+        json!({ "rental": { "nodes": [{ "rentalId": 1 }, { "rentalId": 3 }] } })
+    );
+}
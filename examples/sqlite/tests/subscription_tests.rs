@@ -0,0 +1,430 @@
+use async_graphql::{
+    dynamic::*,
+    futures_util::{poll, StreamExt},
+    Executor, Response,
+};
+use sea_orm::{entity::prelude::async_trait, ColumnTrait, Condition, Database};
+use seaography::{
+    async_graphql, lazy_static, BuilderContext, ChangeEventBus, EntityChangeEvent,
+    InMemoryChangeEventBus, LifecycleHooks, LifecycleHooksInterface, LiveQueryExecutor,
+    OperationType, SharedChangeEventBus, TenancyConfig, TenantContext, UserContext,
+};
+use seaography_sqlite_example::entities::*;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+mod common;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        context.entity_changed_subscription.enabled = true;
+        context
+    };
+    static ref STORE_CONTEXT : BuilderContext = {
+        let mut context = BuilderContext {
+            hooks: LifecycleHooks::new(StoreHooks),
+            ..Default::default()
+        };
+        context.entity_changed_subscription.enabled = true;
+        context
+    };
+    static ref TENANT_CONTEXT : BuilderContext = {
+        let mut context = BuilderContext {
+            tenancy: TenancyConfig {
+                column: Some("store_id".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        context.entity_changed_subscription.enabled = true;
+        context
+    };
+}
+
+/// scopes customers to the store of the user
+struct StoreHooks;
+
+impl LifecycleHooksInterface for StoreHooks {
+    fn entity_filter(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        _action: OperationType,
+    ) -> Option<Condition> {
+        if entity != "Customer" {
+            return None;
+        }
+        let store_id = ctx
+            .data_opt::<UserContext>()
+            .map(|user| user.user_id.clone())
+            .unwrap_or(0i64.into());
+        Some(Condition::all().add(customer::Column::StoreId.eq(store_id)))
+    }
+}
+
+async fn schema() -> Schema {
+    let database = Database::connect("sqlite://sakila.db").await.unwrap();
+    seaography_sqlite_example::query_root::schema_builder(&CONTEXT, database, None, None)
+        .finish()
+        .unwrap()
+}
+
+fn data(response: Response) -> serde_json::Value {
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    response.data.into_json().unwrap()
+}
+
+#[tokio::test]
+async fn entity_changes_are_streamed() {
+    let schema = schema().await;
+
+    let mut all_changes = schema.execute_stream(
+        r#"
+        subscription {
+          languageChanged {
+            operation
+            models {
+              name
+            }
+          }
+        }
+        "#,
+    );
+    let mut filtered_changes = schema.execute_stream(
+        r#"
+        subscription {
+          languageChanged(filter: { name: { eq: "Elvish" } }) {
+            operation
+            models {
+              name
+            }
+          }
+        }
+        "#,
+    );
+
+    // polled once to subscribe before the mutations run
+    assert!(poll!(all_changes.next()).is_pending());
+    assert!(poll!(filtered_changes.next()).is_pending());
+
+    let created = data(
+        schema
+            .execute(
+                r#"
+                mutation {
+                  languageCreateOne(data: { languageId: 1002, name: "Klingon", lastUpdate: "2030-01-01 00:00:00 UTC" }) {
+                    languageId
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+    let language_id = created["languageCreateOne"]["languageId"].as_i64().unwrap();
+
+    assert_eq!(
+        data(all_changes.next().await.unwrap()),
+        serde_json::json!({
+            "languageChanged": { "operation": "CREATE", "models": [{ "name": "Klingon" }] }
+        })
+    );
+    // the created row is re-read before the update, and does not match the filter
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), filtered_changes.next())
+            .await
+            .is_err()
+    );
+
+    data(
+        schema
+            .execute(format!(
+                r#"
+                mutation {{
+                  languageUpdate(data: {{ name: "Elvish" }}, filter: {{ languageId: {{ eq: {language_id} }} }}) {{
+                    languageId
+                  }}
+                }}
+                "#
+            ))
+            .await,
+    );
+
+    let expected = serde_json::json!({
+        "languageChanged": { "operation": "UPDATE", "models": [{ "name": "Elvish" }] }
+    });
+    assert_eq!(data(all_changes.next().await.unwrap()), expected);
+    assert_eq!(data(filtered_changes.next().await.unwrap()), expected);
+
+    data(
+        schema
+            .execute(format!(
+                r#"
+                mutation {{
+                  languageDelete(filter: {{ languageId: {{ eq: {language_id} }} }})
+                }}
+                "#
+            ))
+            .await,
+    );
+
+    assert_eq!(
+        data(all_changes.next().await.unwrap()),
+        serde_json::json!({
+            "languageChanged": { "operation": "DELETE", "models": [{ "name": "Elvish" }] }
+        })
+    );
+}
+
+/// forwards to an in-process bus and records the serialized events
+struct RecordingBus {
+    inner: InMemoryChangeEventBus,
+    published: Mutex<Vec<serde_json::Value>>,
+}
+
+#[async_trait::async_trait]
+impl ChangeEventBus for RecordingBus {
+    async fn publish(&self, event: EntityChangeEvent) {
+        self.published.lock().unwrap().push(event.to_json());
+        self.inner.publish(event).await;
+    }
+
+    fn subscribe(
+        &self,
+    ) -> async_graphql::futures_util::stream::BoxStream<'static, EntityChangeEvent> {
+        self.inner.subscribe()
+    }
+}
+
+#[tokio::test]
+async fn custom_change_event_bus() {
+    let bus = Arc::new(RecordingBus {
+        inner: InMemoryChangeEventBus::new(16),
+        published: Mutex::new(Vec::new()),
+    });
+
+    let database = Database::connect("sqlite://sakila.db").await.unwrap();
+    let schema =
+        seaography_sqlite_example::query_root::schema_builder(&CONTEXT, database, None, None)
+            .data(bus.clone() as SharedChangeEventBus)
+            .finish()
+            .unwrap();
+
+    data(
+        schema
+            .execute(
+                r#"
+                mutation {
+                  languageUpdate(data: { name: "English" }, filter: { languageId: { eq: 1 } }) {
+                    languageId
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+
+    let published = bus.published.lock().unwrap().clone();
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0]["entity"], "Language");
+    assert_eq!(published[0]["operation"], "UPDATE");
+    assert_eq!(
+        EntityChangeEvent::from_json(&published[0])
+            .unwrap()
+            .keys
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn live_queries_rerun_on_changes() {
+    let bus: SharedChangeEventBus = Arc::new(InMemoryChangeEventBus::new(16));
+
+    let database = Database::connect("sqlite://sakila.db").await.unwrap();
+    let schema =
+        seaography_sqlite_example::query_root::schema_builder(&CONTEXT, database, None, None)
+            .data(bus.clone())
+            .finish()
+            .unwrap();
+    let executor = LiveQueryExecutor::new(schema.clone(), bus).debounce(Duration::from_millis(10));
+
+    let mut results = executor.execute_stream(
+        async_graphql::Request::new(
+            r#"
+            query @live {
+              language(filters: { languageId: { eq: 2 } }) {
+                nodes {
+                  name
+                }
+              }
+            }
+            "#,
+        ),
+        None,
+    );
+
+    let first = data(results.next().await.unwrap());
+    assert_eq!(first["language"]["nodes"][0]["name"], "Italian");
+
+    // changes of entities that were not read do not re-run the query
+    data(
+        schema
+            .execute(
+                r#"
+                mutation {
+                  actorUpdate(data: { firstName: "PENELOPE" }, filter: { actorId: { eq: 1 } }) {
+                    actorId
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(poll!(results.next()).is_pending());
+
+    data(
+        schema
+            .execute(
+                r#"
+                mutation {
+                  languageUpdate(data: { name: "Italiano" }, filter: { languageId: { eq: 2 } }) {
+                    languageId
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+
+    let second = data(results.next().await.unwrap());
+    assert_eq!(second["language"]["nodes"][0]["name"], "Italiano");
+
+    // non-live operations are passed through
+    let mut plain = executor.execute_stream(
+        async_graphql::Request::new(
+            "{ language(filters: { languageId: { eq: 2 } }) { nodes { name } } }",
+        ),
+        None,
+    );
+    assert!(plain.next().await.is_some());
+    assert!(plain.next().await.is_none());
+}
+
+#[tokio::test]
+async fn live_queries_rerun_with_the_request_data() {
+    let bus: SharedChangeEventBus = Arc::new(InMemoryChangeEventBus::new(16));
+
+    let database = common::database().await;
+    common::create_customers(&database).await;
+    let schema =
+        seaography_sqlite_example::query_root::schema_builder(&STORE_CONTEXT, database, None, None)
+            .data(bus.clone())
+            .finish()
+            .unwrap();
+    let executor = LiveQueryExecutor::new(schema.clone(), bus).debounce(Duration::from_millis(10));
+
+    let live_query = "query @live { customer { nodes { firstName } } }";
+
+    let mut results = executor.execute_stream(
+        async_graphql::Request::new(live_query).data(UserContext::new(1i64)),
+        None,
+    );
+    assert_eq!(
+        data(results.next().await.unwrap())["customer"]["nodes"],
+        serde_json::json!([{ "firstName": "Mary" }])
+    );
+
+    data(
+        schema
+            .execute(
+                async_graphql::Request::new(
+                    r#"
+                    mutation {
+                      customerUpdate(data: { firstName: "Anne" }, filter: { customerId: { eq: 1 } }) {
+                        customerId
+                      }
+                    }
+                    "#,
+                )
+                .data(UserContext::new(1i64)),
+            )
+            .await,
+    );
+
+    // the re-execution is still scoped to the user's store
+    assert_eq!(
+        data(results.next().await.unwrap())["customer"]["nodes"],
+        serde_json::json!([{ "firstName": "Anne" }])
+    );
+
+    // request data that cannot be carried is rejected
+    let mut rejected =
+        executor.execute_stream(async_graphql::Request::new(live_query).data(7u32), None);
+    assert!(!rejected.next().await.unwrap().errors.is_empty());
+    assert!(rejected.next().await.is_none());
+}
+
+#[tokio::test]
+async fn live_queries_rerun_with_the_tenant() {
+    let bus: SharedChangeEventBus = Arc::new(InMemoryChangeEventBus::new(16));
+
+    let database = common::database().await;
+    common::create_customers(&database).await;
+    let schema = seaography_sqlite_example::query_root::schema_builder(
+        &TENANT_CONTEXT,
+        database,
+        None,
+        None,
+    )
+    .data(bus.clone())
+    .finish()
+    .unwrap();
+    let executor = LiveQueryExecutor::new(schema.clone(), bus).debounce(Duration::from_millis(10));
+
+    let mut results = executor.execute_stream(
+        async_graphql::Request::new("query @live { customer { nodes { firstName } } }")
+            .data(TenantContext::new(1i64)),
+        None,
+    );
+    assert_eq!(
+        data(results.next().await.unwrap())["customer"]["nodes"],
+        serde_json::json!([{ "firstName": "Mary" }])
+    );
+
+    data(
+        schema
+            .execute(
+                async_graphql::Request::new(
+                    r#"mutation { customerUpdate(data: { firstName: "Anne" }) { customerId } }"#,
+                )
+                .data(TenantContext::new(2i64)),
+            )
+            .await,
+    );
+
+    // the re-execution is still scoped to the tenant
+    assert_eq!(
+        data(results.next().await.unwrap())["customer"]["nodes"],
+        serde_json::json!([{ "firstName": "Mary" }])
+    );
+
+    data(
+        schema
+            .execute(
+                async_graphql::Request::new(
+                    r#"mutation { customerUpdate(data: { firstName: "Anne" }) { customerId } }"#,
+                )
+                .data(TenantContext::new(1i64)),
+            )
+            .await,
+    );
+
+    assert_eq!(
+        data(results.next().await.unwrap())["customer"]["nodes"],
+        serde_json::json!([{ "firstName": "Anne" }])
+    );
+}
@@ -0,0 +1,212 @@
+use async_graphql::{dynamic::*, Request, Response};
+use sea_orm::{ConnectionTrait, DbBackend};
+use seaography::{async_graphql, lazy_static, BuilderContext, TenancyConfig, TenantContext};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        BuilderContext {
+            tenancy: TenancyConfig {
+                column: Some("store_id".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    };
+    static ref AUDIT_LOG_CONTEXT : BuilderContext = {
+        let mut context = BuilderContext {
+            tenancy: TenancyConfig {
+                column: Some("store_id".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        context.audit_log.enabled = true;
+        context.audit_log.query = true;
+        context
+    };
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_customers(&database).await;
+    create_table(&database, rental::Entity).await;
+    execute_sql(
+        &database,
+        r#"
+        INSERT INTO rental (rental_id, rental_date, inventory_id, customer_id, return_date, staff_id, last_update) VALUES
+            (1, '2030-01-01 00:00:00', 1, 1, NULL, 1, '2030-01-01 00:00:00'),
+            (2, '2030-01-01 00:00:00', 2, 2, NULL, 1, '2030-01-01 00:00:00');
+        "#,
+    )
+    .await;
+
+    common::schema(&CONTEXT, database)
+}
+
+async fn execute(schema: &Schema, query: &str) -> Response {
+    schema
+        .execute(Request::new(query).data(TenantContext::new(1i64)))
+        .await
+}
+
+const CREATE: &str = r#"
+    mutation {
+      customerCreateOne(data: {
+        customerId: 3, firstName: "Patricia", lastName: "Johnson", addressId: 1, active: 1,
+        createDate: "2030-01-01 00:00:00 UTC", lastUpdate: "2030-01-01 00:00:00 UTC"
+      }) {
+        customerId
+        storeId
+      }
+    }
+"#;
+
+#[tokio::test]
+async fn tenant_scopes_reads() {
+    let schema = schema().await;
+
+    assert_eq!(
+        data(execute(&schema, "{ customer { nodes { customerId } } }").await),
+        json!({ "customer": { "nodes": [{ "customerId": 1 }] } })
+    );
+
+    // rentals are shared, their customers are not
+    assert_eq!(
+        data(
+            execute(
+                &schema,
+                "{ rental { nodes { rentalId customer { customerId } } } }"
+            )
+            .await
+        ),
+        json!({
+            "rental": {
+                "nodes": [
+                    { "rentalId": 1, "customer": { "customerId": 1 } },
+                    { "rentalId": 2, "customer": null }
+                ]
+            }
+        })
+    );
+
+    assert_eq!(
+        data(
+            execute(
+                &schema,
+                r#"{ rental(having: { customer: { firstName: { eq: "Linda" } } }) { nodes { rentalId } } }"#
+            )
+            .await
+        ),
+        json!({ "rental": { "nodes": [] } })
+    );
+
+    // without tenant, scoped entities are empty
+    assert_eq!(
+        data(
+            schema
+                .execute("{ customer { nodes { customerId } } }")
+                .await
+        ),
+        json!({ "customer": { "nodes": [] } })
+    );
+}
+
+#[tokio::test]
+async fn tenant_scopes_mutations() {
+    let schema = schema().await;
+
+    assert_eq!(
+        error(schema.execute(CREATE).await),
+        "No tenant in the request."
+    );
+
+    // the tenant column is not part of the input, it is set from the request
+    assert_eq!(
+        data(execute(&schema, CREATE).await),
+        json!({ "customerCreateOne": { "customerId": 3, "storeId": 1 } })
+    );
+    assert_eq!(
+        error(
+            execute(
+                &schema,
+                "mutation { customerUpdate(data: { storeId: 2 }) { customerId } }"
+            )
+            .await
+        ),
+        r#"Invalid value for argument "data", unknown field "storeId" of type "CustomerUpdateInput""#
+    );
+
+    assert_eq!(
+        data(
+            execute(
+                &schema,
+                "mutation { customerUpdate(data: { active: 0 }) { customerId } }"
+            )
+            .await
+        ),
+        json!({ "customerUpdate": [{ "customerId": 1 }, { "customerId": 3 }] })
+    );
+
+    assert_eq!(
+        data(execute(&schema, "mutation { customerDelete }").await),
+        json!({ "customerDelete": 2 })
+    );
+
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    Request::new("{ customer { nodes { customerId active } } }")
+                        .data(TenantContext::new(2i64))
+                )
+                .await
+        ),
+        json!({ "customer": { "nodes": [{ "customerId": 2, "active": 1 }] } })
+    );
+}
+
+#[tokio::test]
+async fn tenant_scopes_the_audit_log() {
+    let database = database().await;
+    create_customers(&database).await;
+    database
+        .execute(
+            &AUDIT_LOG_CONTEXT
+                .audit_log
+                .table_create_statement(DbBackend::Sqlite),
+        )
+        .await
+        .unwrap();
+    let schema = common::schema(&AUDIT_LOG_CONTEXT, database);
+
+    let deactivate = |tenant_id: i64| {
+        schema.execute(
+            Request::new("mutation { customerUpdate(data: { active: 0 }) { customerId } }")
+                .data(TenantContext::new(tenant_id)),
+        )
+    };
+    data(deactivate(1).await);
+    data(deactivate(2).await);
+
+    let audit_log = "{ auditLog { nodes { tenant primaryKey } } }";
+    for tenant_id in [1i64, 2] {
+        let nodes = &data(
+            schema
+                .execute(Request::new(audit_log).data(TenantContext::new(tenant_id)))
+                .await,
+        )["auditLog"]["nodes"];
+        assert_eq!(nodes.as_array().unwrap().len(), 1);
+        assert_eq!(nodes[0]["tenant"], json!(tenant_id.to_string()));
+    }
+
+    // without tenant, the audit log is empty
+    assert_eq!(
+        data(schema.execute(audit_log).await),
+        json!({ "auditLog": { "nodes": [] } })
+    );
+}
@@ -0,0 +1,119 @@
+use async_graphql::dynamic::*;
+use sea_orm::Database;
+use seaography::{async_graphql, lazy_static, BuilderContext};
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        context.entity_update_batch_mutation.enabled = true;
+        context
+    };
+}
+
+async fn schema() -> Schema {
+    let database = Database::connect("sqlite://sakila.db").await.unwrap();
+    seaography_sqlite_example::query_root::schema_builder(&CONTEXT, database, None, None)
+        .finish()
+        .unwrap()
+}
+
+async fn language_names(schema: &Schema) -> serde_json::Value {
+    let response = schema
+        .execute(
+            r#"
+            {
+              language(filters: { languageId: { lte: 2 } }, orderBy: { languageId: ASC }) {
+                nodes {
+                  name
+                }
+              }
+            }
+            "#,
+        )
+        .await;
+
+    response.data.into_json().unwrap()["language"]["nodes"].clone()
+}
+
+#[tokio::test]
+async fn update_batch_applies_per_row_changes() {
+    let schema = schema().await;
+
+    let before = language_names(&schema).await;
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              languageUpdateBatch(data: [
+                { languageId: 1, changes: { name: "Row 1" } }
+                { languageId: 2, changes: { name: "Row 2" } }
+              ]) {
+                languageId
+                name
+              }
+            }
+            "#,
+        )
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "languageUpdateBatch": [
+                { "languageId": 1, "name": "Row 1" },
+                { "languageId": 2, "name": "Row 2" },
+            ]
+        })
+    );
+
+    let response = schema
+        .execute(format!(
+            r#"
+            mutation {{
+              languageUpdateBatch(data: [
+                {{ languageId: 1, changes: {{ name: {} }} }}
+                {{ languageId: 2, changes: {{ name: {} }} }}
+              ]) {{
+                languageId
+              }}
+            }}
+            "#,
+            before[0]["name"], before[1]["name"]
+        ))
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+}
+
+#[tokio::test]
+async fn update_batch_rolls_back_on_missing_row() {
+    let schema = schema().await;
+
+    let before = language_names(&schema).await;
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              languageUpdateBatch(data: [
+                { languageId: 1, changes: { name: "Rolled back" } }
+                { languageId: 9999, changes: { name: "Missing" } }
+              ]) {
+                languageId
+              }
+            }
+            "#,
+        )
+        .await;
+
+    assert_eq!(response.errors.len(), 1);
+    let extensions = response.errors[0].extensions.as_ref().unwrap();
+    assert_eq!(
+        extensions.get("code"),
+        Some(&async_graphql::Value::from("NOT_FOUND"))
+    );
+    assert_eq!(extensions.get("row"), Some(&async_graphql::Value::from(1)));
+    assert_eq!(language_names(&schema).await, before);
+}
@@ -0,0 +1,224 @@
+use async_graphql::dynamic::*;
+use sea_orm::Database;
+use seaography::{async_graphql, lazy_static, BuilderContext, EntityColumnId, ValidationRule};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+
+mod common;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        context.update_operation_input.enabled = true;
+        context
+    };
+    static ref RANGE_CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        context.update_operation_input.enabled = true;
+        context.validation.rules.insert(
+            EntityColumnId::of::<customer::Entity>(&customer::Column::Active),
+            vec![ValidationRule::Range {
+                min: Some(0.0),
+                max: Some(1.0),
+            }],
+        );
+        context
+    };
+}
+
+async fn schema() -> Schema {
+    let database = Database::connect("sqlite://sakila.db").await.unwrap();
+    seaography_sqlite_example::query_root::schema_builder(&CONTEXT, database, None, None)
+        .finish()
+        .unwrap()
+}
+
+async fn rental_duration(schema: &Schema) -> i64 {
+    let response = schema
+        .execute(
+            r#"
+            {
+              film(filters: { filmId: { eq: 1 } }) {
+                nodes {
+                  rentalDuration
+                }
+              }
+            }
+            "#,
+        )
+        .await;
+
+    response.data.into_json().unwrap()["film"]["nodes"][0]["rentalDuration"]
+        .as_i64()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn increment_and_decrement() {
+    let schema = schema().await;
+
+    let before = rental_duration(&schema).await;
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              filmUpdate(
+                data: { rentalDuration: { increment: 2 } }
+                filter: { filmId: { eq: 1 } }
+              ) {
+                rentalDuration
+              }
+            }
+            "#,
+        )
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(rental_duration(&schema).await, before + 2);
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              filmUpdate(
+                data: { rentalDuration: { decrement: 2 } }
+                filter: { filmId: { eq: 1 } }
+              ) {
+                rentalDuration
+              }
+            }
+            "#,
+        )
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(rental_duration(&schema).await, before);
+}
+
+#[tokio::test]
+async fn exactly_one_operation() {
+    let schema = schema().await;
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              filmUpdate(
+                data: { rentalDuration: { increment: 1, decrement: 1 } }
+                filter: { filmId: { eq: 1 } }
+              ) {
+                rentalDuration
+              }
+            }
+            "#,
+        )
+        .await;
+
+    assert!(!response.errors.is_empty());
+}
+
+#[tokio::test]
+async fn set_null_requires_nullable_column() {
+    let schema = schema().await;
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              filmUpdate(
+                data: { rentalDuration: { setNull: true } }
+                filter: { filmId: { eq: 1 } }
+              ) {
+                rentalDuration
+              }
+            }
+            "#,
+        )
+        .await;
+
+    assert!(!response.errors.is_empty());
+}
+
+#[tokio::test]
+async fn set_null_is_only_offered_on_nullable_columns() {
+    let schema = common::schema(&CONTEXT, common::database().await);
+
+    let fields = |type_name: &str| {
+        let schema = &schema;
+        let query = format!(r#"{{ __type(name: "{type_name}") {{ inputFields {{ name }} }} }}"#);
+        async move { common::data(schema.execute(query).await)["__type"]["inputFields"].clone() }
+    };
+
+    assert_eq!(
+        fields("IntUpdateOperationInput").await,
+        json!([
+            { "name": "set" },
+            { "name": "increment" },
+            { "name": "decrement" },
+            { "name": "multiply" }
+        ])
+    );
+    assert_eq!(
+        fields("NullableIntUpdateOperationInput").await,
+        json!([
+            { "name": "set" },
+            { "name": "increment" },
+            { "name": "decrement" },
+            { "name": "multiply" },
+            { "name": "setNull" }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn range_rules_take_plain_values() {
+    let database = common::database().await;
+    common::create_customers(&database).await;
+    let schema = common::schema(&RANGE_CONTEXT, database);
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              customerUpdate(data: { active: { increment: 5 } }, filter: { customerId: { eq: 1 } }) {
+                active
+              }
+            }
+            "#,
+        )
+        .await;
+    assert!(!response.errors.is_empty());
+
+    let (_, extensions) = common::error_with_extensions(
+        schema
+            .execute(
+                r#"
+                mutation {
+                  customerUpdate(data: { active: 5 }, filter: { customerId: { eq: 1 } }) {
+                    active
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+    assert_eq!(extensions["code"], json!("VALIDATION_FAILED"));
+
+    assert_eq!(
+        common::data(
+            schema
+                .execute(
+                    r#"
+                    mutation {
+                      customerUpdate(data: { active: 0 }, filter: { customerId: { eq: 1 } }) {
+                        active
+                      }
+                    }
+                    "#,
+                )
+                .await
+        ),
+        json!({ "customerUpdate": [{ "active": 0 }] })
+    );
+}
@@ -0,0 +1,140 @@
+use async_graphql::{dynamic::*, Request};
+use seaography::{
+    async_graphql, lazy_static, policy_column, BuilderContext, GuardAction, LifecycleHooks,
+    LifecycleHooksInterface, OperationType, PolicyValue, RowPolicies, UserContext,
+};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        BuilderContext {
+            hooks: LifecycleHooks::new(RoleHooks),
+            row_policies: RowPolicies::default().add::<customer::Entity>(
+                &[OperationType::Read, OperationType::Delete],
+                policy_column::<customer::Entity>(customer::Column::StoreId)
+                    .eq(PolicyValue::claim("store")),
+            ),
+            ..Default::default()
+        }
+    };
+}
+
+struct RoleHooks;
+
+impl LifecycleHooksInterface for RoleHooks {
+    fn entity_guard(
+        &self,
+        ctx: &ResolverContext,
+        _entity: &str,
+        action: OperationType,
+    ) -> GuardAction {
+        let is_manager = ctx
+            .data_opt::<UserContext>()
+            .is_some_and(|user| user.has_role("manager"));
+
+        if action == OperationType::Delete && !is_manager {
+            GuardAction::Block(Some("Managers only".into()))
+        } else {
+            GuardAction::Allow
+        }
+    }
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_customers(&database).await;
+
+    common::schema(&CONTEXT, database)
+}
+
+#[tokio::test]
+async fn claims_and_roles_are_available_to_policies_and_hooks() {
+    let schema = schema().await;
+
+    let clerk = UserContext::new("auth0|clerk").with_claim("store", json!(2));
+    let manager = clerk.clone().with_roles(["manager"]);
+
+    assert_eq!(
+        data(
+            schema
+                .execute(Request::new("{ customer { nodes { customerId } } }").data(clerk.clone()))
+                .await
+        ),
+        json!({ "customer": { "nodes": [{ "customerId": 2 }] } })
+    );
+
+    let response = schema
+        .execute(Request::new("mutation { customerDelete }").data(clerk))
+        .await;
+    assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+    assert_eq!(response.errors[0].message, "Managers only");
+
+    assert_eq!(
+        data(
+            schema
+                .execute(Request::new("mutation { customerDelete }").data(manager))
+                .await
+        ),
+        json!({ "customerDelete": 1 })
+    );
+
+    // a missing claim matches no row
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    Request::new("{ customer { nodes { customerId } } }")
+                        .data(UserContext::new("auth0|clerk"))
+                )
+                .await
+        ),
+        json!({ "customer": { "nodes": [] } })
+    );
+}
+
+#[test]
+fn users_map_onto_rbac_users() {
+    assert_eq!(UserContext::new(7i64).rbac_user_id(), Some(7));
+    assert_eq!(UserContext::new(7i32).rbac_user_id(), Some(7));
+    assert_eq!(UserContext::new("auth0|7").rbac_user_id(), None);
+    assert_eq!(
+        UserContext::new("auth0|7").rbac_user(3).rbac_user_id(),
+        Some(3)
+    );
+
+    let role_users = [("admin", 1), ("editor", 2)];
+    assert_eq!(
+        UserContext::new("auth0|7")
+            .with_roles(["viewer", "editor"])
+            .rbac_user_by_role(&role_users)
+            .rbac_user_id(),
+        Some(2)
+    );
+    assert_eq!(
+        UserContext::new(7i64)
+            .with_roles(["viewer"])
+            .rbac_user_by_role(&role_users)
+            .rbac_user_id(),
+        Some(7)
+    );
+}
+
+#[cfg(feature = "rbac")]
+#[tokio::test]
+async fn users_without_rbac_user_are_rejected() {
+    use seaography::DatabaseContext;
+
+    let database = database().await;
+    let error = database
+        .restricted(Some(&UserContext::new("auth0|7")))
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        "RBAC error: user `auth0|7` has no RBAC user id"
+    );
+}
@@ -0,0 +1,101 @@
+use async_graphql::{dynamic::*, Response};
+use sea_orm::Database;
+use seaography::{async_graphql, lazy_static, BuilderContext, EntityColumnId, ValidationRule};
+use seaography_sqlite_example::entities::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        context.validation.rules.insert(
+            EntityColumnId::of::<language::Entity>(&language::Column::Name),
+            vec![ValidationRule::Length {
+                min: Some(1),
+                max: Some(20),
+            }],
+        );
+        context.validation.rules.insert(
+            EntityColumnId::of::<film_text::Entity>(&film_text::Column::Title),
+            vec![ValidationRule::custom(|value| match value {
+                sea_orm::Value::String(Some(title)) if title.to_uppercase() != *title => {
+                    Err("must be upper case".into())
+                }
+                _ => Ok(()),
+            })],
+        );
+        context.validation.rules.insert(
+            EntityColumnId::of::<film_text::Entity>(&film_text::Column::Description),
+            vec![ValidationRule::Url],
+        );
+        context
+    };
+}
+
+async fn schema() -> Schema {
+    let database = Database::connect("sqlite://sakila.db").await.unwrap();
+    seaography_sqlite_example::query_root::schema_builder(&CONTEXT, database, None, None)
+        .finish()
+        .unwrap()
+}
+
+fn extensions(response: &Response) -> serde_json::Value {
+    let extensions = response.errors[0].extensions.as_ref().unwrap();
+    serde_json::to_value(extensions).unwrap()
+}
+
+#[tokio::test]
+async fn create_with_empty_name_fails() {
+    let schema = schema().await;
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              languageCreateOne(data: { languageId: 1003, name: "", lastUpdate: "2030-01-01 00:00:00 UTC" }) {
+                languageId
+              }
+            }
+            "#,
+        )
+        .await;
+
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(
+        extensions(&response),
+        serde_json::json!({
+            "code": "VALIDATION_FAILED",
+            "entity": "Language",
+            "fields": [
+                { "field": "name", "message": "must be at least 1 characters long" }
+            ]
+        })
+    );
+}
+
+#[tokio::test]
+async fn update_reports_every_failing_field() {
+    let schema = schema().await;
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              filmTextUpdate(
+                data: { title: "lower case", description: "not a url" }
+                filter: { filmId: { eq: 1 } }
+              ) {
+                filmId
+              }
+            }
+            "#,
+        )
+        .await;
+
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(
+        extensions(&response)["fields"],
+        serde_json::json!([
+            { "field": "title", "message": "must be upper case" },
+            { "field": "description", "message": "must be a valid URL" },
+        ])
+    );
+}
@@ -0,0 +1,156 @@
+use async_graphql::{dynamic::*, Response};
+use seaography::{async_graphql, lazy_static, BuilderContext, EntityColumnId};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+
+mod common;
+use common::*;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        // `active` stands in for an integer version counter
+        context
+            .entity_update_mutation
+            .version_columns
+            .insert(EntityColumnId::of::<customer::Entity>(&customer::Column::Active));
+        context
+    };
+    static ref TIMESTAMP_CONTEXT : BuilderContext = {
+        let mut context = BuilderContext::default();
+        context
+            .entity_update_mutation
+            .version_columns
+            .insert(EntityColumnId::of::<customer::Entity>(&customer::Column::LastUpdate));
+        context
+    };
+}
+
+async fn schema() -> Schema {
+    let database = database().await;
+    create_customers(&database).await;
+
+    common::schema(&CONTEXT, database)
+}
+
+fn error_code(response: &Response) -> Option<String> {
+    response.errors.first().and_then(|error| {
+        error
+            .extensions
+            .as_ref()
+            .and_then(|extensions| extensions.get("code"))
+            .and_then(|code| match code {
+                async_graphql::Value::String(code) => Some(code.clone()),
+                _ => None,
+            })
+    })
+}
+
+fn update(customer_id: i64, expected_version: i64) -> String {
+    format!(
+        r#"
+        mutation {{
+          customerUpdate(
+            data: {{ firstName: "Anne" }}
+            filter: {{ customerId: {{ eq: {customer_id} }} }}
+            expectedVersion: {expected_version}
+          ) {{
+            customerId
+            active
+          }}
+        }}
+        "#
+    )
+}
+
+#[tokio::test]
+async fn version_column_is_not_updatable() {
+    let schema = schema().await;
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              customerUpdate(
+                data: { active: 5 }
+                filter: { customerId: { eq: 1 } }
+                expectedVersion: 1
+              ) {
+                customerId
+              }
+            }
+            "#,
+        )
+        .await;
+
+    assert!(!response.errors.is_empty());
+    assert_eq!(error_code(&response), None);
+}
+
+#[tokio::test]
+async fn expected_version_is_required() {
+    let schema = schema().await;
+
+    let response = schema
+        .execute(
+            r#"
+            mutation {
+              customerUpdate(
+                data: { firstName: "Anne" }
+                filter: { customerId: { eq: 1 } }
+              ) {
+                customerId
+              }
+            }
+            "#,
+        )
+        .await;
+
+    assert!(!response.errors.is_empty());
+}
+
+#[tokio::test]
+async fn version_is_incremented() {
+    let schema = schema().await;
+
+    assert_eq!(
+        data(schema.execute(update(1, 1)).await),
+        json!({ "customerUpdate": [{ "customerId": 1, "active": 2 }] })
+    );
+
+    // the previous version is stale now, even within the same second
+    let response = schema.execute(update(1, 1)).await;
+    assert_eq!(error_code(&response).as_deref(), Some("CONFLICT"));
+
+    assert_eq!(
+        data(schema.execute(update(1, 2)).await),
+        json!({ "customerUpdate": [{ "customerId": 1, "active": 3 }] })
+    );
+}
+
+#[tokio::test]
+async fn stale_version_is_a_conflict() {
+    let schema = schema().await;
+
+    let response = schema.execute(update(1, 0)).await;
+
+    assert_eq!(error_code(&response).as_deref(), Some("CONFLICT"));
+}
+
+#[tokio::test]
+async fn missing_record_is_not_a_conflict() {
+    let schema = schema().await;
+
+    assert_eq!(
+        data(schema.execute(update(9999, 1)).await),
+        json!({ "customerUpdate": [] })
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "only integer columns are supported")]
+async fn timestamp_version_column_is_rejected() {
+    let database = database().await;
+
+    common::schema(&TIMESTAMP_CONTEXT, database);
+}
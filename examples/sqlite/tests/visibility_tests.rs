@@ -0,0 +1,190 @@
+use async_graphql::{dynamic::*, Request};
+use seaography::{
+    async_graphql, lazy_static, BuilderContext, OperationType, UserContext, VisibilityConfig,
+};
+use seaography_sqlite_example::entities::*;
+use serde_json::json;
+
+mod common;
+use common::*;
+
+fn visibility() -> VisibilityConfig {
+    VisibilityConfig::default()
+        .restrict_entity(
+            "Staff",
+            &[
+                OperationType::Read,
+                OperationType::Create,
+                OperationType::Update,
+                OperationType::Delete,
+            ],
+            &["admin"],
+        )
+        .restrict_entity(
+            "Language",
+            &[
+                OperationType::Create,
+                OperationType::Update,
+                OperationType::Delete,
+            ],
+            &["admin"],
+        )
+        .restrict_field("Customer", "email", &[OperationType::Read], &["admin"])
+}
+
+lazy_static::lazy_static! {
+    static ref FULL: BuilderContext = BuilderContext {
+        visibility: visibility(),
+        ..Default::default()
+    };
+    static ref PUBLIC: BuilderContext = BuilderContext {
+        visibility: visibility().for_roles(&[]),
+        ..Default::default()
+    };
+}
+
+async fn schema(context: &'static BuilderContext) -> Schema {
+    let database = database().await;
+    disable_foreign_keys(&database).await;
+    create_table(&database, customer::Entity).await;
+    create_table(&database, staff::Entity).await;
+    execute_sql(
+        &database,
+        r#"
+        INSERT INTO customer (customer_id, store_id, first_name, last_name, email, address_id, active, create_date, last_update) VALUES
+            (1, 1, 'Mary', 'Smith', 'mary@example.com', 1, 1, '2030-01-01 00:00:00', '2030-01-01 00:00:00');
+        "#,
+    )
+    .await;
+
+    common::schema(context, database)
+}
+
+fn names(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["name"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+#[tokio::test]
+async fn public_schema_is_pruned() {
+    let schema = schema(&PUBLIC).await;
+
+    let introspection = data(
+        schema
+            .execute(
+                r#"
+                {
+                  __schema {
+                    queryType { fields { name } }
+                    mutationType { fields { name } }
+                  }
+                  staffConnection: __type(name: "StaffConnection") { name }
+                  customer: __type(name: "Customer") { fields { name } }
+                  customerFilter: __type(name: "CustomerFilterInput") { inputFields { name } }
+                  customerOrder: __type(name: "CustomerOrderInput") { inputFields { name } }
+                  rental: __type(name: "Rental") { fields { name } }
+                  rentalHaving: __type(name: "RentalHavingInput") { inputFields { name } }
+                }
+                "#,
+            )
+            .await,
+    );
+
+    let queries = names(&introspection["__schema"]["queryType"]["fields"]);
+    assert!(queries.contains(&"customer".to_owned()));
+    assert!(queries.contains(&"language".to_owned()));
+    assert!(!queries.contains(&"staff".to_owned()));
+
+    let mutations = names(&introspection["__schema"]["mutationType"]["fields"]);
+    assert!(mutations.contains(&"customerCreateOne".to_owned()));
+    assert!(mutations
+        .iter()
+        .all(|mutation| !mutation.starts_with("language") && !mutation.starts_with("staff")));
+
+    // unreferenced types are left out of introspection
+    assert_eq!(introspection["staffConnection"], json!(null));
+
+    for (name, fields) in [
+        ("customer", "fields"),
+        ("customerFilter", "inputFields"),
+        ("customerOrder", "inputFields"),
+    ] {
+        let fields = names(&introspection[name][fields]);
+        assert!(fields.contains(&"firstName".to_owned()), "{name}");
+        assert!(!fields.contains(&"email".to_owned()), "{name}");
+    }
+
+    assert!(!names(&introspection["rental"]["fields"]).contains(&"staff".to_owned()));
+    assert!(!names(&introspection["rentalHaving"]["inputFields"]).contains(&"staff".to_owned()));
+}
+
+#[tokio::test]
+async fn full_schema_enforces_visibility() {
+    let schema = schema(&FULL).await;
+
+    let introspection = data(
+        schema
+            .execute(r#"{ __type(name: "Customer") { fields { name } } }"#)
+            .await,
+    );
+    assert!(names(&introspection["__type"]["fields"]).contains(&"email".to_owned()));
+
+    assert_eq!(
+        error(schema.execute("{ staff { nodes { staffId } } }").await),
+        "Entity guard triggered."
+    );
+    assert_eq!(
+        error(
+            schema
+                .execute("{ customer { nodes { customerId email } } }")
+                .await
+        ),
+        "Field guard triggered."
+    );
+    assert_eq!(
+        error(
+            schema
+                .execute(
+                    r#"{ customer(filters: { email: { eq: "mary@example.com" } }) { nodes { customerId } } }"#
+                )
+                .await
+        ),
+        "Filtering or ordering on field `email` is not allowed."
+    );
+    assert_eq!(
+        error(
+            schema
+                .execute(r#"mutation { languageDelete(filter: { languageId: { eq: 1 } }) }"#)
+                .await
+        ),
+        "Entity guard triggered."
+    );
+
+    let admin = UserContext::new(1i64).with_roles(["admin"]);
+
+    assert_eq!(
+        data(
+            schema
+                .execute(Request::new("{ staff { nodes { staffId } } }").data(admin.clone()))
+                .await
+        ),
+        json!({ "staff": { "nodes": [] } })
+    );
+    assert_eq!(
+        data(
+            schema
+                .execute(
+                    Request::new(
+                        r#"{ customer(filters: { email: { eq: "mary@example.com" } }) { nodes { customerId email } } }"#
+                    )
+                    .data(admin)
+                )
+                .await
+        ),
+        json!({ "customer": { "nodes": [{ "customerId": 1, "email": "mary@example.com" }] } })
+    );
+}
@@ -0,0 +1,160 @@
+use async_graphql::{
+    dynamic::{ResolverContext, Schema},
+    Response,
+};
+use sea_orm::{entity::prelude::async_trait, Database};
+use seaography::{
+    async_graphql, lazy_static, BuilderContext, EntityChanges, LifecycleHooks,
+    LifecycleHooksInterface, OperationType, WatchContext,
+};
+use seaography_sqlite_example::entities::*;
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    static ref CONTEXT : BuilderContext = {
+        BuilderContext {
+            hooks: LifecycleHooks::new(WatchHooks),
+            ..Default::default()
+        }
+    };
+    static ref CHANGES: Mutex<Vec<Change>> = Mutex::new(Vec::new());
+}
+
+/// (action, keys, names before, names after)
+type Change = (
+    OperationType,
+    Vec<String>,
+    Option<Vec<String>>,
+    Option<Vec<String>>,
+);
+
+struct WatchHooks;
+
+#[async_trait::async_trait]
+impl LifecycleHooksInterface for WatchHooks {
+    async fn entity_watch_changes(
+        &self,
+        _ctx: &WatchContext<'_>,
+        entity: &str,
+        action: OperationType,
+        changes: &EntityChanges<'_>,
+    ) {
+        if entity != "Language" {
+            return;
+        }
+        let names = |models: &[language::Model]| {
+            models
+                .iter()
+                .map(|language| language.name.clone())
+                .collect::<Vec<_>>()
+        };
+        CHANGES.lock().unwrap().push((
+            action,
+            changes.keys.clone(),
+            changes.before_models::<language::Model>().map(names),
+            changes.after_models::<language::Model>().map(names),
+        ));
+    }
+
+    fn watch_pre_images(
+        &self,
+        _ctx: &ResolverContext,
+        entity: &str,
+        _action: OperationType,
+    ) -> bool {
+        entity == "Language"
+    }
+}
+
+async fn schema() -> Schema {
+    let database = Database::connect("sqlite://sakila.db").await.unwrap();
+    seaography_sqlite_example::query_root::schema_builder(&CONTEXT, database, None, None)
+        .finish()
+        .unwrap()
+}
+
+fn data(response: Response) -> serde_json::Value {
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    response.data.into_json().unwrap()
+}
+
+#[tokio::test]
+async fn watch_receives_changes() {
+    let schema = schema().await;
+
+    let created = data(
+        schema
+            .execute(
+                r#"
+                mutation {
+                  languageCreateOne(data: { languageId: 1001, name: "Klingon", lastUpdate: "2030-01-01 00:00:00 UTC" }) {
+                    languageId
+                  }
+                }
+                "#,
+            )
+            .await,
+    );
+    let language_id = created["languageCreateOne"]["languageId"].as_i64().unwrap();
+
+    data(
+        schema
+            .execute(format!(
+                r#"
+                mutation {{
+                  languageUpdate(data: {{ name: "tlhIngan" }}, filter: {{ languageId: {{ eq: {language_id} }} }}) {{
+                    languageId
+                  }}
+                }}
+                "#
+            ))
+            .await,
+    );
+
+    data(
+        schema
+            .execute(format!(
+                r#"
+                mutation {{
+                  languageDelete(filter: {{ languageId: {{ eq: {language_id} }} }})
+                }}
+                "#
+            ))
+            .await,
+    );
+
+    let changes = CHANGES.lock().unwrap().clone();
+    assert_eq!(changes.len(), 3);
+
+    let keys = &changes[0].1;
+    assert_eq!(keys.len(), 1);
+    assert!(changes.iter().all(|change| &change.1 == keys));
+
+    assert_eq!(
+        changes[0],
+        (
+            OperationType::Create,
+            keys.clone(),
+            None,
+            Some(vec!["Klingon".to_owned()])
+        )
+    );
+    assert_eq!(
+        changes[1],
+        (
+            OperationType::Update,
+            keys.clone(),
+            Some(vec!["Klingon".to_owned()]),
+            Some(vec!["tlhIngan".to_owned()])
+        )
+    );
+    assert_eq!(
+        changes[2],
+        (
+            OperationType::Delete,
+            keys.clone(),
+            Some(vec!["tlhIngan".to_owned()]),
+            None
+        )
+    );
+}
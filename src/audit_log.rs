@@ -0,0 +1,257 @@
+//! The built-in audit log. When enabled, generated mutations record one row per
+//! affected record into the configured table, within their transaction. The read-only
+//! query of the table is only added on request, as it is guarded by the hooks alone.
+//! The table can be created with `AuditLogConfig::table_create_statement`.
+
+use std::{
+    collections::BTreeMap,
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_graphql::dynamic::ResolverContext;
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{value::sea_value_to_json_value, Alias, ExprTrait, TableCreateStatement},
+    ActiveValue, Condition, DbBackend, Iterable, Schema,
+};
+
+use crate::{
+    entity_change_key, operation_name, BuilderContext, EntityObjectBuilder, OperationType,
+    TenantContext, UserContext,
+};
+
+/// The configuration of the built-in audit log
+pub struct AuditLogConfig {
+    /// record mutations into the audit log table
+    pub enabled: bool,
+    /// name of the audit log table, the same for all schemas of a process
+    pub table: String,
+    /// add the query named after the table, `auditLog` by default. It has no access control
+    /// of its own: block it with `entity_guard` or scope it with `entity_filter` for its entity
+    pub query: bool,
+}
+
+impl std::default::Default for AuditLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            table: "audit_log".into(),
+            query: false,
+        }
+    }
+}
+
+impl AuditLogConfig {
+    /// used to get the statement creating the audit log table
+    pub fn table_create_statement(&self, backend: DbBackend) -> TableCreateStatement {
+        Schema::new(backend)
+            .create_table_from_entity(Entity)
+            .table(Alias::new(&self.table))
+            .to_owned()
+    }
+}
+
+static TABLE_NAME: OnceLock<&'static str> = OnceLock::new();
+
+/// used to bind the audit log entity to the configured table when a schema is built
+pub(crate) fn bind_table_name(context: &'static BuilderContext) {
+    let table = TABLE_NAME.get_or_init(|| &context.audit_log.table);
+    if *table != context.audit_log.table {
+        panic!(
+            "Audit log table `{}` differs from table `{}` of another schema",
+            context.audit_log.table, table
+        );
+    }
+}
+
+#[derive(Copy, Clone, Default, Debug, DeriveEntity)]
+pub struct Entity;
+
+impl EntityName for Entity {
+    fn table_name(&self) -> &'static str {
+        TABLE_NAME.get().copied().unwrap_or("audit_log")
+    }
+}
+
+/// A change of one record
+#[derive(Clone, Debug, PartialEq, DeriveModel, DeriveActiveModel)]
+pub struct Model {
+    pub id: i64,
+    /// the user id of the `UserContext` the mutation ran with
+    pub actor: Option<String>,
+    /// the tenant id of the `TenantContext` the mutation ran with, when tenancy is configured
+    pub tenant: Option<String>,
+    /// milliseconds since the unix epoch
+    pub timestamp: i64,
+    /// the GraphQL object name of the entity
+    pub entity: String,
+    /// the primary key of the record, encoded as cursor
+    pub primary_key: String,
+    /// `CREATE`, `UPDATE` or `DELETE`
+    pub operation: String,
+    /// `{ field: { before, after } }` of the changed fields, as JSON
+    pub diff: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
+pub enum Column {
+    Id,
+    Actor,
+    Tenant,
+    Timestamp,
+    Entity,
+    PrimaryKey,
+    Operation,
+    Diff,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
+pub enum PrimaryKey {
+    Id,
+}
+
+impl PrimaryKeyTrait for PrimaryKey {
+    type ValueType = i64;
+
+    fn auto_increment() -> bool {
+        true
+    }
+}
+
+impl ColumnTrait for Column {
+    type EntityName = Entity;
+
+    fn def(&self) -> ColumnDef {
+        match self {
+            Self::Id => ColumnType::BigInteger.def(),
+            Self::Actor | Self::Tenant => ColumnType::string(None).def().null(),
+            Self::Timestamp => ColumnType::BigInteger.def(),
+            Self::Entity | Self::PrimaryKey | Self::Operation => ColumnType::string(None).def(),
+            Self::Diff => ColumnType::Text.def(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+fn tenant_id_string(tenant: &TenantContext) -> String {
+    match sea_value_to_json_value(&tenant.tenant_id) {
+        Json::String(tenant_id) => tenant_id,
+        tenant_id => tenant_id.to_string(),
+    }
+}
+
+/// used to get the condition scoping the audit log to the tenant of the request,
+/// when tenancy is configured. Without tenant, no row matches
+pub(crate) fn tenant_condition(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+) -> Option<Condition> {
+    if !context.tenancy.is_enabled() {
+        return None;
+    }
+
+    Some(match ctx.data_opt::<TenantContext>() {
+        Some(tenant) => Condition::all().add(Column::Tenant.eq(tenant_id_string(tenant))),
+        None => Condition::all().add(Expr::value(1).eq(0)),
+    })
+}
+
+/// used to record the records affected by a mutation, on the connection of the mutation's
+/// transaction. Updates have to pass the models before the mutation to compute the diff
+pub(crate) async fn write_audit_log<T, C>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    connection: &C,
+    operation: OperationType,
+    before: Option<&[T::Model]>,
+    after: Option<&[T::Model]>,
+) -> Result<(), DbErr>
+where
+    T: EntityTrait,
+    C: ConnectionTrait,
+{
+    if !context.audit_log.enabled {
+        return Ok(());
+    }
+
+    let entity_object_builder = EntityObjectBuilder { context };
+    let entity = entity_object_builder.type_name::<T>();
+
+    let actor = ctx
+        .data_opt::<UserContext>()
+        .map(UserContext::user_id_string);
+    let tenant = ctx.data_opt::<TenantContext>().map(tenant_id_string);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or_default();
+
+    let before: BTreeMap<String, &T::Model> = before
+        .unwrap_or_default()
+        .iter()
+        .map(|model| (entity_change_key::<T>(model), model))
+        .collect();
+
+    let diff = |before: Option<&T::Model>, after: Option<&T::Model>| {
+        let mut diff = serde_json::Map::new();
+        for column in T::Column::iter() {
+            let before = before.map(|model| model.get(column));
+            let after = after.map(|model| model.get(column));
+            if before.is_some() && after.is_some() && before == after {
+                continue;
+            }
+
+            let mut change = serde_json::Map::new();
+            if let Some(before) = before {
+                change.insert("before".into(), sea_value_to_json_value(&before));
+            }
+            if let Some(after) = after {
+                change.insert("after".into(), sea_value_to_json_value(&after));
+            }
+            diff.insert(
+                entity_object_builder.column_name::<T>(&column),
+                change.into(),
+            );
+        }
+        Json::Object(diff).to_string()
+    };
+
+    let changes: Vec<(String, String)> = match after {
+        Some(after) => after
+            .iter()
+            .map(|model| {
+                let key = entity_change_key::<T>(model);
+                let diff = diff(before.get(&key).copied(), Some(model));
+                (key, diff)
+            })
+            .collect(),
+        None => before
+            .iter()
+            .map(|(key, model)| (key.clone(), diff(Some(model), None)))
+            .collect(),
+    };
+
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    Entity::insert_many(changes.into_iter().map(|(primary_key, diff)| ActiveModel {
+        id: ActiveValue::NotSet,
+        actor: ActiveValue::Set(actor.clone()),
+        tenant: ActiveValue::Set(tenant.clone()),
+        timestamp: ActiveValue::Set(timestamp),
+        entity: ActiveValue::Set(entity.clone()),
+        primary_key: ActiveValue::Set(primary_key),
+        operation: ActiveValue::Set(operation_name(operation).into()),
+        diff: ActiveValue::Set(diff),
+    }))
+    .exec_without_returning(connection)
+    .await?;
+
+    Ok(())
+}
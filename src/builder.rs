@@ -8,17 +8,21 @@ use async_graphql::{
 use sea_orm::{ActiveEnum, ActiveModelTrait, EntityTrait, IntoActiveModel};
 
 use crate::{
-    ActiveEnumBuilder, ActiveEnumFilterInputBuilder, BuilderContext, ConnectionObjectBuilder,
-    CursorInputBuilder, CustomEnum, CustomFields, CustomInputObject, CustomOutputObject,
-    CustomUnion, EdgeObjectBuilder, EntityCreateBatchMutationBuilder,
-    EntityCreateOneMutationBuilder, EntityDeleteMutationBuilder, EntityInputBuilder,
-    EntityObjectBuilder, EntityQueryFieldBuilder, EntityUpdateMutationBuilder, FilterInputBuilder,
-    FilterTypesMapHelper, HavingInputBuilder, OffsetInputBuilder, OneToManyLoader, OneToOneLoader,
-    OrderByEnumBuilder, OrderInputBuilder, PageInfoObjectBuilder, PageInputBuilder,
-    PaginationInfoObjectBuilder, PaginationInputBuilder, RelatedEntityFilter,
-    RelatedEntityFilterField,
+    relation_is_hidden, ActiveEnumBuilder, ActiveEnumFilterInputBuilder, BuilderContext,
+    ConnectionObjectBuilder, CursorInputBuilder, CustomEnum, CustomFields, CustomInputObject,
+    CustomOutputObject, CustomUnion, EdgeObjectBuilder, EntityCreateBatchMutationBuilder,
+    EntityCreateOneMutationBuilder, EntityDeleteMutationBuilder, EntityHooks, EntityInputBuilder,
+    EntityObjectBuilder, EntityQueryFieldBuilder, EntityUpdateBatchMutationBuilder,
+    EntityUpdateMutationBuilder, ErrorExtension, FieldGuardCacheExtension, FilterInputBuilder,
+    FilterTypesMapHelper, HavingInputBuilder, MutationPayloadBuilder, OffsetInputBuilder,
+    OneToManyLoader, OneToOneLoader, OperationType, OrderByEnumBuilder, OrderInputBuilder,
+    PageInfoObjectBuilder, PageInputBuilder, PaginationInfoObjectBuilder, PaginationInputBuilder,
+    PermissionsQueryBuilder, RegisteredEntityHooks, RelatedEntityFilter, RelatedEntityFilterField,
+    RequestTransactionExtension, UpdateOperationInputBuilder, UpdateOperationKind,
 };
 
+use std::collections::BTreeMap;
+
 type MetadataHashMap = std::collections::HashMap<String, serde_json::Value>;
 
 /// The Builder is used to create the Schema for GraphQL
@@ -51,9 +55,15 @@ pub struct Builder {
     /// holds all entities mutations
     pub mutations: Vec<Field>,
 
+    /// holds the fields of the permissions query by entity
+    pub permissions: BTreeMap<String, Field>,
+
     /// holds all subscriptions
     pub subscriptions: Vec<SubscriptionField>,
 
+    /// holds the entity object names by root field name, reported with errors
+    pub root_field_entities: BTreeMap<String, String>,
+
     /// holds all entities metadata
     pub metadata: MetadataHashMap,
 
@@ -68,6 +78,10 @@ pub struct Builder {
 
     /// Set the maximum complexity a query can have
     pub complexity: Option<usize>,
+
+    /// The bus change subscriptions are served from, an in-process bus if not set
+    #[cfg(feature = "subscriptions")]
+    pub change_event_bus: Option<crate::SharedChangeEventBus>,
 }
 
 impl Builder {
@@ -99,12 +113,16 @@ impl Builder {
             scalars: Vec::new(),
             queries: Vec::new(),
             mutations: Vec::new(),
+            permissions: BTreeMap::new(),
             subscriptions: Vec::new(),
+            root_field_entities: BTreeMap::new(),
             metadata: Default::default(),
             connection,
             context,
             depth: None,
             complexity: None,
+            #[cfg(feature = "subscriptions")]
+            change_event_bus: None,
         }
     }
 
@@ -159,13 +177,53 @@ impl Builder {
             context: self.context,
         };
 
-        if cfg!(feature = "field-pluralize") {
+        // the object types of hidden entities are only left unreferenced
+        let is_hidden = self.context.visibility.is_hidden(
+            &entity_object_builder.type_name::<T>(),
+            None,
+            OperationType::Read,
+        );
+
+        if cfg!(feature = "field-pluralize") && !is_hidden {
             let query = entity_query_field_builder.to_singular_field::<T>();
             self.queries.push(query);
+            self.root_field_entities.insert(
+                entity_query_field_builder.type_name::<T>(),
+                entity_object_builder.type_name::<T>(),
+            );
         }
 
-        let connection_query = entity_query_field_builder.to_field::<T>();
-        self.queries.push(connection_query);
+        if !is_hidden {
+            let connection_query = entity_query_field_builder.to_field::<T>();
+            self.queries.push(connection_query);
+            self.root_field_entities.insert(
+                entity_query_field_builder.connection_field_name::<T>(),
+                entity_object_builder.type_name::<T>(),
+            );
+
+            let permissions_query_builder = PermissionsQueryBuilder {
+                context: self.context,
+            };
+            self.permissions.insert(
+                entity_object_builder.type_name::<T>(),
+                permissions_query_builder.entity_field::<T>(false),
+            );
+        }
+
+        #[cfg(feature = "subscriptions")]
+        if self.context.entity_changed_subscription.enabled && !is_hidden {
+            let entity_changed_subscription_builder = crate::EntityChangedSubscriptionBuilder {
+                context: self.context,
+            };
+            self.outputs
+                .push(entity_changed_subscription_builder.to_object::<T>());
+            self.subscriptions
+                .push(entity_changed_subscription_builder.to_field::<T>());
+            self.root_field_entities.insert(
+                entity_changed_subscription_builder.type_name::<T>(),
+                entity_object_builder.type_name::<T>(),
+            );
+        }
 
         let schema = sea_orm::Schema::new(self.connection.get_database_backend());
         let metadata = schema.json_schema_from_entity(T::default());
@@ -201,7 +259,7 @@ impl Builder {
 
     pub fn register_entity_mutations<T, A>(&mut self)
     where
-        T: EntityTrait,
+        T: EntityTrait<ActiveModel = A>,
         <T as EntityTrait>::Model: Sync,
         <T as EntityTrait>::Model: IntoActiveModel<A>,
         A: ActiveModelTrait<Entity = T> + sea_orm::ActiveModelBehavior + Send + 'static,
@@ -220,34 +278,143 @@ impl Builder {
         let entity_update_input_object = entity_input_builder.update_input_object::<T>();
         self.inputs
             .extend([entity_insert_input_object, entity_update_input_object]);
+        if self.context.entity_update_batch_mutation.enabled {
+            let entity_update_batch_input_object =
+                entity_input_builder.update_batch_input_object::<T>();
+            self.inputs.push(entity_update_batch_input_object);
+        }
 
-        // create one mutation
-        let entity_create_one_mutation_builder = EntityCreateOneMutationBuilder {
-            context: self.context,
+        // payload objects are shared by the mutations with the same suffix
+        let context = self.context;
+        let mut payloads = BTreeMap::new();
+        for (payload, suffix, records) in [
+            (
+                context.entity_create_one_mutation.payload,
+                &context.entity_create_one_mutation.payload_suffix,
+                true,
+            ),
+            (
+                context.entity_create_batch_mutation.payload,
+                &context.entity_create_batch_mutation.payload_suffix,
+                true,
+            ),
+            (
+                context.entity_update_mutation.payload,
+                &context.entity_update_mutation.payload_suffix,
+                true,
+            ),
+            (
+                context.entity_update_batch_mutation.enabled
+                    && context.entity_update_batch_mutation.payload,
+                &context.entity_update_batch_mutation.payload_suffix,
+                true,
+            ),
+            (
+                context.entity_delete_mutation.payload,
+                &context.entity_delete_mutation.payload_suffix,
+                false,
+            ),
+        ] {
+            if payload {
+                *payloads.entry(suffix).or_insert(false) |= records;
+            }
+        }
+        let mutation_payload_builder = MutationPayloadBuilder { context };
+        self.outputs.extend(
+            payloads
+                .into_iter()
+                .map(|(suffix, records)| mutation_payload_builder.to_object::<T>(suffix, records)),
+        );
+
+        let object_name = entity_object_builder.type_name::<T>();
+        let is_hidden = |action| {
+            self.context
+                .visibility
+                .is_hidden(&object_name, None, action)
         };
-        let create_one_mutation = entity_create_one_mutation_builder.to_field::<T, A>();
-        self.mutations.push(create_one_mutation);
 
-        // create batch mutation
-        let entity_create_batch_mutation_builder: EntityCreateBatchMutationBuilder =
-            EntityCreateBatchMutationBuilder {
+        if !is_hidden(OperationType::Create) {
+            // create one mutation
+            let entity_create_one_mutation_builder = EntityCreateOneMutationBuilder {
                 context: self.context,
             };
-        let create_batch_mutation = entity_create_batch_mutation_builder.to_field::<T, A>();
-        self.mutations.push(create_batch_mutation);
+            let create_one_mutation = entity_create_one_mutation_builder.to_field::<T, A>();
+            self.mutations.push(create_one_mutation);
+            self.root_field_entities.insert(
+                entity_create_one_mutation_builder.type_name::<T>(),
+                object_name.clone(),
+            );
 
-        // update mutation
-        let entity_update_mutation_builder = EntityUpdateMutationBuilder {
-            context: self.context,
-        };
-        let update_mutation = entity_update_mutation_builder.to_field::<T, A>();
-        self.mutations.push(update_mutation);
+            // create batch mutation
+            let entity_create_batch_mutation_builder: EntityCreateBatchMutationBuilder =
+                EntityCreateBatchMutationBuilder {
+                    context: self.context,
+                };
+            let create_batch_mutation = entity_create_batch_mutation_builder.to_field::<T, A>();
+            self.mutations.push(create_batch_mutation);
+            self.root_field_entities.insert(
+                entity_create_batch_mutation_builder.type_name::<T>(),
+                object_name.clone(),
+            );
+        }
 
-        let entity_delete_mutation_builder = EntityDeleteMutationBuilder {
-            context: self.context,
-        };
-        let delete_mutation = entity_delete_mutation_builder.to_field::<T, A>();
-        self.mutations.push(delete_mutation);
+        if !is_hidden(OperationType::Update) {
+            // update mutation
+            let entity_update_mutation_builder = EntityUpdateMutationBuilder {
+                context: self.context,
+            };
+            let update_mutation = entity_update_mutation_builder.to_field::<T, A>();
+            self.mutations.push(update_mutation);
+            self.root_field_entities.insert(
+                entity_update_mutation_builder.type_name::<T>(),
+                object_name.clone(),
+            );
+
+            // update batch mutation
+            if self.context.entity_update_batch_mutation.enabled {
+                let entity_update_batch_mutation_builder = EntityUpdateBatchMutationBuilder {
+                    context: self.context,
+                };
+                let update_batch_mutation = entity_update_batch_mutation_builder.to_field::<T, A>();
+                self.mutations.push(update_batch_mutation);
+                self.root_field_entities.insert(
+                    entity_update_batch_mutation_builder.type_name::<T>(),
+                    object_name.clone(),
+                );
+            }
+        }
+
+        if !is_hidden(OperationType::Delete) {
+            let entity_delete_mutation_builder = EntityDeleteMutationBuilder {
+                context: self.context,
+            };
+            let delete_mutation = entity_delete_mutation_builder.to_field::<T, A>();
+            self.mutations.push(delete_mutation);
+            self.root_field_entities.insert(
+                entity_delete_mutation_builder.type_name::<T>(),
+                object_name.clone(),
+            );
+        }
+
+        if !is_hidden(OperationType::Read) {
+            let permissions_query_builder = PermissionsQueryBuilder {
+                context: self.context,
+            };
+            self.permissions.insert(
+                object_name.clone(),
+                permissions_query_builder.entity_field::<T>(true),
+            );
+        }
+    }
+
+    /// used to check whether a relation of entity `T` is left out of the schema,
+    /// as the relation field or the related entity is hidden
+    pub fn is_relation_hidden<T, R>(&self, relation: &R) -> bool
+    where
+        T: EntityTrait,
+        R: RelationBuilder,
+    {
+        relation_is_hidden::<T, R>(self.context, relation)
     }
 
     pub fn register_entity_dataloader_one_to_one<T, R, S>(mut self, _entity: T, spawner: S) -> Self
@@ -293,6 +460,19 @@ impl Builder {
         self
     }
 
+    /// Used to register the typed hooks of an entity,
+    /// they run after the global hooks of the context
+    pub fn register_entity_hooks<T, H>(mut self, hooks: H) -> Self
+    where
+        T: EntityTrait,
+        H: EntityHooks<T> + 'static,
+    {
+        self.schema = self
+            .schema
+            .data(RegisteredEntityHooks::<T>(Box::new(hooks)));
+        self
+    }
+
     /// Used to register an SeaORM ActiveEnum to the schema
     pub fn register_enumeration<A>(&mut self)
     where
@@ -444,9 +624,38 @@ impl Builder {
     /// Consume the builder context and generate a ready to be completed GraphQL schema.
     /// You can extend the schema, or attach additional data to it before finish().
     pub fn schema_builder(mut self) -> SchemaBuilder {
+        if self.context.audit_log.enabled {
+            crate::audit_log::bind_table_name(self.context);
+        }
+        if self.context.audit_log.enabled && self.context.audit_log.query {
+            let related_entity_filter = RelatedEntityFilter::<crate::audit_log::Entity>::default();
+            self.register_entity::<crate::audit_log::Entity>(vec![], &related_entity_filter);
+            self = self.register_related_entity_filter(related_entity_filter);
+        }
+
         #[cfg(feature = "schema-meta")]
         self.register_schema_meta();
 
+        let mutation_payload_builder = MutationPayloadBuilder {
+            context: self.context,
+        };
+        if mutation_payload_builder.enabled() {
+            self.outputs
+                .push(mutation_payload_builder.user_error_object());
+        }
+
+        if self.context.permissions_query.enabled && !self.permissions.is_empty() {
+            let permissions_query_builder = PermissionsQueryBuilder {
+                context: self.context,
+            };
+            let permissions = std::mem::take(&mut self.permissions);
+            self.outputs
+                .push(permissions_query_builder.to_object(permissions.into_values()));
+            self.outputs
+                .extend(permissions_query_builder.to_permission_objects());
+            self.queries.push(permissions_query_builder.to_field());
+        }
+
         let query = self.query;
         let mutation = self.mutation;
         let subscription = self.subscription;
@@ -512,6 +721,22 @@ impl Builder {
             .into_iter()
             .fold(schema, |schema, cur| schema.register(cur));
 
+        // register update operation inputs
+        let schema = if self.context.update_operation_input.enabled {
+            let update_operation_input_builder = UpdateOperationInputBuilder {
+                context: self.context,
+            };
+            UpdateOperationKind::all()
+                .into_iter()
+                .fold(schema, |schema, kind| {
+                    schema
+                        .register(update_operation_input_builder.input_object(kind, false))
+                        .register(update_operation_input_builder.input_object(kind, true))
+                })
+        } else {
+            schema
+        };
+
         let json_scalar = Scalar::new("Json");
 
         let schema = schema
@@ -567,12 +792,51 @@ impl Builder {
             .register(query)
             .register(mutation);
 
+        let schema = if self.context.errors.enabled || self.context.errors.hide_internal {
+            schema.extension(ErrorExtension {
+                context: self.context,
+                entities: std::sync::Arc::new(self.root_field_entities),
+            })
+        } else {
+            schema
+        };
+
+        let schema = if self.context.hooks.is_custom() {
+            schema.extension(FieldGuardCacheExtension)
+        } else {
+            schema
+        };
+
+        let schema = if self.context.request_transaction.enabled {
+            schema.extension(RequestTransactionExtension)
+        } else {
+            schema
+        };
+
         let schema = if have_subscription {
             schema.register(subscription)
         } else {
             schema
         };
 
+        #[cfg(feature = "subscriptions")]
+        let schema = if self.context.entity_changed_subscription.enabled {
+            schema
+                .register(
+                    crate::EntityChangedSubscriptionBuilder {
+                        context: self.context,
+                    }
+                    .operation_enumeration(),
+                )
+                .data(self.change_event_bus.unwrap_or_else(|| {
+                    std::sync::Arc::new(crate::InMemoryChangeEventBus::new(
+                        self.context.entity_changed_subscription.capacity,
+                    ))
+                }))
+        } else {
+            schema
+        };
+
         let schema = if let Some(depth) = self.depth {
             schema.limit_depth(depth)
         } else {
@@ -674,6 +938,7 @@ macro_rules! register_entity {
     ($builder:expr, $module_path:ident, mutation: $mutation:expr) => {
         let relations =
             <$module_path::RelatedEntity as sea_orm::Iterable>::iter()
+                .filter(|rel| !$builder.is_relation_hidden::<$module_path::Entity, _>(rel))
                 .map(|rel| seaography::RelationBuilder::get_relation(&rel, $builder.context))
                 .collect();
         let related_entity_filter =
@@ -1,27 +1,44 @@
 use crate::{
-    ActiveEnumConfig, ActiveEnumFilterInputConfig, ConnectionObjectConfig, CursorInputConfig,
-    EdgeObjectConfig, EntityCreateBatchMutationConfig, EntityCreateOneMutationConfig,
-    EntityDeleteMutationConfig, EntityInputConfig, EntityObjectConfig, EntityQueryFieldConfig,
-    EntityUpdateMutationConfig, FilterInputConfig, HavingInputConfig, OffsetInputConfig,
-    OrderByEnumConfig, OrderInputConfig, PageInfoObjectConfig, PageInputConfig,
-    PaginationInfoObjectConfig, PaginationInputConfig,
+    ActiveEnumConfig, ActiveEnumFilterInputConfig, AuditLogConfig, ConnectionObjectConfig,
+    CursorInputConfig, EdgeObjectConfig, EntityChangedSubscriptionConfig,
+    EntityCreateBatchMutationConfig, EntityCreateOneMutationConfig, EntityDeleteMutationConfig,
+    EntityInputConfig, EntityObjectConfig, EntityQueryFieldConfig, EntityUpdateBatchMutationConfig,
+    EntityUpdateMutationConfig, ErrorConfig, FilterInputConfig, HavingInputConfig,
+    MutationPayloadConfig, OffsetInputConfig, OrderByEnumConfig, OrderInputConfig, OutboxConfig,
+    PageInfoObjectConfig, PageInputConfig, PaginationInfoObjectConfig, PaginationInputConfig,
+    PermissionsQueryConfig, RequestTransactionConfig, UpdateOperationInputConfig,
 };
 
 pub mod entity_column_id;
 pub use entity_column_id::*;
 
+pub mod entity_hooks;
+pub use entity_hooks::*;
+
 pub mod guards;
 pub use guards::*;
 
 pub mod hooks;
 pub use hooks::*;
 
+pub mod row_policy;
+pub use row_policy::*;
+
+pub mod tenancy;
+pub use tenancy::*;
+
 pub mod types_map;
 pub use types_map::*;
 
 pub mod filter_types_map;
 pub use filter_types_map::*;
 
+pub mod validation;
+pub use validation::*;
+
+pub mod visibility;
+pub use visibility::*;
+
 /// Used to hold the configuration for various aspects
 /// related to our builder options. You can modify the
 /// context to make the generated GraphQL nodes match
@@ -48,15 +65,29 @@ pub struct BuilderContext {
     pub entity_object: EntityObjectConfig,
     pub connection_object: ConnectionObjectConfig,
     pub entity_query_field: EntityQueryFieldConfig,
+    pub permissions_query: PermissionsQueryConfig,
 
     pub entity_create_one_mutation: EntityCreateOneMutationConfig,
     pub entity_create_batch_mutation: EntityCreateBatchMutationConfig,
     pub entity_update_mutation: EntityUpdateMutationConfig,
+    pub entity_update_batch_mutation: EntityUpdateBatchMutationConfig,
     pub entity_delete_mutation: EntityDeleteMutationConfig,
+    pub mutation_payload: MutationPayloadConfig,
+    pub request_transaction: RequestTransactionConfig,
+
+    pub entity_changed_subscription: EntityChangedSubscriptionConfig,
+    pub outbox: OutboxConfig,
+    pub audit_log: AuditLogConfig,
 
     pub entity_input: EntityInputConfig,
+    pub update_operation_input: UpdateOperationInputConfig,
 
+    pub errors: ErrorConfig,
     pub hooks: LifecycleHooks,
+    pub row_policies: RowPolicies,
+    pub tenancy: TenancyConfig,
     pub types: TypesMapConfig,
     pub filter_types: FilterTypesMapConfig,
+    pub validation: ValidationConfig,
+    pub visibility: VisibilityConfig,
 }
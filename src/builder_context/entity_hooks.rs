@@ -0,0 +1,290 @@
+use async_graphql::dynamic::ResolverContext;
+use sea_orm::{
+    entity::prelude::async_trait, sea_query::Condition, ColumnTrait, ConnectionTrait, EntityTrait,
+    Iterable, ModelTrait, PaginatorTrait, PrimaryKeyToColumn, QueryFilter,
+};
+
+use crate::{
+    assign_tenant, encode_cursor, guard_error, tenant_condition, BuilderContext, Connection, Edge,
+    GuardAction, OperationType,
+};
+
+/// Typed hooks of a single entity, registered with `Builder::register_entity_hooks`.
+/// They are composed with the global `LifecycleHooks`, which run first: a block of
+/// either blocks the operation and the filters of both apply
+#[async_trait::async_trait]
+pub trait EntityHooks<E>: Send + Sync
+where
+    E: EntityTrait,
+{
+    /// Entity level guard, the typed counterpart of `entity_guard_async`
+    async fn guard(&self, _ctx: &ResolverContext, _action: OperationType) -> GuardAction {
+        GuardAction::Allow
+    }
+
+    /// Filter applied to select, update and delete (but not insert)
+    async fn filter(&self, _ctx: &ResolverContext, _action: OperationType) -> Option<Condition> {
+        None
+    }
+
+    /// Inspect and modify the ActiveModel of a create mutation before it is inserted
+    fn before_insert(
+        &self,
+        _ctx: &ResolverContext,
+        _active_model: &mut E::ActiveModel,
+    ) -> GuardAction {
+        GuardAction::Allow
+    }
+
+    /// Inspect and modify the ActiveModel of an update mutation before it is applied
+    fn before_update(
+        &self,
+        _ctx: &ResolverContext,
+        _active_model: &mut E::ActiveModel,
+    ) -> GuardAction {
+        GuardAction::Allow
+    }
+
+    /// Inspect and modify a model read by a query, relation or subscription
+    /// before it is resolved, after the global `after_read`
+    fn after_read(&self, _ctx: &ResolverContext, _model: &mut E::Model) {}
+}
+
+/// The hooks of an entity as stored in the schema data
+pub struct RegisteredEntityHooks<E>(pub Box<dyn EntityHooks<E>>)
+where
+    E: EntityTrait;
+
+fn entity_hooks<'a, T>(ctx: &'a ResolverContext) -> Option<&'a dyn EntityHooks<T>>
+where
+    T: EntityTrait,
+{
+    ctx.data_opt::<RegisteredEntityHooks<T>>()
+        .map(|hooks| hooks.0.as_ref())
+}
+
+/// used to evaluate the visibility rules, the global and the typed entity guard
+pub(crate) async fn resolve_entity_guard<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    entity: &str,
+    action: OperationType,
+) -> GuardAction
+where
+    T: EntityTrait,
+{
+    if !context.visibility.allows_user(ctx, entity, None, action) {
+        return GuardAction::Block(None);
+    }
+
+    let guard = context.hooks.entity_guard_async(ctx, entity, action).await;
+    if matches!(guard, GuardAction::Block(_)) {
+        return guard;
+    }
+
+    match entity_hooks::<T>(ctx) {
+        Some(hooks) => hooks.guard(ctx, action).await,
+        None => guard,
+    }
+}
+
+/// used to combine the global and the typed entity filter with the row policies
+/// and the tenant scope
+pub(crate) async fn resolve_entity_filter<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    entity: &str,
+    action: OperationType,
+) -> Option<Condition>
+where
+    T: EntityTrait,
+{
+    let filter = context.hooks.entity_filter_async(ctx, entity, action).await;
+
+    let typed_filter = match entity_hooks::<T>(ctx) {
+        Some(hooks) => hooks.filter(ctx, action).await,
+        None => None,
+    };
+
+    let policy = context.row_policies.condition::<T>(ctx, action);
+    let tenant = tenant_condition::<T>(ctx, context);
+
+    [filter, typed_filter, policy, tenant]
+        .into_iter()
+        .flatten()
+        .reduce(|condition, other| Condition::all().add(condition).add(other))
+}
+
+/// used to run `before_active_model_save` and the typed `before_insert` or `before_update`,
+/// then to assign the tenant of inserted rows and check them against the row policies
+pub(crate) fn resolve_before_save<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    entity: &str,
+    action: OperationType,
+    active_model: &mut T::ActiveModel,
+) -> GuardAction
+where
+    T: EntityTrait,
+    T::ActiveModel: 'static,
+{
+    let guard = context
+        .hooks
+        .before_active_model_save(ctx, entity, action, active_model);
+    if matches!(guard, GuardAction::Block(_)) {
+        return guard;
+    }
+
+    let guard = match (entity_hooks::<T>(ctx), action) {
+        (Some(hooks), OperationType::Create) => hooks.before_insert(ctx, active_model),
+        (Some(hooks), OperationType::Update) => hooks.before_update(ctx, active_model),
+        _ => guard,
+    };
+    if matches!(guard, GuardAction::Block(_)) {
+        return guard;
+    }
+
+    // the tenant is assigned last, so hooks cannot move rows to another tenant
+    if action == OperationType::Create {
+        if let Err(reason) = assign_tenant::<T>(ctx, context, active_model) {
+            return GuardAction::Block(Some(reason));
+        }
+    }
+
+    // inserted rows have to match the create policies and the policies they are read back with
+    if action == OperationType::Create
+        && [OperationType::Create, OperationType::Read]
+            .into_iter()
+            .any(|action| !context.row_policies.check::<T>(ctx, action, active_model))
+    {
+        return GuardAction::Block(Some("Row policy violated.".into()));
+    }
+
+    guard
+}
+
+/// used to get the condition rows have to match after an update, the equivalent of
+/// `WITH CHECK`: the update and read policies and the tenant scope
+pub(crate) fn resolve_update_check<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+) -> Option<Condition>
+where
+    T: EntityTrait,
+{
+    let update = context
+        .row_policies
+        .condition::<T>(ctx, OperationType::Update);
+    let read = context
+        .row_policies
+        .condition::<T>(ctx, OperationType::Read);
+    let tenant = tenant_condition::<T>(ctx, context);
+
+    [update, read, tenant]
+        .into_iter()
+        .flatten()
+        .reduce(|condition, other| Condition::all().add(condition).add(other))
+}
+
+/// used to reject an update that moved rows out of the update check, `models` are
+/// the updated rows, or their pre-images if the database cannot return them
+pub(crate) async fn check_updated_rows<T, C>(
+    connection: &C,
+    check: Option<&Condition>,
+    models: &[T::Model],
+) -> async_graphql::Result<()>
+where
+    T: EntityTrait,
+    <T as EntityTrait>::Model: Sync,
+    C: ConnectionTrait,
+{
+    let Some(check) = check else {
+        return Ok(());
+    };
+    if models.is_empty() {
+        return Ok(());
+    }
+
+    let keys = models.iter().fold(Condition::any(), |condition, model| {
+        condition.add(T::PrimaryKey::iter().fold(Condition::all(), |key, column| {
+            let column = column.into_column();
+            key.add(column.eq(model.get(column)))
+        }))
+    });
+
+    // a negated check would miss rows it evaluates to null for, matching rows are counted
+    let matching = T::find()
+        .filter(keys)
+        .filter(check.clone())
+        .count(connection)
+        .await?;
+
+    if matching < models.len() as u64 {
+        return Err(guard_error(None, "Row policy violated."));
+    }
+
+    Ok(())
+}
+
+/// used to run the global and the typed `after_read` on the models read,
+/// models removed by the global hook are not passed to the typed one
+// the global hook receives the `Vec` itself to be able to remove models
+#[allow(clippy::ptr_arg)]
+pub(crate) fn resolve_after_read<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    entity: &str,
+    models: &mut Vec<T::Model>,
+) where
+    T: EntityTrait,
+{
+    context.hooks.after_read(ctx, entity, models);
+
+    if let Some(hooks) = entity_hooks::<T>(ctx) {
+        for model in models.iter_mut() {
+            hooks.after_read(ctx, model);
+        }
+    }
+}
+
+/// used to run the `after_read` hooks on an optional model
+pub(crate) fn resolve_after_read_one<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    entity: &str,
+    model: Option<T::Model>,
+) -> Option<T::Model>
+where
+    T: EntityTrait,
+{
+    let mut models: Vec<T::Model> = model.into_iter().collect();
+    resolve_after_read::<T>(ctx, context, entity, &mut models);
+    models.pop()
+}
+
+/// used to run the `after_read` hooks on the nodes of a connection. The edges of
+/// removed models are dropped, while the page info and the pagination info still
+/// describe the page read from the database, so a page emptied by the hooks can be paged past
+pub(crate) fn resolve_after_read_connection<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    entity: &str,
+    connection: &mut Connection<T>,
+) where
+    T: EntityTrait,
+    <T as EntityTrait>::Model: Sync,
+{
+    let mut models: Vec<T::Model> = std::mem::take(&mut connection.edges)
+        .into_iter()
+        .map(|edge| edge.node)
+        .collect();
+    resolve_after_read::<T>(ctx, context, entity, &mut models);
+
+    connection.edges = models
+        .into_iter()
+        .map(|node| Edge {
+            cursor: encode_cursor(node.get_primary_key_value()),
+            node,
+        })
+        .collect();
+}
@@ -1,12 +1,378 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use async_graphql::{
+    dynamic::{ResolverContext, ValueAccessor},
+    extensions::{
+        Extension, ExtensionContext, ExtensionFactory, NextParseQuery, NextPrepareRequest,
+        NextResolve, ResolveInfo,
+    },
+    futures_util::lock::Mutex as AsyncMutex,
+    parser::types::{ExecutableDocument, FragmentDefinition, Selection, SelectionSet},
+    Name, Positioned, QueryPathNode, QueryPathSegment, Request, ServerResult, Value, Variables,
+};
+use sea_orm::{EntityTrait, Iterable};
+
+use crate::{
+    coded_error, BuilderContext, EntityObjectBuilder, ErrorCode, OperationType, RelatedEntityFilter,
+};
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum GuardAction {
     Block(Option<String>),
     Allow,
+    /// resolve the field to null, only applies to field guards on reads
+    Mask,
+    /// resolve the field to the given value, only applies to field guards on reads.
+    /// Relation fields are masked instead
+    Replace(async_graphql::Value),
+}
+
+impl GuardAction {
+    /// used to check whether the field is masked or replaced
+    pub fn is_masked(&self) -> bool {
+        matches!(self, GuardAction::Mask | GuardAction::Replace(_))
+    }
 }
 
+/// used to report a blocked operation with the `FORBIDDEN` code
 pub fn guard_error(reason: Option<String>, fallback: &str) -> async_graphql::Error {
-    match reason {
-        Some(reason) => async_graphql::Error::new(reason),
-        None => async_graphql::Error::new(fallback),
+    coded_error(ErrorCode::Forbidden, reason.as_deref().unwrap_or(fallback))
+}
+
+/// used to evaluate the visibility rules and the field guard of a field
+pub(crate) async fn resolve_field_guard(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    entity: &str,
+    field: &str,
+    action: OperationType,
+) -> GuardAction {
+    if !context
+        .visibility
+        .allows_user(ctx, entity, Some(field), action)
+    {
+        return GuardAction::Block(None);
+    }
+
+    context
+        .hooks
+        .field_guard_async(ctx, entity, field, action)
+        .await
+}
+
+/// used to evaluate the visibility rules and the field guards of the fields of an input
+pub(crate) async fn resolve_field_guards(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    entity: &str,
+    fields: &[&str],
+    action: OperationType,
+) -> GuardAction {
+    if fields.iter().any(|field| {
+        !context
+            .visibility
+            .allows_user(ctx, entity, Some(field), action)
+    }) {
+        return GuardAction::Block(None);
+    }
+
+    context
+        .hooks
+        .field_guards_async(ctx, entity, fields, action)
+        .await
+        .into_iter()
+        .find(|result| matches!(result, GuardAction::Block(_)))
+        .unwrap_or(GuardAction::Allow)
+}
+
+/// The field guard actions of one object, by field name
+type ObjectGuards = Arc<AsyncMutex<HashMap<String, GuardAction>>>;
+
+/// The read field guards of the objects resolved by a request, by path of the object
+#[derive(Default)]
+pub(crate) struct FieldGuardCache {
+    objects: Mutex<HashMap<String, ObjectGuards>>,
+    fragments: Mutex<HashMap<Name, Positioned<FragmentDefinition>>>,
+    /// names of the fields selected on the objects returned by a field, by path of the field
+    selections: Mutex<HashMap<String, BTreeSet<String>>>,
+}
+
+impl FieldGuardCache {
+    /// used to get the names of the fields selected on an object, if known
+    fn selected_fields(&self, object: &QueryPathNode<'_>) -> Option<BTreeSet<String>> {
+        // the objects of a list are selected by the field returning the list
+        let field = match (object.segment, object.parent) {
+            (QueryPathSegment::Index(_), Some(parent)) => parent,
+            _ => object,
+        };
+        self.selections
+            .lock()
+            .expect("field guard cache lock")
+            .get(&field.to_string())
+            .cloned()
+    }
+}
+
+/// Adds a `FieldGuardCache` to every request, so the field guards of the fields selected on
+/// an object are evaluated at once instead of once per field
+pub(crate) struct FieldGuardCacheExtension;
+
+impl ExtensionFactory for FieldGuardCacheExtension {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(FieldGuardCacheExtension)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for FieldGuardCacheExtension {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        next.run(ctx, request.data(FieldGuardCache::default()))
+            .await
+    }
+
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        if let Some(cache) = ctx.data_opt::<FieldGuardCache>() {
+            *cache.fragments.lock().expect("field guard cache lock") = document.fragments.clone();
+        }
+        Ok(document)
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let selection_set = &info.field.selection_set.node;
+        if let (false, Some(cache)) = (
+            selection_set.items.is_empty(),
+            ctx.data_opt::<FieldGuardCache>(),
+        ) {
+            let mut names = BTreeSet::new();
+            collect_selected_fields(
+                &cache.fragments.lock().expect("field guard cache lock"),
+                selection_set,
+                &mut names,
+            );
+            cache
+                .selections
+                .lock()
+                .expect("field guard cache lock")
+                .insert(info.path_node.to_string(), names);
+        }
+
+        next.run(ctx, info).await
+    }
+}
+
+/// used to collect the names of the fields of a selection set, including those of its fragments
+fn collect_selected_fields(
+    fragments: &HashMap<Name, Positioned<FragmentDefinition>>,
+    selection_set: &SelectionSet,
+    names: &mut BTreeSet<String>,
+) {
+    for selection in &selection_set.items {
+        match &selection.node {
+            Selection::Field(field) => {
+                names.insert(field.node.name.node.to_string());
+            }
+            Selection::FragmentSpread(spread) => {
+                if let Some(fragment) = fragments.get(&spread.node.fragment_name.node) {
+                    collect_selected_fields(fragments, &fragment.node.selection_set.node, names);
+                }
+            }
+            Selection::InlineFragment(fragment) => {
+                collect_selected_fields(fragments, &fragment.node.selection_set.node, names);
+            }
+        }
+    }
+}
+
+/// used to evaluate the visibility rules and the field guard of a field read from a model of `T`
+pub(crate) async fn resolve_read_field_guard<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    entity: &str,
+    field: &str,
+) -> GuardAction
+where
+    T: EntityTrait,
+{
+    if !context
+        .visibility
+        .allows_user(ctx, entity, Some(field), OperationType::Read)
+    {
+        return GuardAction::Block(None);
+    }
+
+    read_field_guard::<T>(ctx, context, entity, field).await
+}
+
+/// used to evaluate the field guard of a field read from a model of `T`. The guards of
+/// the columns selected on the object are evaluated at once, with the first of its fields
+pub(crate) async fn read_field_guard<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    entity: &str,
+    field: &str,
+) -> GuardAction
+where
+    T: EntityTrait,
+{
+    let (Some(cache), Some(parent)) = (
+        ctx.data_opt::<FieldGuardCache>(),
+        ctx.path_node.as_ref().and_then(|node| node.parent),
+    ) else {
+        return context
+            .hooks
+            .field_guard_async(ctx, entity, field, OperationType::Read)
+            .await;
+    };
+
+    let object = cache
+        .objects
+        .lock()
+        .expect("field guard cache lock")
+        .entry(format!("{entity}@{parent}"))
+        .or_default()
+        .clone();
+    // the other fields of the object wait for the evaluation
+    let mut guards = object.lock().await;
+
+    if let Some(guard) = guards.get(field) {
+        return guard.clone();
+    }
+
+    // relation fields and objects of unknown selection are evaluated on their own
+    let selected = cache.selected_fields(parent).unwrap_or_default();
+    let entity_object_builder = EntityObjectBuilder { context };
+    let mut fields: Vec<String> = T::Column::iter()
+        .map(|column| entity_object_builder.column_name::<T>(&column))
+        .filter(|name| selected.contains(name) && !guards.contains_key(name))
+        .collect();
+    if !fields.iter().any(|name| name == field) {
+        fields.push(field.into());
+    }
+    let names: Vec<&str> = fields.iter().map(String::as_str).collect();
+
+    let results = context
+        .hooks
+        .field_guards_async(ctx, entity, &names, OperationType::Read)
+        .await;
+    guards.extend(fields.into_iter().zip(results));
+
+    guards.get(field).cloned().unwrap_or(GuardAction::Allow)
+}
+
+/// used to reject filtering and ordering on fields masked by the field guard,
+/// which would reveal their values. `having` filters on the fields of related entities
+pub(crate) async fn reject_masked_fields<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    filters: Option<ValueAccessor<'_>>,
+    order_by: Option<ValueAccessor<'_>>,
+    having: Option<ValueAccessor<'_>>,
+) -> async_graphql::Result<()>
+where
+    T: EntityTrait,
+{
+    let entity_object_builder = EntityObjectBuilder { context };
+    let object_name = entity_object_builder.type_name::<T>();
+
+    let columns: BTreeSet<String> = T::Column::iter()
+        .map(|column| entity_object_builder.column_name::<T>(&column))
+        .collect();
+
+    let mut fields = BTreeSet::new();
+    for value in [filters, order_by].into_iter().flatten() {
+        collect_column_fields(&columns, value.as_value(), &mut fields);
+    }
+
+    if let (Some(having), Some(related_entity_filter)) =
+        (having, ctx.data_opt::<RelatedEntityFilter<T>>())
+    {
+        for (relation, related_type_name, related_fields) in
+            related_entity_filter.filtered_fields(having.as_value())
+        {
+            // masked relations resolve to null, rows having them must not be told apart
+            fields.insert(relation.into());
+            reject_masked(ctx, context, related_type_name, related_fields).await?;
+        }
+    }
+
+    reject_masked(ctx, context, &object_name, fields).await
+}
+
+/// used to reject filtering and ordering on the given fields of an object type, if masked
+async fn reject_masked(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    object_name: &str,
+    fields: BTreeSet<String>,
+) -> async_graphql::Result<()> {
+    for field in fields {
+        if !context
+            .visibility
+            .allows_user(ctx, object_name, Some(&field), OperationType::Read)
+        {
+            return Err(coded_error(
+                ErrorCode::Forbidden,
+                format!("Filtering or ordering on field `{field}` is not allowed."),
+            ));
+        }
+
+        if context
+            .hooks
+            .field_guard_async(ctx, object_name, &field, OperationType::Read)
+            .await
+            .is_masked()
+        {
+            return Err(coded_error(
+                ErrorCode::Forbidden,
+                format!("Filtering or ordering on masked field `{field}` is not allowed."),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// used to collect the column fields of a filter or order input, including nested conditions
+pub(crate) fn collect_column_fields(
+    columns: &BTreeSet<String>,
+    value: &async_graphql::Value,
+    fields: &mut BTreeSet<String>,
+) {
+    match value {
+        async_graphql::Value::Object(object) => {
+            for (name, value) in object {
+                if columns.contains(name.as_str()) {
+                    fields.insert(name.to_string());
+                } else {
+                    collect_column_fields(columns, value, fields);
+                }
+            }
+        }
+        async_graphql::Value::List(list) => {
+            for value in list {
+                collect_column_fields(columns, value, fields);
+            }
+        }
+        _ => {}
     }
 }
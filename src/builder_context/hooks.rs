@@ -1,13 +1,20 @@
 use super::GuardAction;
-use async_graphql::dynamic::ResolverContext;
+use async_graphql::{dynamic::ResolverContext, extensions::ExtensionContext};
 use sea_orm::{entity::prelude::async_trait, Condition};
 use std::{any::Any, ops::Deref};
 
-pub struct LifecycleHooks(pub(crate) Box<dyn LifecycleHooksInterface>);
+pub struct LifecycleHooks {
+    hooks: Box<dyn LifecycleHooksInterface>,
+    /// false for the default hooks, which need no field guard cache
+    custom: bool,
+}
 
 impl Default for LifecycleHooks {
     fn default() -> Self {
-        Self(Box::new(DefaultLifecycleHook))
+        Self {
+            hooks: Box::new(DefaultLifecycleHook),
+            custom: false,
+        }
     }
 }
 
@@ -26,6 +33,68 @@ pub enum OperationType {
     Delete,
 }
 
+/// The rows affected by a mutation, passed to `entity_watch_changes`
+pub struct EntityChanges<'a> {
+    /// primary keys of the affected rows, encoded as cursors.
+    /// Deletes only carry keys if `watch_pre_images` asked for them
+    pub keys: Vec<String>,
+    /// models before the mutation as `Vec<Model>`, for updates and deletes
+    /// if `watch_pre_images` asked for them
+    pub before: Option<&'a (dyn Any + Send + Sync)>,
+    /// models after the mutation as `Vec<Model>`, absent for deletes
+    pub after: Option<&'a (dyn Any + Send + Sync)>,
+}
+
+impl<'a> EntityChanges<'a> {
+    /// used to get the models before the mutation, `M` is the entity's `Model`.
+    /// Pre-images of updates are not necessarily in the order of `after`
+    pub fn before_models<M: 'static>(&self) -> Option<&[M]> {
+        self.before
+            .and_then(|models| models.downcast_ref::<Vec<M>>())
+            .map(Vec::as_slice)
+    }
+
+    /// used to get the models after the mutation, `M` is the entity's `Model`
+    pub fn after_models<M: 'static>(&self) -> Option<&[M]> {
+        self.after
+            .and_then(|models| models.downcast_ref::<Vec<M>>())
+            .map(Vec::as_slice)
+    }
+}
+
+/// The request passed to `entity_watch_changes`. Within a request transaction the hooks run
+/// once it is committed, after the mutation fields have been resolved
+pub struct WatchContext<'a> {
+    source: WatchContextSource<'a>,
+}
+
+enum WatchContextSource<'a> {
+    Resolver(&'a ResolverContext<'a>),
+    Extension(&'a ExtensionContext<'a>),
+}
+
+impl<'a> WatchContext<'a> {
+    pub(crate) fn resolver(ctx: &'a ResolverContext<'a>) -> Self {
+        Self {
+            source: WatchContextSource::Resolver(ctx),
+        }
+    }
+
+    pub(crate) fn extension(ctx: &'a ExtensionContext<'a>) -> Self {
+        Self {
+            source: WatchContextSource::Extension(ctx),
+        }
+    }
+
+    /// used to get data of the request or the schema, e.g. the `UserContext`
+    pub fn data_opt<D: Any + Send + Sync>(&self) -> Option<&D> {
+        match self.source {
+            WatchContextSource::Resolver(ctx) => ctx.data_opt::<D>(),
+            WatchContextSource::Extension(ctx) => ctx.data_opt::<D>(),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 pub trait LifecycleHooksInterface: Send + Sync {
     /// This happens before an Entity is accessed
@@ -41,7 +110,29 @@ pub trait LifecycleHooksInterface: Send + Sync {
     /// This happens after an Entity is mutated
     async fn entity_watch(&self, _ctx: &ResolverContext, _entity: &str, _action: OperationType) {}
 
-    /// This happens before an Entity is accessed, invoked on each field
+    /// This happens after an Entity is mutated and the change is committed, with the affected rows
+    async fn entity_watch_changes(
+        &self,
+        _ctx: &WatchContext<'_>,
+        _entity: &str,
+        _action: OperationType,
+        _changes: &EntityChanges<'_>,
+    ) {
+    }
+
+    /// Whether update and delete mutations load the rows before changing them,
+    /// within the same transaction, to pass them to `entity_watch_changes`
+    fn watch_pre_images(
+        &self,
+        _ctx: &ResolverContext,
+        _entity: &str,
+        _action: OperationType,
+    ) -> bool {
+        false
+    }
+
+    /// This happens before an Entity is accessed, invoked on each field. On reads, it is
+    /// also invoked for the fields filtered or ordered on, rejected when masked
     fn field_guard(
         &self,
         _ctx: &ResolverContext,
@@ -62,7 +153,64 @@ pub trait LifecycleHooksInterface: Send + Sync {
         None
     }
 
-    /// Inspect and modify an ActiveModel before save (only insert for now)
+    /// Async version of `entity_guard`, awaited by all generated resolvers.
+    /// Forwards to `entity_guard` by default
+    async fn entity_guard_async(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        action: OperationType,
+    ) -> GuardAction {
+        self.entity_guard(ctx, entity, action)
+    }
+
+    /// Async version of `field_guard`, awaited by all generated resolvers.
+    /// Forwards to `field_guard` by default
+    async fn field_guard_async(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        field: &str,
+        action: OperationType,
+    ) -> GuardAction {
+        self.field_guard(ctx, entity, field, action)
+    }
+
+    /// Evaluates the field guards of several fields at once, returning the action of each
+    /// field: all fields of an input object, or all columns of an object read.
+    /// Forwards to `field_guard_async` for each field by default
+    async fn field_guards_async(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        fields: &[&str],
+        action: OperationType,
+    ) -> Vec<GuardAction> {
+        let mut results = Vec::with_capacity(fields.len());
+        for field in fields {
+            results.push(self.field_guard_async(ctx, entity, field, action).await);
+        }
+        results
+    }
+
+    /// Async version of `entity_filter`, awaited by all generated resolvers.
+    /// Forwards to `entity_filter` by default
+    async fn entity_filter_async(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        action: OperationType,
+    ) -> Option<Condition> {
+        self.entity_filter(ctx, entity, action)
+    }
+
+    /// This happens after the models of an Entity are loaded by a query, relation
+    /// or subscription, before they are resolved. `models` is the `Vec<Model>` of the
+    /// entity, models may be modified or removed
+    fn after_read(&self, _ctx: &ResolverContext, _entity: &str, _models: &mut dyn Any) {}
+
+    /// Inspect and modify an ActiveModel before insert or update.
+    /// See `EntityHooks` for a typed alternative
     fn before_active_model_save(
         &self,
         _ctx: &ResolverContext,
@@ -78,7 +226,15 @@ impl LifecycleHooksInterface for DefaultLifecycleHook {}
 
 impl LifecycleHooks {
     pub fn new<T: LifecycleHooksInterface + 'static>(t: T) -> Self {
-        Self(Box::new(t))
+        Self {
+            hooks: Box::new(t),
+            custom: true,
+        }
+    }
+
+    /// used to check whether hooks other than the default ones are set
+    pub(crate) fn is_custom(&self) -> bool {
+        self.custom
     }
 }
 
@@ -86,7 +242,7 @@ impl Deref for LifecycleHooks {
     type Target = dyn LifecycleHooksInterface;
 
     fn deref(&self) -> &Self::Target {
-        &*self.0
+        &*self.hooks
     }
 }
 
@@ -121,6 +277,25 @@ impl LifecycleHooksInterface for MultiLifecycleHooks {
         }
     }
 
+    async fn entity_watch_changes(
+        &self,
+        ctx: &WatchContext<'_>,
+        entity: &str,
+        action: OperationType,
+        changes: &EntityChanges<'_>,
+    ) {
+        for hook in &self.hooks {
+            hook.entity_watch_changes(ctx, entity, action, changes)
+                .await;
+        }
+    }
+
+    fn watch_pre_images(&self, ctx: &ResolverContext, entity: &str, action: OperationType) -> bool {
+        self.hooks
+            .iter()
+            .any(|hook| hook.watch_pre_images(ctx, entity, action))
+    }
+
     fn field_guard(
         &self,
         ctx: &ResolverContext,
@@ -128,13 +303,18 @@ impl LifecycleHooksInterface for MultiLifecycleHooks {
         field: &str,
         action: OperationType,
     ) -> GuardAction {
+        // a block takes precedence over the first mask
+        let mut masked = GuardAction::Allow;
         for hook in &self.hooks {
             let result = hook.field_guard(ctx, entity, field, action);
             if matches!(result, GuardAction::Block(_)) {
                 return result;
             }
+            if result.is_masked() && !masked.is_masked() {
+                masked = result;
+            }
         }
-        GuardAction::Allow
+        masked
     }
 
     fn entity_filter(
@@ -157,4 +337,108 @@ impl LifecycleHooksInterface for MultiLifecycleHooks {
             None
         }
     }
+
+    async fn entity_guard_async(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        action: OperationType,
+    ) -> GuardAction {
+        for hook in &self.hooks {
+            let result = hook.entity_guard_async(ctx, entity, action).await;
+            if matches!(result, GuardAction::Block(_)) {
+                return result;
+            }
+        }
+        GuardAction::Allow
+    }
+
+    async fn field_guard_async(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        field: &str,
+        action: OperationType,
+    ) -> GuardAction {
+        // a block takes precedence over the first mask
+        let mut masked = GuardAction::Allow;
+        for hook in &self.hooks {
+            let result = hook.field_guard_async(ctx, entity, field, action).await;
+            if matches!(result, GuardAction::Block(_)) {
+                return result;
+            }
+            if result.is_masked() && !masked.is_masked() {
+                masked = result;
+            }
+        }
+        masked
+    }
+
+    async fn field_guards_async(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        fields: &[&str],
+        action: OperationType,
+    ) -> Vec<GuardAction> {
+        // per field, a block takes precedence over the first mask
+        let mut results = vec![GuardAction::Allow; fields.len()];
+        for hook in &self.hooks {
+            let hook_results = hook.field_guards_async(ctx, entity, fields, action).await;
+            for (result, hook_result) in results.iter_mut().zip(hook_results) {
+                if matches!(result, GuardAction::Block(_)) {
+                    continue;
+                }
+                if matches!(hook_result, GuardAction::Block(_))
+                    || (hook_result.is_masked() && !result.is_masked())
+                {
+                    *result = hook_result;
+                }
+            }
+        }
+        results
+    }
+
+    async fn entity_filter_async(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        action: OperationType,
+    ) -> Option<Condition> {
+        let mut cond = Condition::all();
+        for hook in &self.hooks {
+            if let Some(inner_cond) = hook.entity_filter_async(ctx, entity, action).await {
+                if !inner_cond.is_empty() {
+                    cond = cond.add(inner_cond);
+                }
+            }
+        }
+        if !cond.is_empty() {
+            Some(cond)
+        } else {
+            None
+        }
+    }
+
+    fn before_active_model_save(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        action: OperationType,
+        active_model: &mut dyn Any,
+    ) -> GuardAction {
+        for hook in &self.hooks {
+            let result = hook.before_active_model_save(ctx, entity, action, active_model);
+            if matches!(result, GuardAction::Block(_)) {
+                return result;
+            }
+        }
+        GuardAction::Allow
+    }
+
+    fn after_read(&self, ctx: &ResolverContext, entity: &str, models: &mut dyn Any) {
+        for hook in &self.hooks {
+            hook.after_read(ctx, entity, models);
+        }
+    }
 }
@@ -0,0 +1,338 @@
+use std::{any::Any, any::TypeId, collections::HashMap};
+
+use async_graphql::dynamic::ResolverContext;
+use sea_orm::{
+    sea_query::{value::sea_value_to_json_value, Condition, Expr, ExprTrait},
+    ActiveModelTrait, EntityTrait, Value,
+};
+
+use crate::{OperationType, UserContext};
+
+type FnPolicyValue = Box<dyn Fn(&ResolverContext) -> Option<Value> + Send + Sync>;
+
+/// A value a policy compares a column with
+pub enum PolicyValue {
+    /// a constant
+    Value(Value),
+    /// the `user_id` of the `UserContext`, no user matches no row
+    UserId,
+    /// a claim of the `UserContext`, no user or claim matches no row
+    Claim(String),
+    /// a value taken from the request, `None` matches no row
+    Context(FnPolicyValue),
+}
+
+impl PolicyValue {
+    /// used to compare with a constant
+    pub fn value<V>(value: V) -> Self
+    where
+        V: Into<Value>,
+    {
+        Self::Value(value.into())
+    }
+
+    /// used to compare with the `user_id` of the `UserContext`
+    pub fn user_id() -> Self {
+        Self::UserId
+    }
+
+    /// used to compare with a claim of the `UserContext`, e.g. an organization
+    pub fn claim<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::Claim(name.into())
+    }
+
+    /// used to compare with a value taken from the request, e.g. from its data
+    pub fn context<F>(f: F) -> Self
+    where
+        F: Fn(&ResolverContext) -> Option<Value> + Send + Sync + 'static,
+    {
+        Self::Context(Box::new(f))
+    }
+
+    fn resolve(&self, ctx: &ResolverContext) -> Option<Value> {
+        match self {
+            Self::Value(value) => Some(value.clone()),
+            Self::UserId => ctx
+                .data_opt::<UserContext>()
+                .map(|user_context| user_context.user_id.clone()),
+            Self::Claim(name) => ctx
+                .data_opt::<UserContext>()
+                .and_then(|user_context| user_context.claim(name))
+                .and_then(json_to_value),
+            Self::Context(f) => f(ctx),
+        }
+    }
+}
+
+/// used to convert a scalar claim into a value, other claims match no row
+fn json_to_value(value: &serde_json::Value) -> Option<Value> {
+    match value {
+        serde_json::Value::Bool(value) => Some((*value).into()),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(number) => Some(number.into()),
+            None => number.as_f64().map(Into::into),
+        },
+        serde_json::Value::String(value) => Some(value.clone().into()),
+        _ => None,
+    }
+}
+
+/// A row level security policy of entity `E`, built with `policy_column`.
+/// Policies compile to conditions, which are applied to reads, relation loads,
+/// `having` subqueries, updates and deletes, and are checked against inserted rows
+pub enum PolicyExpr<E>
+where
+    E: EntityTrait,
+{
+    Eq(E::Column, PolicyValue),
+    Ne(E::Column, PolicyValue),
+    IsNull(E::Column),
+    IsNotNull(E::Column),
+    All(Vec<PolicyExpr<E>>),
+    Any(Vec<PolicyExpr<E>>),
+    Not(Box<PolicyExpr<E>>),
+    /// matches every row
+    Allow,
+    /// matches no row
+    Deny,
+}
+
+/// A column of entity `E`, the starting point of a policy expression
+pub struct PolicyColumn<E>(E::Column)
+where
+    E: EntityTrait;
+
+/// used to start a policy expression on a column
+/// ```ignore
+/// policy_column::<customer::Entity>(customer::Column::StoreId)
+///     .eq(PolicyValue::context(|ctx| ctx.data_opt::<Store>().map(|store| store.id.into())))
+/// ```
+pub fn policy_column<E>(column: E::Column) -> PolicyColumn<E>
+where
+    E: EntityTrait,
+{
+    PolicyColumn(column)
+}
+
+impl<E> PolicyColumn<E>
+where
+    E: EntityTrait,
+{
+    pub fn eq(self, value: PolicyValue) -> PolicyExpr<E> {
+        PolicyExpr::Eq(self.0, value)
+    }
+
+    pub fn ne(self, value: PolicyValue) -> PolicyExpr<E> {
+        PolicyExpr::Ne(self.0, value)
+    }
+
+    pub fn is_null(self) -> PolicyExpr<E> {
+        PolicyExpr::IsNull(self.0)
+    }
+
+    pub fn is_not_null(self) -> PolicyExpr<E> {
+        PolicyExpr::IsNotNull(self.0)
+    }
+}
+
+impl<E> PolicyExpr<E>
+where
+    E: EntityTrait,
+{
+    pub fn and(self, other: PolicyExpr<E>) -> Self {
+        match self {
+            Self::All(mut exprs) => {
+                exprs.push(other);
+                Self::All(exprs)
+            }
+            expr => Self::All(vec![expr, other]),
+        }
+    }
+
+    pub fn or(self, other: PolicyExpr<E>) -> Self {
+        match self {
+            Self::Any(mut exprs) => {
+                exprs.push(other);
+                Self::Any(exprs)
+            }
+            expr => Self::Any(vec![expr, other]),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+
+    /// used to compile the policy into a condition for the current request
+    pub fn condition(&self, ctx: &ResolverContext) -> Condition {
+        // an empty condition would be skipped, so no match is spelled out
+        let deny = || Condition::all().add(Expr::value(1).eq(0));
+
+        let column = |column: &E::Column| Expr::col((E::default(), *column));
+
+        match self {
+            Self::Eq(col, value) => match value.resolve(ctx) {
+                Some(value) => Condition::all().add(column(col).eq(value)),
+                None => deny(),
+            },
+            Self::Ne(col, value) => match value.resolve(ctx) {
+                Some(value) => Condition::all().add(column(col).ne(value)),
+                None => deny(),
+            },
+            Self::IsNull(col) => Condition::all().add(column(col).is_null()),
+            Self::IsNotNull(col) => Condition::all().add(column(col).is_not_null()),
+            Self::All(exprs) => exprs.iter().fold(Condition::all(), |condition, expr| {
+                condition.add(expr.condition(ctx))
+            }),
+            Self::Any(exprs) if exprs.is_empty() => deny(),
+            Self::Any(exprs) => exprs.iter().fold(Condition::any(), |condition, expr| {
+                condition.add(expr.condition(ctx))
+            }),
+            Self::Not(expr) => {
+                let condition = expr.condition(ctx);
+                if condition.is_empty() {
+                    deny()
+                } else {
+                    condition.not()
+                }
+            }
+            Self::Allow => Condition::all(),
+            Self::Deny => deny(),
+        }
+    }
+
+    /// used to check the values of an ActiveModel against the policy, unset columns
+    /// only match `IsNull`
+    pub fn check(&self, ctx: &ResolverContext, active_model: &E::ActiveModel) -> bool {
+        let get = |column: &E::Column| active_model.get(*column).into_value();
+
+        // values are compared as JSON, as the column and the policy value may
+        // differ in width, e.g. `Int` and `BigInt`
+        let equals = |col: &E::Column, value: &PolicyValue| -> Option<bool> {
+            let left = get(col)?;
+            let right = value.resolve(ctx)?;
+            Some(sea_value_to_json_value(&left) == sea_value_to_json_value(&right))
+        };
+
+        let is_null = |column: &E::Column| {
+            get(column).map_or(true, |value| {
+                serde_json::Value::is_null(&sea_value_to_json_value(&value))
+            })
+        };
+
+        match self {
+            Self::Eq(col, value) => equals(col, value).unwrap_or(false),
+            Self::Ne(col, value) => equals(col, value).is_some_and(|equals| !equals),
+            Self::IsNull(col) => is_null(col),
+            Self::IsNotNull(col) => !is_null(col),
+            Self::All(exprs) => exprs.iter().all(|expr| expr.check(ctx, active_model)),
+            Self::Any(exprs) => exprs.iter().any(|expr| expr.check(ctx, active_model)),
+            Self::Not(expr) => !expr.check(ctx, active_model),
+            Self::Allow => true,
+            Self::Deny => false,
+        }
+    }
+}
+
+/// A policy with its entity type erased
+trait ErasedPolicy: Send + Sync {
+    fn condition(&self, ctx: &ResolverContext) -> Condition;
+
+    fn check(&self, ctx: &ResolverContext, active_model: &dyn Any) -> bool;
+}
+
+impl<E> ErasedPolicy for PolicyExpr<E>
+where
+    E: EntityTrait,
+    E::ActiveModel: 'static,
+{
+    fn condition(&self, ctx: &ResolverContext) -> Condition {
+        PolicyExpr::condition(self, ctx)
+    }
+
+    fn check(&self, ctx: &ResolverContext, active_model: &dyn Any) -> bool {
+        active_model
+            .downcast_ref::<E::ActiveModel>()
+            .is_some_and(|active_model| PolicyExpr::check(self, ctx, active_model))
+    }
+}
+
+/// The policies of an entity with the operations they apply to
+type EntityPolicies = Vec<(Vec<OperationType>, Box<dyn ErasedPolicy>)>;
+
+/// The row level security policies of all entities. All policies of an entity
+/// and operation have to match; entities and operations without policy are unrestricted
+/// ```ignore
+/// context.row_policies = RowPolicies::default().add::<rental::Entity>(
+///     &[OperationType::Read, OperationType::Update, OperationType::Create],
+///     policy_column::<rental::Entity>(rental::Column::CustomerId).eq(PolicyValue::user_id()),
+/// );
+/// ```
+#[derive(Default)]
+pub struct RowPolicies {
+    policies: HashMap<TypeId, EntityPolicies>,
+}
+
+impl RowPolicies {
+    /// used to add a policy of entity `E` for the given operations
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<E>(mut self, actions: &[OperationType], policy: PolicyExpr<E>) -> Self
+    where
+        E: EntityTrait,
+        E::ActiveModel: 'static,
+    {
+        self.policies
+            .entry(TypeId::of::<E>())
+            .or_default()
+            .push((actions.to_vec(), Box::new(policy)));
+        self
+    }
+
+    fn policies<E>(&self, action: OperationType) -> impl Iterator<Item = &dyn ErasedPolicy>
+    where
+        E: EntityTrait,
+    {
+        self.policies
+            .get(&TypeId::of::<E>())
+            .into_iter()
+            .flatten()
+            .filter(move |(actions, _)| actions.contains(&action))
+            .map(|(_, policy)| policy.as_ref())
+    }
+
+    /// used to get the condition of entity `E` for an operation, if it has policies
+    pub fn condition<E>(&self, ctx: &ResolverContext, action: OperationType) -> Option<Condition>
+    where
+        E: EntityTrait,
+    {
+        let conditions: Vec<Condition> = self
+            .policies::<E>(action)
+            .map(|policy| policy.condition(ctx))
+            .collect();
+
+        (!conditions.is_empty()).then(|| {
+            conditions
+                .into_iter()
+                .fold(Condition::all(), |condition, other| condition.add(other))
+        })
+    }
+
+    /// used to check an ActiveModel of entity `E` against its policies for an operation
+    pub fn check<E>(
+        &self,
+        ctx: &ResolverContext,
+        action: OperationType,
+        active_model: &E::ActiveModel,
+    ) -> bool
+    where
+        E: EntityTrait,
+        E::ActiveModel: 'static,
+    {
+        self.policies::<E>(action)
+            .all(|policy| policy.check(ctx, active_model))
+    }
+}
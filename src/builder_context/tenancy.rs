@@ -0,0 +1,109 @@
+use std::{any::TypeId, collections::BTreeSet};
+
+use async_graphql::dynamic::ResolverContext;
+use sea_orm::{
+    sea_query::{Condition, Expr, ExprTrait},
+    ActiveModelTrait, EntityTrait, IdenStatic, Iterable, Value,
+};
+
+use crate::{audit_log, BuilderContext, EntityColumnId};
+
+/// The configuration of shared-schema multi-tenancy. Entities with a tenant column
+/// are scoped to the tenant of the request's `TenantContext`: reads, relations,
+/// `having` subqueries, updates and deletes only match its rows, and inserts get the
+/// tenant column set server-side, which is hidden from the input types
+#[derive(Default)]
+pub struct TenancyConfig {
+    /// name of the tenant column by convention, e.g. `tenant_id`; every entity
+    /// having a column of that name is scoped
+    pub column: Option<String>,
+    /// tenant columns of entities not following the convention
+    pub entity_columns: BTreeSet<EntityColumnId>,
+}
+
+impl TenancyConfig {
+    /// whether a tenant column is configured
+    pub fn is_enabled(&self) -> bool {
+        self.column.is_some() || !self.entity_columns.is_empty()
+    }
+
+    /// used to get the tenant column of a SeaORM entity, if it is scoped
+    pub fn tenant_column<T>(&self) -> Option<T::Column>
+    where
+        T: EntityTrait,
+    {
+        T::Column::iter()
+            .find(|column| {
+                self.entity_columns
+                    .contains(&EntityColumnId::of::<T>(column))
+            })
+            .or_else(|| {
+                let name = self.column.as_deref()?;
+                T::Column::iter().find(|column| column.as_str() == name)
+            })
+    }
+}
+
+/// The tenant of the request, added to the request data next to the `UserContext`
+#[derive(Clone, Debug)]
+pub struct TenantContext {
+    /// of the type of the tenant columns
+    pub tenant_id: Value,
+}
+
+impl TenantContext {
+    pub fn new<V>(tenant_id: V) -> Self
+    where
+        V: Into<Value>,
+    {
+        Self {
+            tenant_id: tenant_id.into(),
+        }
+    }
+}
+
+/// used to get the condition scoping a SeaORM entity to the tenant of the request.
+/// Without tenant, no row of a scoped entity matches
+pub(crate) fn tenant_condition<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+) -> Option<Condition>
+where
+    T: EntityTrait,
+{
+    // the audit log records the tenant of every change as text
+    if TypeId::of::<T>() == TypeId::of::<audit_log::Entity>() {
+        return audit_log::tenant_condition(ctx, context);
+    }
+
+    let column = context.tenancy.tenant_column::<T>()?;
+
+    Some(match ctx.data_opt::<TenantContext>() {
+        Some(tenant) => {
+            Condition::all().add(Expr::col((T::default(), column)).eq(tenant.tenant_id.clone()))
+        }
+        None => Condition::all().add(Expr::value(1).eq(0)),
+    })
+}
+
+/// used to set the tenant column of an inserted row to the tenant of the request
+pub(crate) fn assign_tenant<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    active_model: &mut T::ActiveModel,
+) -> Result<(), String>
+where
+    T: EntityTrait,
+{
+    let Some(column) = context.tenancy.tenant_column::<T>() else {
+        return Ok(());
+    };
+
+    let tenant = ctx
+        .data_opt::<TenantContext>()
+        .ok_or_else(|| "No tenant in the request.".to_owned())?;
+
+    active_model
+        .try_set(column, tenant.tenant_id.clone())
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,227 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use sea_orm::{EntityTrait, Iterable};
+
+use crate::{validation_error, BuilderContext, EntityColumnId, EntityObjectBuilder};
+
+pub type FnValidator = Arc<dyn Fn(&sea_orm::Value) -> Result<(), String> + Send + Sync>;
+
+/// A rule that input values of a column have to satisfy.
+/// Null values are not validated, nullability is enforced by the schema
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum ValidationRule {
+    /// number of characters of a string
+    Length {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    /// inclusive bounds of a number
+    Range { min: Option<f64>, max: Option<f64> },
+    /// string has to match the regular expression
+    #[cfg(feature = "validation-regex")]
+    Regex(regex::Regex),
+    /// string has to look like an email address
+    Email,
+    /// string has to look like an absolute URL
+    Url,
+    /// custom validator returning the error message on failure
+    Custom(FnValidator),
+}
+
+impl ValidationRule {
+    /// used to create a custom rule from a closure
+    pub fn custom<F>(validator: F) -> Self
+    where
+        F: Fn(&sea_orm::Value) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(validator))
+    }
+
+    /// used to create a regex rule, panics on an invalid pattern
+    #[cfg(feature = "validation-regex")]
+    pub fn regex(pattern: &str) -> Self {
+        Self::Regex(regex::Regex::new(pattern).expect("valid regular expression"))
+    }
+
+    /// used to check a value against the rule, returning the error message on failure
+    pub fn check(&self, value: &sea_orm::Value) -> Result<(), String> {
+        match self {
+            Self::Length { min, max } => {
+                let length = match string_value(value) {
+                    Some(value) => value.chars().count(),
+                    None => return Ok(()),
+                };
+                if let Some(min) = min {
+                    if length < *min {
+                        return Err(format!("must be at least {min} characters long"));
+                    }
+                }
+                if let Some(max) = max {
+                    if length > *max {
+                        return Err(format!("must be at most {max} characters long"));
+                    }
+                }
+                Ok(())
+            }
+            Self::Range { min, max } => {
+                let number = match number_value(value) {
+                    Some(number) => number,
+                    None => return Ok(()),
+                };
+                if let Some(min) = min {
+                    if number < *min {
+                        return Err(format!("must be greater than or equal to {min}"));
+                    }
+                }
+                if let Some(max) = max {
+                    if number > *max {
+                        return Err(format!("must be less than or equal to {max}"));
+                    }
+                }
+                Ok(())
+            }
+            #[cfg(feature = "validation-regex")]
+            Self::Regex(regex) => match string_value(value) {
+                Some(value) if !regex.is_match(value) => {
+                    Err(format!("must match the pattern `{}`", regex.as_str()))
+                }
+                _ => Ok(()),
+            },
+            Self::Email => match string_value(value) {
+                Some(value) if !is_email(value) => Err("must be a valid email address".into()),
+                _ => Ok(()),
+            },
+            Self::Url => match string_value(value) {
+                Some(value) if !is_url(value) => Err("must be a valid URL".into()),
+                _ => Ok(()),
+            },
+            Self::Custom(validator) => {
+                if is_null(value) {
+                    Ok(())
+                } else {
+                    validator(value)
+                }
+            }
+        }
+    }
+}
+
+/// Used to hold the input validation rules of columns. Rules cannot be set through
+/// `#[sea_orm(seaography(..))]` attributes yet, as SeaORM only parses `ignore` there
+#[derive(Default)]
+pub struct ValidationConfig {
+    pub rules: BTreeMap<EntityColumnId, Vec<ValidationRule>>,
+}
+
+/// used to validate parsed input values, keyed by GraphQL field name,
+/// reporting every failing field at once
+pub fn validate_input<T>(
+    context: &'static BuilderContext,
+    data: &BTreeMap<String, sea_orm::Value>,
+) -> async_graphql::Result<()>
+where
+    T: EntityTrait,
+{
+    if context.validation.rules.is_empty() {
+        return Ok(());
+    }
+
+    let entity_object_builder = EntityObjectBuilder { context };
+
+    let mut errors: Vec<(String, String)> = Vec::new();
+
+    for column in T::Column::iter() {
+        let rules = match context
+            .validation
+            .rules
+            .get(&EntityColumnId::of::<T>(&column))
+        {
+            Some(rules) => rules,
+            None => continue,
+        };
+        let column_name = entity_object_builder.column_name::<T>(&column);
+        let value = match data.get(&column_name) {
+            Some(value) => value,
+            None => continue,
+        };
+
+        for rule in rules {
+            if let Err(message) = rule.check(value) {
+                errors.push((column_name.clone(), message));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(validation_error(
+            &entity_object_builder.type_name::<T>(),
+            errors,
+        ))
+    }
+}
+
+fn is_null(value: &sea_orm::Value) -> bool {
+    *value == value.as_null()
+}
+
+fn string_value(value: &sea_orm::Value) -> Option<&str> {
+    match value {
+        sea_orm::Value::String(Some(value)) => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+fn number_value(value: &sea_orm::Value) -> Option<f64> {
+    match value {
+        sea_orm::Value::TinyInt(Some(value)) => Some(*value as f64),
+        sea_orm::Value::SmallInt(Some(value)) => Some(*value as f64),
+        sea_orm::Value::Int(Some(value)) => Some(*value as f64),
+        sea_orm::Value::BigInt(Some(value)) => Some(*value as f64),
+        sea_orm::Value::TinyUnsigned(Some(value)) => Some(*value as f64),
+        sea_orm::Value::SmallUnsigned(Some(value)) => Some(*value as f64),
+        sea_orm::Value::Unsigned(Some(value)) => Some(*value as f64),
+        sea_orm::Value::BigUnsigned(Some(value)) => Some(*value as f64),
+        sea_orm::Value::Float(Some(value)) => Some(*value as f64),
+        sea_orm::Value::Double(Some(value)) => Some(*value),
+        #[cfg(feature = "with-decimal")]
+        sea_orm::Value::Decimal(Some(value)) => value.to_string().parse().ok(),
+        #[cfg(feature = "with-bigdecimal")]
+        sea_orm::Value::BigDecimal(Some(value)) => value.to_string().parse().ok(),
+        // decimals are passed as strings without a decimal library
+        sea_orm::Value::String(Some(value)) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+fn is_email(value: &str) -> bool {
+    let (local, domain) = match value.split_once('@') {
+        Some(parts) => parts,
+        None => return false,
+    };
+
+    !local.is_empty()
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !value.chars().any(char::is_whitespace)
+}
+
+fn is_url(value: &str) -> bool {
+    let (scheme, rest) = match value.split_once("://") {
+        Some(parts) => parts,
+        None => return false,
+    };
+
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !host.is_empty()
+        && !value.chars().any(char::is_whitespace)
+}
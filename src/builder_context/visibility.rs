@@ -0,0 +1,150 @@
+use async_graphql::dynamic::ResolverContext;
+use sea_orm::EntityTrait;
+
+use crate::{BuilderContext, EntityObjectBuilder, OperationType, RelationBuilder, UserContext};
+
+/// A restriction of an entity or one of its fields to roles
+struct VisibilityRule {
+    entity: String,
+    field: Option<String>,
+    actions: Vec<OperationType>,
+    roles: Vec<String>,
+}
+
+/// The roles entities, their operations and fields are restricted to. The rules
+/// block users of the `UserContext` without one of the roles at runtime, and prune
+/// schemas built for `schema_roles`: queries, mutations, relations, columns and their
+/// filter, order and input fields the roles cannot use are left out, so introspection
+/// only shows what the roles can call. Custom queries and mutations are registered as is
+/// ```ignore
+/// fn visibility() -> VisibilityConfig {
+///     VisibilityConfig::default()
+///         .restrict_entity("Staff", &[OperationType::Read], &["admin"])
+///         .restrict_field("Customer", "email", &[OperationType::Read], &["admin", "clerk"])
+/// }
+///
+/// static ref ADMIN: BuilderContext = BuilderContext { visibility: visibility(), ..Default::default() };
+/// static ref PUBLIC: BuilderContext = BuilderContext {
+///     visibility: visibility().for_roles(&[]),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Default)]
+pub struct VisibilityConfig {
+    /// the roles the schema is built for, `None` builds the full schema
+    pub schema_roles: Option<Vec<String>>,
+    rules: Vec<VisibilityRule>,
+}
+
+impl VisibilityConfig {
+    /// used to restrict operations of an entity, named by its object type, to roles
+    pub fn restrict_entity(
+        mut self,
+        entity: &str,
+        actions: &[OperationType],
+        roles: &[&str],
+    ) -> Self {
+        self.rules.push(VisibilityRule {
+            entity: entity.into(),
+            field: None,
+            actions: actions.to_vec(),
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+        });
+        self
+    }
+
+    /// used to restrict a column or relation field of an entity to roles. Reads restrict
+    /// the output, filter and order fields, creates and updates the input fields
+    pub fn restrict_field(
+        mut self,
+        entity: &str,
+        field: &str,
+        actions: &[OperationType],
+        roles: &[&str],
+    ) -> Self {
+        self.rules.push(VisibilityRule {
+            entity: entity.into(),
+            field: Some(field.into()),
+            actions: actions.to_vec(),
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+        });
+        self
+    }
+
+    /// used to build the schema for the given roles
+    pub fn for_roles(mut self, roles: &[&str]) -> Self {
+        self.schema_roles = Some(roles.iter().map(|role| role.to_string()).collect());
+        self
+    }
+
+    /// used to check whether any of the roles may perform the operation on the
+    /// entity, or on the field if given; each matching rule needs one of its roles
+    pub fn allows(
+        &self,
+        roles: &[String],
+        entity: &str,
+        field: Option<&str>,
+        action: OperationType,
+    ) -> bool {
+        self.rules
+            .iter()
+            .filter(|rule| {
+                rule.entity == entity
+                    && rule.actions.contains(&action)
+                    && (rule.field.is_none() || rule.field.as_deref() == field)
+            })
+            .all(|rule| rule.roles.iter().any(|role| roles.contains(role)))
+    }
+
+    /// used to check whether the schema leaves out the entity or field for an operation
+    pub fn is_hidden(&self, entity: &str, field: Option<&str>, action: OperationType) -> bool {
+        self.schema_roles
+            .as_ref()
+            .is_some_and(|roles| !self.allows(roles, entity, field, action))
+    }
+
+    /// used to check whether the schema leaves out a column field. The fields of hidden
+    /// entities are kept, as their types are left unreferenced and must not be empty
+    pub fn is_field_hidden(&self, entity: &str, field: &str, action: OperationType) -> bool {
+        self.is_hidden(entity, Some(field), action) && !self.is_hidden(entity, None, action)
+    }
+
+    /// used to check whether the user of the request may perform the operation,
+    /// users without `UserContext` have no role
+    pub fn allows_user(
+        &self,
+        ctx: &ResolverContext,
+        entity: &str,
+        field: Option<&str>,
+        action: OperationType,
+    ) -> bool {
+        let roles = ctx
+            .data_opt::<UserContext>()
+            .map(|user_context| user_context.roles.as_slice())
+            .unwrap_or_default();
+
+        self.allows(roles, entity, field, action)
+    }
+}
+
+/// used to check whether a relation of entity `T` is left out of the schema,
+/// as the relation field or the related entity is hidden
+pub(crate) fn relation_is_hidden<T, R>(context: &'static BuilderContext, relation: &R) -> bool
+where
+    T: EntityTrait,
+    R: RelationBuilder,
+{
+    let visibility = &context.visibility;
+
+    visibility.is_hidden(
+        &EntityObjectBuilder { context }.type_name::<T>(),
+        Some(&relation.get_relation_name(context)),
+        OperationType::Read,
+    ) || visibility.is_hidden(
+        relation
+            .get_related_entity_filter(context)
+            .related_type_name(),
+        None,
+        OperationType::Read,
+    )
+}
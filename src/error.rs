@@ -1,5 +1,13 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo},
+    ServerError, ServerResult, Value,
+};
 use thiserror::Error;
 
+use crate::BuilderContext;
+
 #[derive(Error, Debug)]
 pub enum SeaographyError {
     #[error("[async_graphql] {0:?}")]
@@ -31,3 +39,314 @@ impl From<async_graphql::InputValueError<async_graphql::Upload>> for SeaographyE
 }
 
 pub type SeaResult<T> = Result<T, SeaographyError>;
+
+/// The `extensions.code` of the errors reported by the resolvers, stable across
+/// database backends so clients can branch on them
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorCode {
+    BadUserInput,
+    Forbidden,
+    NotFound,
+    ValidationFailed,
+    Conflict,
+    UniqueViolation,
+    ForeignKeyViolation,
+    NotNullViolation,
+    CheckViolation,
+    PaginationLimitExceeded,
+    TooManyAffectedRows,
+    InternalServerError,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::BadUserInput => "BAD_USER_INPUT",
+            ErrorCode::Forbidden => "FORBIDDEN",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::ValidationFailed => "VALIDATION_FAILED",
+            ErrorCode::Conflict => "CONFLICT",
+            ErrorCode::UniqueViolation => "UNIQUE_VIOLATION",
+            ErrorCode::ForeignKeyViolation => "FOREIGN_KEY_VIOLATION",
+            ErrorCode::NotNullViolation => "NOT_NULL_VIOLATION",
+            ErrorCode::CheckViolation => "CHECK_VIOLATION",
+            ErrorCode::PaginationLimitExceeded => "PAGINATION_LIMIT_EXCEEDED",
+            ErrorCode::TooManyAffectedRows => "TOO_MANY_AFFECTED_ROWS",
+            ErrorCode::InternalServerError => "INTERNAL_SERVER_ERROR",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// used to create an error with `extensions.code`
+pub fn coded_error(code: ErrorCode, message: impl Into<String>) -> async_graphql::Error {
+    use async_graphql::ErrorExtensions;
+
+    async_graphql::Error::new(message).extend_with(|_, extensions| {
+        extensions.set("code", code.as_str());
+    })
+}
+
+/// used to report an optimistic concurrency conflict, distinguishable by `extensions.code`
+pub fn conflict_error(entity: &str) -> async_graphql::Error {
+    use async_graphql::ErrorExtensions;
+
+    coded_error(
+        ErrorCode::Conflict,
+        format!("Version conflict: {entity} has been modified by another request."),
+    )
+    .extend_with(|_, extensions| {
+        extensions.set("entity", entity);
+    })
+}
+
+/// used to report failed input validation, with one `{ field, message }` entry per failure
+pub fn validation_error(entity: &str, errors: Vec<(String, String)>) -> async_graphql::Error {
+    use async_graphql::ErrorExtensions;
+
+    let message = errors
+        .iter()
+        .map(|(field, message)| format!("`{field}` {message}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let fields = serde_json::Value::Array(
+        errors
+            .into_iter()
+            .map(|(field, message)| serde_json::json!({ "field": field, "message": message }))
+            .collect(),
+    );
+
+    coded_error(
+        ErrorCode::ValidationFailed,
+        format!("Validation failed for {entity}: {message}."),
+    )
+    .extend_with(|_, extensions| {
+        extensions.set("entity", entity);
+        if let Ok(fields) = async_graphql::Value::from_json(fields.clone()) {
+            extensions.set("fields", fields);
+        }
+    })
+}
+
+/// used to classify a database error, with the name of the violated constraint if any.
+/// Constraint violations are recognized from the messages of Postgres, MySQL and SQLite;
+/// SQLite does not name its constraints, the violating columns are reported instead
+pub fn classify_db_error(error: &sea_orm::DbErr) -> (ErrorCode, Option<String>) {
+    use sea_orm::{DbErr, RuntimeErr, SqlErr};
+
+    let message = match error {
+        // the pagination limits are checked before the statement runs
+        DbErr::Query(RuntimeErr::Internal(message))
+            if message.starts_with("Requested pagination limit") =>
+        {
+            return if message.contains("exceeds maximum allowed") {
+                (ErrorCode::PaginationLimitExceeded, None)
+            } else {
+                (ErrorCode::BadUserInput, None)
+            };
+        }
+        DbErr::RecordNotFound(_) | DbErr::RecordNotUpdated => return (ErrorCode::NotFound, None),
+        DbErr::AccessDenied { .. } => return (ErrorCode::Forbidden, None),
+        DbErr::Exec(_) | DbErr::Query(_) => error.to_string(),
+        _ => return (ErrorCode::InternalServerError, None),
+    };
+
+    let unique = quoted_after(&message, "unique constraint ", '"', '"')
+        .or_else(|| quoted_after(&message, " for key ", '\'', '\''))
+        .or_else(|| rest_after(&message, "UNIQUE constraint failed: "));
+    let foreign_key = quoted_after(&message, "foreign key constraint ", '"', '"')
+        .or_else(|| quoted_after(&message, "CONSTRAINT ", '`', '`'));
+    let not_null = quoted_after(&message, "null value in column ", '"', '"')
+        .or_else(|| {
+            message
+                .contains("cannot be null")
+                .then(|| quoted_after(&message, "Column ", '\'', '\''))
+                .flatten()
+        })
+        .or_else(|| rest_after(&message, "NOT NULL constraint failed: "));
+    let check = quoted_after(&message, "check constraint ", '"', '"')
+        .or_else(|| quoted_after(&message, "Check constraint ", '\'', '\''))
+        .or_else(|| rest_after(&message, "CHECK constraint failed: "));
+
+    match error.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => return (ErrorCode::UniqueViolation, unique),
+        Some(SqlErr::ForeignKeyConstraintViolation(_)) => {
+            return (ErrorCode::ForeignKeyViolation, foreign_key)
+        }
+        _ => {}
+    }
+
+    if message.contains("UNIQUE constraint failed")
+        || message.contains("violates unique constraint")
+        || message.contains("Duplicate entry")
+    {
+        (ErrorCode::UniqueViolation, unique)
+    } else if message.contains("FOREIGN KEY constraint failed")
+        || message.contains("violates foreign key constraint")
+        || message.contains("a foreign key constraint fails")
+    {
+        (ErrorCode::ForeignKeyViolation, foreign_key)
+    } else if not_null.is_some() || message.contains("violates not-null constraint") {
+        (ErrorCode::NotNullViolation, not_null)
+    } else if check.is_some() {
+        (ErrorCode::CheckViolation, check)
+    } else {
+        (ErrorCode::InternalServerError, None)
+    }
+}
+
+/// used to get the text quoted after the marker
+fn quoted_after(message: &str, marker: &str, open: char, close: char) -> Option<String> {
+    let rest = &message[message.find(marker)? + marker.len()..];
+    let rest = rest.strip_prefix(open)?;
+    Some(rest[..rest.find(close)?].to_string())
+}
+
+/// used to get the text after the marker
+fn rest_after(message: &str, marker: &str) -> Option<String> {
+    let rest = message[message.find(marker)? + marker.len()..].trim();
+    (!rest.is_empty()).then(|| rest.to_string())
+}
+
+/// The configuration of the errors reported to clients
+#[derive(Default)]
+pub struct ErrorConfig {
+    /// add `extensions.code` to the database and conversion errors, and the entity and
+    /// field of the resolved field to all coded errors
+    pub enabled: bool,
+    /// replace the messages of database and internal errors, which may contain SQL,
+    /// with generic ones. The codes and constraint names are still reported, even if
+    /// not `enabled`
+    pub hide_internal: bool,
+}
+
+/// This extension adds `extensions.code` to the database and conversion errors of the
+/// resolvers, and the entity and field of the resolved field to all coded errors
+pub struct ErrorExtension {
+    pub context: &'static BuilderContext,
+    /// the entity object names by root field name
+    pub entities: Arc<BTreeMap<String, String>>,
+}
+
+impl ExtensionFactory for ErrorExtension {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ErrorExtension {
+            context: self.context,
+            entities: self.entities.clone(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for ErrorExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let (entity, field) = match info.parent_type {
+            "Query" | "Mutation" | "Subscription" => (self.entities.get(info.name).cloned(), None),
+            parent_type => (Some(parent_type.to_string()), Some(info.name.to_string())),
+        };
+
+        next.run(ctx, info)
+            .await
+            .map_err(|error| self.extend_error(error, entity, field))
+    }
+}
+
+impl ErrorExtension {
+    fn extend_error(
+        &self,
+        mut error: ServerError,
+        entity: Option<String>,
+        field: Option<String>,
+    ) -> ServerError {
+        // coded errors wrapped by conversions keep their message and extensions
+        let wrapped = match error.source::<SeaographyError>() {
+            Some(SeaographyError::AsyncGraphQLError(inner))
+                if inner
+                    .extensions
+                    .as_ref()
+                    .is_some_and(|extensions| extensions.get("code").is_some()) =>
+            {
+                Some((inner.message.clone(), inner.extensions.clone()))
+            }
+            _ => None,
+        };
+        if let Some((message, extensions)) = wrapped {
+            error.message = message;
+            error.extensions = extensions;
+        }
+
+        let code = error
+            .extensions
+            .as_ref()
+            .and_then(|extensions| extensions.get("code"));
+        if code.is_none() {
+            // database errors wrapped by conversions are classified as such
+            let db_err = error.source::<sea_orm::DbErr>().or_else(|| {
+                match error.source::<SeaographyError>() {
+                    Some(SeaographyError::AsyncGraphQLError(inner)) => inner
+                        .source
+                        .as_ref()
+                        .and_then(|source| source.downcast_ref::<sea_orm::DbErr>()),
+                    _ => None,
+                }
+            });
+            let (code, constraint) = if let Some(db_err) = db_err {
+                classify_db_error(db_err)
+            } else if error.source::<SeaographyError>().is_some() {
+                (ErrorCode::BadUserInput, None)
+            } else {
+                return error;
+            };
+
+            if self.context.errors.hide_internal {
+                if let Some(message) = hidden_message(code) {
+                    error.message = message.into();
+                }
+            }
+            let extensions = error.extensions.get_or_insert_with(Default::default);
+            extensions.set("code", code.as_str());
+            if let Some(constraint) = constraint {
+                extensions.set("constraint", constraint);
+            }
+        }
+        let Some(extensions) = error.extensions.as_mut() else {
+            return error;
+        };
+
+        // nested fields only report the entity objects
+        let entity = entity
+            .filter(|entity| field.is_none() || self.entities.values().any(|name| name == entity));
+        if let (None, Some(entity)) = (extensions.get("entity"), &entity) {
+            extensions.set("entity", entity.as_str());
+            if let (None, Some(field)) = (extensions.get("field"), field) {
+                extensions.set("field", field);
+            }
+        }
+
+        error
+    }
+}
+
+/// used to get the generic message of the errors hidden by `hide_internal`
+pub(crate) fn hidden_message(code: ErrorCode) -> Option<&'static str> {
+    match code {
+        ErrorCode::UniqueViolation => Some("Unique constraint violated."),
+        ErrorCode::ForeignKeyViolation => Some("Foreign key constraint violated."),
+        ErrorCode::NotNullViolation => Some("Not null constraint violated."),
+        ErrorCode::CheckViolation => Some("Check constraint violated."),
+        ErrorCode::NotFound => Some("Record not found."),
+        ErrorCode::Forbidden => Some("Access denied."),
+        ErrorCode::InternalServerError => Some("Internal server error."),
+        _ => None,
+    }
+}
@@ -1,9 +1,12 @@
 use std::collections::BTreeMap;
 
-use async_graphql::dynamic::{InputObject, InputValue, ObjectAccessor};
+use async_graphql::dynamic::{InputObject, InputValue, ObjectAccessor, TypeRef};
 use sea_orm::{ColumnTrait, EntityTrait, Iterable, PrimaryKeyToColumn, PrimaryKeyTrait};
 
-use crate::{BuilderContext, EntityColumnId, EntityObjectBuilder, SeaResult, TypesMapHelper};
+use crate::{
+    BuilderContext, EntityColumnId, EntityObjectBuilder, EntityUpdateMutationBuilder,
+    OperationType, SeaResult, TypesMapHelper, UpdateOperation, UpdateOperationInputBuilder,
+};
 
 /// The configuration structure of EntityInputBuilder
 pub struct EntityInputConfig {
@@ -15,6 +18,8 @@ pub struct EntityInputConfig {
     pub update_suffix: String,
     /// names of "{entity}.{column}" you want to skip the update input to be generated
     pub update_skips: Vec<String>,
+    /// suffix that is appended on the per-row input objects of batch updates
+    pub update_batch_suffix: String,
 }
 
 impl std::default::Default for EntityInputConfig {
@@ -24,6 +29,7 @@ impl std::default::Default for EntityInputConfig {
             insert_skips: Vec::new(),
             update_suffix: "UpdateInput".into(),
             update_skips: Vec::new(),
+            update_batch_suffix: "UpdateBatchInput".into(),
         }
     }
 }
@@ -58,6 +64,21 @@ impl EntityInputBuilder {
         format!("{}{}", object_name, self.context.entity_input.update_suffix)
     }
 
+    /// used to get SeaORM entity batch update row input object name
+    pub fn update_batch_type_name<T>(&self) -> String
+    where
+        T: EntityTrait,
+    {
+        let entity_object_builder = EntityObjectBuilder {
+            context: self.context,
+        };
+        let object_name = entity_object_builder.type_name::<T>();
+        format!(
+            "{}{}",
+            object_name, self.context.entity_input.update_batch_suffix
+        )
+    }
+
     /// used to produce the SeaORM entity input object
     fn input_object<T>(&self, is_insert: bool) -> InputObject
     where
@@ -93,6 +114,42 @@ impl EntityInputBuilder {
                 return object;
             }
 
+            let action = if is_insert {
+                OperationType::Create
+            } else {
+                OperationType::Update
+            };
+            if self.context.visibility.is_field_hidden(
+                &entity_object_builder.type_name::<T>(),
+                &column_name,
+                action,
+            ) {
+                return object;
+            }
+
+            // version columns are bumped by the update mutation itself
+            if !is_insert
+                && self
+                    .context
+                    .entity_update_mutation
+                    .version_columns
+                    .contains(&entity_column_id)
+            {
+                return object;
+            }
+
+            // the tenant column is set from the request
+            if self
+                .context
+                .tenancy
+                .tenant_column::<T>()
+                .is_some_and(|tenant_column| {
+                    EntityColumnId::of::<T>(&tenant_column) == entity_column_id
+                })
+            {
+                return object;
+            }
+
             let column_def = column.def();
 
             if column_def.seaography().ignore {
@@ -107,6 +164,21 @@ impl EntityInputBuilder {
             let is_insert_not_nullable =
                 is_insert && !(column_def.is_null() || auto_increment || has_default_expr);
 
+            // numeric update columns accept an operation object when enabled
+            if !is_insert {
+                let update_operation_input_builder = UpdateOperationInputBuilder {
+                    context: self.context,
+                };
+                if let Some(kind) = update_operation_input_builder.kind_for_column::<T>(&column) {
+                    return object.field(InputValue::new(
+                        column_name,
+                        TypeRef::named(
+                            update_operation_input_builder.type_name(kind, column_def.is_null()),
+                        ),
+                    ));
+                }
+            }
+
             let graphql_type = match types_map_helper.input_type_for_column::<T>(
                 &column,
                 &entity_column_id,
@@ -138,6 +210,56 @@ impl EntityInputBuilder {
         self.input_object::<T>(false)
     }
 
+    /// used to produce the SeaORM entity batch update row input object,
+    /// holding the primary key, the changes and the expected version if any
+    pub fn update_batch_input_object<T>(&self) -> InputObject
+    where
+        T: EntityTrait,
+        <T as EntityTrait>::Model: Sync,
+    {
+        let entity_object_builder = EntityObjectBuilder {
+            context: self.context,
+        };
+        let types_map_helper = TypesMapHelper {
+            context: self.context,
+        };
+        let entity_update_mutation_builder = EntityUpdateMutationBuilder {
+            context: self.context,
+        };
+
+        let object = T::PrimaryKey::iter().fold(
+            InputObject::new(self.update_batch_type_name::<T>()),
+            |object, key| {
+                let column = key.into_column();
+                let graphql_type = types_map_helper
+                    .input_type_for_column::<T>(&column, &EntityColumnId::of::<T>(&column), true)
+                    .expect("primary key column to be supported");
+                object.field(InputValue::new(
+                    entity_object_builder.column_name::<T>(&column),
+                    graphql_type,
+                ))
+            },
+        );
+
+        let object = object.field(InputValue::new(
+            &self.context.entity_update_batch_mutation.changes_field,
+            TypeRef::named_nn(self.update_type_name::<T>()),
+        ));
+
+        match entity_update_mutation_builder.version_column::<T>() {
+            Some(column) => {
+                let version_type = types_map_helper
+                    .input_type_for_column::<T>(&column, &EntityColumnId::of::<T>(&column), true)
+                    .expect("version column to be supported");
+                object.field(InputValue::new(
+                    &self.context.entity_update_mutation.version_field,
+                    version_type,
+                ))
+            }
+            None => object,
+        }
+    }
+
     pub fn parse_object<T>(
         &self,
         object: &ObjectAccessor,
@@ -170,4 +292,63 @@ impl EntityInputBuilder {
 
         Ok(map)
     }
+
+    /// used to parse an update input object, numeric columns given as an operation
+    /// object are returned separately from the plain values
+    #[allow(clippy::type_complexity)]
+    pub fn parse_update_object<T>(
+        &self,
+        object: &ObjectAccessor,
+    ) -> SeaResult<(
+        BTreeMap<String, sea_orm::Value>,
+        Vec<(T::Column, UpdateOperation)>,
+    )>
+    where
+        T: EntityTrait,
+    {
+        let entity_object_builder = EntityObjectBuilder {
+            context: self.context,
+        };
+        let types_map_helper = TypesMapHelper {
+            context: self.context,
+        };
+        let update_operation_input_builder = UpdateOperationInputBuilder {
+            context: self.context,
+        };
+
+        let mut map = BTreeMap::<String, sea_orm::Value>::new();
+        let mut operations = Vec::new();
+
+        for column in T::Column::iter() {
+            let column_name = entity_object_builder.column_name::<T>(&column);
+
+            let value = match object.get(&column_name) {
+                Some(value) => value,
+                None => continue,
+            };
+
+            if update_operation_input_builder
+                .kind_for_column::<T>(&column)
+                .is_some()
+            {
+                let operation =
+                    update_operation_input_builder.parse_object::<T>(&column, &value.object()?)?;
+
+                match operation {
+                    UpdateOperation::Set(value) => {
+                        map.insert(column_name, value);
+                    }
+                    operation => operations.push((column, operation)),
+                }
+                continue;
+            }
+
+            let result =
+                types_map_helper.async_graphql_value_to_sea_orm_value::<T>(&column, &value)?;
+
+            map.insert(column_name, result);
+        }
+
+        Ok((map, operations))
+    }
 }
@@ -1,7 +1,9 @@
 use async_graphql::dynamic::{InputObject, InputValue, TypeRef};
 use sea_orm::{ColumnTrait, EntityTrait, Iterable};
 
-use crate::{pluralize_unique, BuilderContext, EntityObjectBuilder, FilterTypesMapHelper};
+use crate::{
+    pluralize_unique, BuilderContext, EntityObjectBuilder, FilterTypesMapHelper, OperationType,
+};
 
 /// The configuration structure for FilterInputBuilder
 pub struct FilterInputConfig {
@@ -51,6 +53,14 @@ impl FilterInputBuilder {
             if column.def().seaography().ignore {
                 return object;
             }
+            // filtering on a hidden column would reveal its values
+            if self.context.visibility.is_field_hidden(
+                &entity_name,
+                &entity_object_builder.column_name::<T>(&column),
+                OperationType::Read,
+            ) {
+                return object;
+            }
             match filter_types_map_helper.get_column_filter_input_value::<T>(&column) {
                 Some(field) => object.field(field),
                 None => object,
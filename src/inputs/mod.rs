@@ -28,3 +28,6 @@ pub use entity_input::*;
 
 pub mod active_enum_filter_input;
 pub use active_enum_filter_input::*;
+
+pub mod update_operation_input;
+pub use update_operation_input::*;
@@ -1,7 +1,10 @@
 use async_graphql::dynamic::{InputObject, InputValue, TypeRef, ValueAccessor};
 use sea_orm::{ColumnTrait, EntityTrait, Iterable};
 
-use crate::{pluralize_unique, BuilderContext, EntityObjectBuilder, SeaResult, SeaographyError};
+use crate::{
+    pluralize_unique, BuilderContext, EntityObjectBuilder, OperationType, SeaResult,
+    SeaographyError,
+};
 
 /// The configuration structure for OrderInputBuilder
 pub struct OrderInputConfig {
@@ -47,8 +50,16 @@ impl OrderInputBuilder {
             if column.def().seaography().ignore {
                 return object;
             }
+            let column_name = entity_object_builder.column_name::<T>(&column);
+            if self.context.visibility.is_field_hidden(
+                &object_name,
+                &column_name,
+                OperationType::Read,
+            ) {
+                return object;
+            }
             object.field(InputValue::new(
-                entity_object_builder.column_name::<T>(&column),
+                column_name,
                 TypeRef::named(&self.context.order_by_enum.type_name),
             ))
         })
@@ -0,0 +1,264 @@
+use async_graphql::dynamic::{InputObject, InputValue, ObjectAccessor, TypeRef};
+use sea_orm::{
+    sea_query::{Expr, ExprTrait, Keyword},
+    ColumnTrait, ColumnType, EntityTrait, IdenStatic,
+};
+
+use crate::{BuilderContext, EntityColumnId, SeaResult, SeaographyError, TypesMapHelper};
+
+/// The configuration structure for UpdateOperationInputBuilder
+pub struct UpdateOperationInputConfig {
+    /// if true, numeric columns of update input objects accept an operation
+    /// object (e.g. `{ increment: 1 }`) instead of a plain value
+    pub enabled: bool,
+    /// suffix that is appended on the numeric type name, e.g. `IntUpdateOperationInput`
+    pub type_suffix: String,
+    /// prefix of the type names of nullable columns, the only ones offering `setNull`,
+    /// e.g. `NullableIntUpdateOperationInput`
+    pub nullable_prefix: String,
+    /// name for 'set' field
+    pub set: String,
+    /// name for 'increment' field
+    pub increment: String,
+    /// name for 'decrement' field
+    pub decrement: String,
+    /// name for 'multiply' field
+    pub multiply: String,
+    /// name for 'setNull' field
+    pub set_null: String,
+}
+
+impl std::default::Default for UpdateOperationInputConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            type_suffix: "UpdateOperationInput".into(),
+            nullable_prefix: "Nullable".into(),
+            set: "set".into(),
+            increment: "increment".into(),
+            decrement: "decrement".into(),
+            multiply: "multiply".into(),
+            set_null: {
+                if cfg!(feature = "field-snake-case") {
+                    "set_null"
+                } else {
+                    "setNull"
+                }
+                .into()
+            },
+        }
+    }
+}
+
+/// The numeric families that support update operations
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum UpdateOperationKind {
+    Int,
+    Float,
+    Decimal,
+}
+
+impl UpdateOperationKind {
+    pub fn all() -> [Self; 3] {
+        [Self::Int, Self::Float, Self::Decimal]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Int => "Int",
+            Self::Float => "Float",
+            Self::Decimal => "Decimal",
+        }
+    }
+
+    fn operand_type(&self) -> &'static str {
+        match self {
+            Self::Int => TypeRef::INT,
+            Self::Float => TypeRef::FLOAT,
+            Self::Decimal => TypeRef::STRING,
+        }
+    }
+}
+
+/// The parsed value of an update operation input object
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateOperation {
+    Set(sea_orm::Value),
+    Increment(sea_orm::Value),
+    Decrement(sea_orm::Value),
+    Multiply(sea_orm::Value),
+    SetNull,
+}
+
+impl UpdateOperation {
+    /// used to turn the operation into the right hand side of `col = ...`
+    pub fn into_expr<C>(self, column: C) -> Expr
+    where
+        C: ColumnTrait,
+    {
+        match self {
+            Self::Set(value) => Expr::val(value),
+            Self::Increment(value) => Expr::col(column).add(value),
+            Self::Decrement(value) => Expr::col(column).sub(value),
+            Self::Multiply(value) => Expr::col(column).mul(value),
+            Self::SetNull => Expr::Keyword(Keyword::Null),
+        }
+    }
+}
+
+/// This builder produces the update operation input objects of numeric columns
+pub struct UpdateOperationInputBuilder {
+    pub context: &'static BuilderContext,
+}
+
+impl UpdateOperationInputBuilder {
+    /// used to get type name, nullable columns get their own type offering `setNull`
+    pub fn type_name(&self, kind: UpdateOperationKind, nullable: bool) -> String {
+        let config = &self.context.update_operation_input;
+
+        format!(
+            "{}{}{}",
+            if nullable {
+                config.nullable_prefix.as_str()
+            } else {
+                ""
+            },
+            kind.name(),
+            config.type_suffix
+        )
+    }
+
+    /// used to get the operation kind of a column, None if the column does not support operations
+    pub fn kind_for_column<T>(&self, column: &T::Column) -> Option<UpdateOperationKind>
+    where
+        T: EntityTrait,
+    {
+        if !self.context.update_operation_input.enabled {
+            return None;
+        }
+
+        // custom types and conversions are left untouched
+        let entity_column_id = EntityColumnId::of::<T>(column);
+        if self
+            .context
+            .types
+            .column_options
+            .contains_key(&entity_column_id)
+        {
+            return None;
+        }
+
+        // the rules could not check the computed value, ruled columns only take plain values
+        if self
+            .context
+            .validation
+            .rules
+            .contains_key(&entity_column_id)
+        {
+            return None;
+        }
+
+        match column.def().get_column_type() {
+            ColumnType::TinyInteger
+            | ColumnType::SmallInteger
+            | ColumnType::Integer
+            | ColumnType::BigInteger
+            | ColumnType::TinyUnsigned
+            | ColumnType::SmallUnsigned
+            | ColumnType::Unsigned
+            | ColumnType::BigUnsigned => Some(UpdateOperationKind::Int),
+            ColumnType::Float | ColumnType::Double => Some(UpdateOperationKind::Float),
+            ColumnType::Decimal(_) | ColumnType::Money(_) => Some(UpdateOperationKind::Decimal),
+            _ => None,
+        }
+    }
+
+    /// used to get the update operation input object of a numeric kind
+    pub fn input_object(&self, kind: UpdateOperationKind, nullable: bool) -> InputObject {
+        let config = &self.context.update_operation_input;
+
+        let object = InputObject::new(self.type_name(kind, nullable))
+            .field(InputValue::new(
+                &config.set,
+                TypeRef::named(kind.operand_type()),
+            ))
+            .field(InputValue::new(
+                &config.increment,
+                TypeRef::named(kind.operand_type()),
+            ))
+            .field(InputValue::new(
+                &config.decrement,
+                TypeRef::named(kind.operand_type()),
+            ))
+            .field(InputValue::new(
+                &config.multiply,
+                TypeRef::named(kind.operand_type()),
+            ));
+
+        if nullable {
+            object.field(InputValue::new(
+                &config.set_null,
+                TypeRef::named(TypeRef::BOOLEAN),
+            ))
+        } else {
+            object
+        }
+    }
+
+    /// used to parse an update operation input object of a column
+    pub fn parse_object<T>(
+        &self,
+        column: &T::Column,
+        object: &ObjectAccessor,
+    ) -> SeaResult<UpdateOperation>
+    where
+        T: EntityTrait,
+    {
+        let config = &self.context.update_operation_input;
+        let types_map_helper = TypesMapHelper {
+            context: self.context,
+        };
+
+        let mut operations = Vec::new();
+
+        for (name, value) in object.iter() {
+            let operation = if name == config.set.as_str() {
+                UpdateOperation::Set(
+                    types_map_helper.async_graphql_value_to_sea_orm_value::<T>(column, &value)?,
+                )
+            } else if name == config.increment.as_str() {
+                UpdateOperation::Increment(
+                    types_map_helper.async_graphql_value_to_sea_orm_value::<T>(column, &value)?,
+                )
+            } else if name == config.decrement.as_str() {
+                UpdateOperation::Decrement(
+                    types_map_helper.async_graphql_value_to_sea_orm_value::<T>(column, &value)?,
+                )
+            } else if name == config.multiply.as_str() {
+                UpdateOperation::Multiply(
+                    types_map_helper.async_graphql_value_to_sea_orm_value::<T>(column, &value)?,
+                )
+            } else if name == config.set_null.as_str() {
+                if !value.boolean()? {
+                    continue;
+                }
+                UpdateOperation::SetNull
+            } else {
+                continue;
+            };
+            operations.push(operation);
+        }
+
+        if operations.len() != 1 {
+            return Err(SeaographyError::AsyncGraphQLError(
+                format!(
+                    "Exactly one update operation is expected for column `{}`",
+                    column.as_str()
+                )
+                .into(),
+            ));
+        }
+
+        Ok(operations.remove(0))
+    }
+}
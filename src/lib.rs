@@ -297,6 +297,14 @@ pub use query::*;
 pub mod mutation;
 pub use mutation::*;
 
+pub mod subscription;
+pub use subscription::*;
+
+pub mod audit_log;
+pub use audit_log::AuditLogConfig;
+
+pub(crate) use audit_log::write_audit_log;
+
 mod custom;
 pub use custom::*;
 
@@ -0,0 +1,51 @@
+use async_graphql::{dynamic::ValueAccessor, ErrorExtensions, Value};
+
+use crate::{coded_error, ErrorCode};
+
+/// used to reject update and delete mutations without a filter condition,
+/// which would change every row of the table
+pub(crate) fn reject_unfiltered(
+    entity: &str,
+    filter: Option<ValueAccessor<'_>>,
+) -> async_graphql::Result<()> {
+    if filter.is_some_and(|filter| has_condition(filter.as_value())) {
+        return Ok(());
+    }
+
+    Err(coded_error(
+        ErrorCode::BadUserInput,
+        format!("A filter with at least one condition is required to change {entity}."),
+    ))
+}
+
+/// used to reject mutations affecting more rows than allowed, returning the error
+/// before the commit rolls the transaction back
+pub(crate) fn check_affected_rows(
+    entity: &str,
+    max_affected_rows: Option<u64>,
+    affected_rows: u64,
+) -> async_graphql::Result<()> {
+    match max_affected_rows {
+        Some(max) if affected_rows > max => Err(coded_error(
+            ErrorCode::TooManyAffectedRows,
+            format!(
+                "The mutation would affect {affected_rows} rows of {entity}, more than the maximum of {max}."
+            ),
+        )
+        .extend_with(|_, extensions| {
+            extensions.set("affectedCount", affected_rows);
+            extensions.set("maxAffectedRows", max);
+        })),
+        _ => Ok(()),
+    }
+}
+
+/// used to check whether a filter input sets any value, nested inputs without values match every row
+fn has_condition(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Object(fields) => fields.values().any(has_condition),
+        Value::List(items) => items.iter().any(has_condition),
+        _ => true,
+    }
+}
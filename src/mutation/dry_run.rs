@@ -0,0 +1,18 @@
+use async_graphql::dynamic::ResolverContext;
+
+/// Request data turning every mutation of the request into a dry run, e.g. set from
+/// a request header with `request.data(DryRun(true))`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DryRun(pub bool);
+
+/// used to check whether the mutation is a dry run, which is rolled back after
+/// reporting what it would have done. The `dryRun` argument overrides the request data
+pub(crate) fn is_dry_run(
+    ctx: &ResolverContext<'_>,
+    dry_run_field: &str,
+) -> async_graphql::Result<bool> {
+    match ctx.args.get(dry_run_field) {
+        Some(value) if !value.is_null() => Ok(value.boolean()?),
+        _ => Ok(ctx.data_opt::<DryRun>().is_some_and(|dry_run| dry_run.0)),
+    }
+}
@@ -1,11 +1,18 @@
+use std::collections::HashMap;
+
 use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, TypeRef};
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, EntityTrait, IntoActiveModel, TransactionTrait,
+    sea_query::Condition, ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait,
+    DatabaseConnection, DbBackend, EntityTrait, IdenStatic, IntoActiveModel, Iterable, ModelTrait,
+    PrimaryKeyToColumn, PrimaryKeyTrait, QueryFilter,
 };
 
 use crate::{
-    guard_error, prepare_active_model, BuilderContext, DatabaseContext, EntityInputBuilder,
-    EntityObjectBuilder, EntityQueryFieldBuilder, GuardAction, OperationType, UserContext,
+    begin_transaction, guard_error, is_dry_run, notify_entity_change, prepare_active_model,
+    resolve_before_save, resolve_entity_guard, resolve_field_guards, write_audit_log, write_outbox,
+    BuilderContext, DatabaseContext, EntityInputBuilder, EntityObjectBuilder,
+    EntityQueryFieldBuilder, GuardAction, MutationPayloadBuilder, OperationType,
+    RequestTransaction, UserContext,
 };
 
 /// The configuration structure of EntityCreateBatchMutationBuilder
@@ -14,6 +21,16 @@ pub struct EntityCreateBatchMutationConfig {
     pub mutation_suffix: String,
     /// name for `data` field
     pub data_field: String,
+    /// maximum number of bind parameters per `INSERT` statement, rows are
+    /// chunked to stay below it. `None` uses the limit of the database backend
+    pub max_parameters: Option<usize>,
+    /// name for `dryRun` field, dry runs report the created records and are rolled back
+    pub dry_run_field: String,
+    /// return a `{Entity}CreatePayload` object with the records, the affected count and
+    /// the user errors instead of the records
+    pub payload: bool,
+    /// suffix that is appended on the entity name for the payload object
+    pub payload_suffix: String,
 }
 
 impl std::default::Default for EntityCreateBatchMutationConfig {
@@ -28,6 +45,17 @@ impl std::default::Default for EntityCreateBatchMutationConfig {
                 .into()
             },
             data_field: "data".into(),
+            max_parameters: None,
+            dry_run_field: {
+                if cfg!(feature = "field-snake-case") {
+                    "dry_run"
+                } else {
+                    "dryRun"
+                }
+                .into()
+            },
+            payload: false,
+            payload_suffix: "CreatePayload".into(),
         }
     }
 }
@@ -56,7 +84,7 @@ impl EntityCreateBatchMutationBuilder {
     /// used to get the create mutation field for a SeaORM entity
     pub fn to_field<T, A>(&self) -> Field
     where
-        T: EntityTrait,
+        T: EntityTrait<ActiveModel = A>,
         <T as EntityTrait>::Model: Sync,
         <T as EntityTrait>::Model: IntoActiveModel<A>,
         A: ActiveModelTrait<Entity = T> + sea_orm::ActiveModelBehavior + Send + 'static,
@@ -71,16 +99,31 @@ impl EntityCreateBatchMutationBuilder {
         let context = self.context;
 
         let object_name: String = entity_object_builder.type_name::<T>();
-        let hooks = &self.context.hooks;
 
-        Field::new(
-            self.type_name::<T>(),
-            TypeRef::named_nn_list_nn(entity_object_builder.basic_type_name::<T>()),
-            move |ctx| {
-                let object_name = object_name.clone();
+        let payload = context.entity_create_batch_mutation.payload;
+        let payload_builder = MutationPayloadBuilder { context };
+        let output_type = if payload {
+            TypeRef::named_nn(
+                payload_builder
+                    .type_name::<T>(&context.entity_create_batch_mutation.payload_suffix),
+            )
+        } else {
+            TypeRef::named_nn_list_nn(entity_object_builder.basic_type_name::<T>())
+        };
+
+        Field::new(self.type_name::<T>(), output_type, move |ctx| {
+            let object_name = object_name.clone();
+            payload_builder.resolve::<T>(
+                payload,
+                ctx.data_opt::<RequestTransaction>(),
                 FieldFuture::new(async move {
-                    if let GuardAction::Block(reason) =
-                        hooks.entity_guard(&ctx, &object_name, OperationType::Create)
+                    if let GuardAction::Block(reason) = resolve_entity_guard::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Create,
+                    )
+                    .await
                     {
                         return Err(guard_error(reason, "Entity guard triggered."));
                     }
@@ -89,12 +132,15 @@ impl EntityCreateBatchMutationBuilder {
                         .data::<DatabaseConnection>()?
                         .restricted(ctx.data_opt::<UserContext>())?;
 
-                    let transaction = db.begin().await?;
+                    let dry_run =
+                        is_dry_run(&ctx, &context.entity_create_batch_mutation.dry_run_field)?;
+
+                    let transaction = begin_transaction(&ctx, db).await?;
 
                     let entity_input_builder = EntityInputBuilder { context };
                     let entity_object_builder = EntityObjectBuilder { context };
 
-                    let mut results: Vec<_> = Vec::new();
+                    let mut active_models: Vec<A> = Vec::new();
                     for input in ctx
                         .args
                         .try_get(&context.entity_create_batch_mutation.data_field)?
@@ -102,12 +148,20 @@ impl EntityCreateBatchMutationBuilder {
                         .iter()
                     {
                         let input_object = &input.object()?;
-                        for (column, _) in input_object.iter() {
-                            if let GuardAction::Block(reason) =
-                                hooks.field_guard(&ctx, &object_name, column, OperationType::Create)
-                            {
-                                return Err(guard_error(reason, "Field guard triggered."));
-                            }
+                        let fields: Vec<&str> = input_object
+                            .iter()
+                            .map(|(column, _)| column.as_str())
+                            .collect();
+                        if let GuardAction::Block(reason) = resolve_field_guards(
+                            &ctx,
+                            context,
+                            &object_name,
+                            &fields,
+                            OperationType::Create,
+                        )
+                        .await
+                        {
+                            return Err(guard_error(reason, "Field guard triggered."));
                         }
 
                         let mut active_model = prepare_active_model::<T, A>(
@@ -115,8 +169,9 @@ impl EntityCreateBatchMutationBuilder {
                             &entity_object_builder,
                             input_object,
                         )?;
-                        if let GuardAction::Block(reason) = hooks.before_active_model_save(
+                        if let GuardAction::Block(reason) = resolve_before_save::<T>(
                             &ctx,
+                            context,
                             &object_name,
                             OperationType::Create,
                             &mut active_model,
@@ -127,25 +182,232 @@ impl EntityCreateBatchMutationBuilder {
                             ));
                         }
 
-                        let result = active_model.insert(&transaction).await?;
-                        results.push(result);
+                        active_models.push(A::before_save(active_model, &transaction, true).await?);
+                    }
+
+                    let max_parameters = context
+                        .entity_create_batch_mutation
+                        .max_parameters
+                        .unwrap_or_else(|| max_parameters_of(db.get_database_backend()));
+
+                    let mut results: Vec<T::Model> = Vec::with_capacity(active_models.len());
+                    for chunk in insert_chunks::<T, A>(active_models, max_parameters) {
+                        results.extend(insert_chunk::<T, A, _>(chunk, &transaction).await?);
+                    }
+
+                    let mut saved: Vec<T::Model> = Vec::with_capacity(results.len());
+                    for model in results {
+                        saved.push(A::after_save(model, &transaction, true).await?);
+                    }
+                    let results = saved;
+
+                    if dry_run {
+                        transaction.rollback().await?;
+                        return Ok(Some(FieldValue::list(
+                            results.into_iter().map(FieldValue::owned_any),
+                        )));
                     }
 
+                    write_audit_log::<T, _>(
+                        &ctx,
+                        context,
+                        &transaction,
+                        OperationType::Create,
+                        None,
+                        Some(&results),
+                    )
+                    .await?;
+
+                    write_outbox::<T, _>(
+                        context,
+                        &transaction,
+                        &object_name,
+                        OperationType::Create,
+                        &results,
+                    )
+                    .await?;
+
                     transaction.commit().await?;
 
-                    hooks
-                        .entity_watch(&ctx, &object_name, OperationType::Create)
-                        .await;
+                    notify_entity_change::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Create,
+                        results.clone(),
+                        None,
+                    )
+                    .await;
 
                     Ok(Some(FieldValue::list(
                         results.into_iter().map(FieldValue::owned_any),
                     )))
-                })
-            },
-        )
+                }),
+            )
+        })
         .argument(InputValue::new(
             &context.entity_create_batch_mutation.data_field,
             TypeRef::named_nn_list_nn(entity_input_builder.insert_type_name::<T>()),
         ))
+        .argument(InputValue::new(
+            &context.entity_create_batch_mutation.dry_run_field,
+            TypeRef::named(TypeRef::BOOLEAN),
+        ))
+    }
+}
+
+/// used to get the bind parameter limit of a database backend
+fn max_parameters_of(backend: DbBackend) -> usize {
+    match backend {
+        DbBackend::MySql => 65535,
+        DbBackend::Postgres => 65535,
+        DbBackend::Sqlite => 32766,
+        _ => 999,
     }
 }
+
+/// used to split active models into chunks that can be inserted by one statement:
+/// consecutive models setting the same columns, below the parameter limit
+fn insert_chunks<T, A>(active_models: Vec<A>, max_parameters: usize) -> Vec<Vec<A>>
+where
+    T: EntityTrait,
+    A: ActiveModelTrait<Entity = T>,
+{
+    let mut chunks: Vec<(Vec<bool>, Vec<A>)> = Vec::new();
+
+    for active_model in active_models {
+        // NotSet columns of a multi-row insert would become NULL instead of their default
+        let columns: Vec<bool> = T::Column::iter()
+            .map(|column| !active_model.is_not_set(column))
+            .collect();
+        let parameters = columns.iter().filter(|set| **set).count().max(1);
+        let max_rows = (max_parameters / parameters).max(1);
+
+        match chunks.last_mut() {
+            Some((chunk_columns, chunk)) if *chunk_columns == columns && chunk.len() < max_rows => {
+                chunk.push(active_model)
+            }
+            _ => chunks.push((columns, vec![active_model])),
+        }
+    }
+
+    chunks.into_iter().map(|(_, chunk)| chunk).collect()
+}
+
+/// used to insert one chunk of active models. The models are returned in input order when
+/// every row sets its primary key; rows with generated keys come back in the order of the
+/// database, which RETURNING does not guarantee. Without RETURNING (MySQL), rows with
+/// auto-increment keys fall back to one insert per row
+async fn insert_chunk<T, A, C>(
+    chunk: Vec<A>,
+    transaction: &C,
+) -> async_graphql::Result<Vec<T::Model>>
+where
+    T: EntityTrait,
+    <T as EntityTrait>::Model: IntoActiveModel<A>,
+    A: ActiveModelTrait<Entity = T> + sea_orm::ActiveModelBehavior + Send,
+    C: ConnectionTrait,
+{
+    let keys = primary_keys::<T, A>(&chunk);
+
+    if transaction.support_returning() {
+        let models = T::insert_many(chunk)
+            .exec_with_returning(transaction)
+            .await?;
+        return match keys {
+            Some(keys) => in_key_order::<T>(models, keys),
+            None => Ok(models),
+        };
+    }
+
+    // without RETURNING, only rows with known primary keys can be read back
+    // after a multi-row insert; generated keys fall back to one insert per row
+    let keys = match keys {
+        Some(keys) => keys,
+        None if T::PrimaryKey::auto_increment() => {
+            let mut results = Vec::with_capacity(chunk.len());
+            for active_model in chunk {
+                let result = T::insert(active_model)
+                    .exec_with_returning(transaction)
+                    .await?;
+                results.push(result);
+            }
+            return Ok(results);
+        }
+        None => {
+            return Err(async_graphql::Error::new(format!(
+                "Primary key `{}` is required.",
+                T::PrimaryKey::iter()
+                    .map(|key| key.into_column().as_str())
+                    .collect::<Vec<_>>()
+                    .join("`, `")
+            )))
+        }
+    };
+
+    let condition = keys.iter().fold(Condition::any(), |condition, key| {
+        condition.add(
+            T::PrimaryKey::iter()
+                .zip(key.iter())
+                .fold(Condition::all(), |condition, (column, value)| {
+                    condition.add(column.into_column().eq(value.clone()))
+                }),
+        )
+    });
+
+    T::insert_many(chunk)
+        .exec_without_returning(transaction)
+        .await?;
+
+    let models = T::find().filter(condition).all(transaction).await?;
+
+    in_key_order::<T>(models, keys)
+}
+
+/// used to get the primary keys set by the active models, None if any row leaves one to the database
+fn primary_keys<T, A>(chunk: &[A]) -> Option<Vec<Vec<sea_orm::Value>>>
+where
+    T: EntityTrait,
+    A: ActiveModelTrait<Entity = T>,
+{
+    chunk
+        .iter()
+        .map(|active_model| {
+            T::PrimaryKey::iter()
+                .map(|key| match active_model.get(key.into_column()) {
+                    ActiveValue::Set(value) | ActiveValue::Unchanged(value) => Some(value),
+                    ActiveValue::NotSet => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// used to order inserted models like their primary keys
+fn in_key_order<T>(
+    models: Vec<T::Model>,
+    keys: Vec<Vec<sea_orm::Value>>,
+) -> async_graphql::Result<Vec<T::Model>>
+where
+    T: EntityTrait,
+{
+    let mut models: HashMap<Vec<sea_orm::Value>, T::Model> = models
+        .into_iter()
+        .map(|model| {
+            let key = T::PrimaryKey::iter()
+                .map(|column| model.get(column.into_column()))
+                .collect();
+            (key, model)
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(keys.len());
+    for key in keys {
+        match models.remove(&key) {
+            Some(model) => results.push(model),
+            None => return Err(async_graphql::Error::new("Inserted row not found.")),
+        }
+    }
+
+    Ok(results)
+}
@@ -5,8 +5,11 @@ use sea_orm::{
 };
 
 use crate::{
-    guard_error, BuilderContext, DatabaseContext, EntityInputBuilder, EntityObjectBuilder,
-    EntityQueryFieldBuilder, GuardAction, OperationType, UserContext,
+    begin_transaction, guard_error, is_dry_run, notify_entity_change, resolve_before_save,
+    resolve_entity_guard, resolve_field_guards, validate_input, write_audit_log, write_outbox,
+    BuilderContext, DatabaseContext, EntityInputBuilder, EntityObjectBuilder,
+    EntityQueryFieldBuilder, GuardAction, MutationPayloadBuilder, OperationType,
+    RequestTransaction, UserContext,
 };
 
 /// The configuration structure of EntityCreateOneMutationBuilder
@@ -15,6 +18,13 @@ pub struct EntityCreateOneMutationConfig {
     pub mutation_suffix: String,
     /// name for `data` field
     pub data_field: String,
+    /// name for `dryRun` field, dry runs report the created record and are rolled back
+    pub dry_run_field: String,
+    /// return a `{Entity}CreatePayload` object with the records, the affected count and
+    /// the user errors instead of the records
+    pub payload: bool,
+    /// suffix that is appended on the entity name for the payload object
+    pub payload_suffix: String,
 }
 
 impl std::default::Default for EntityCreateOneMutationConfig {
@@ -29,6 +39,16 @@ impl std::default::Default for EntityCreateOneMutationConfig {
                 .into()
             },
             data_field: "data".into(),
+            dry_run_field: {
+                if cfg!(feature = "field-snake-case") {
+                    "dry_run"
+                } else {
+                    "dryRun"
+                }
+                .into()
+            },
+            payload: false,
+            payload_suffix: "CreatePayload".into(),
         }
     }
 }
@@ -57,7 +77,7 @@ impl EntityCreateOneMutationBuilder {
     /// used to get the create mutation field for a SeaORM entity
     pub fn to_field<T, A>(&self) -> Field
     where
-        T: EntityTrait,
+        T: EntityTrait<ActiveModel = A>,
         <T as EntityTrait>::Model: Sync,
         <T as EntityTrait>::Model: IntoActiveModel<A>,
         A: ActiveModelTrait<Entity = T> + sea_orm::ActiveModelBehavior + Send + 'static,
@@ -72,16 +92,30 @@ impl EntityCreateOneMutationBuilder {
         let context = self.context;
 
         let object_name: String = entity_object_builder.type_name::<T>();
-        let hooks = &self.context.hooks;
 
-        Field::new(
-            self.type_name::<T>(),
-            TypeRef::named_nn(entity_object_builder.basic_type_name::<T>()),
-            move |ctx| {
-                let object_name = object_name.clone();
+        let payload = context.entity_create_one_mutation.payload;
+        let payload_builder = MutationPayloadBuilder { context };
+        let output_type = if payload {
+            TypeRef::named_nn(
+                payload_builder.type_name::<T>(&context.entity_create_one_mutation.payload_suffix),
+            )
+        } else {
+            TypeRef::named_nn(entity_object_builder.basic_type_name::<T>())
+        };
+
+        Field::new(self.type_name::<T>(), output_type, move |ctx| {
+            let object_name = object_name.clone();
+            payload_builder.resolve::<T>(
+                payload,
+                ctx.data_opt::<RequestTransaction>(),
                 FieldFuture::new(async move {
-                    if let GuardAction::Block(reason) =
-                        hooks.entity_guard(&ctx, &object_name, OperationType::Create)
+                    if let GuardAction::Block(reason) = resolve_entity_guard::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Create,
+                    )
+                    .await
                     {
                         return Err(guard_error(reason, "Entity guard triggered."));
                     }
@@ -93,12 +127,20 @@ impl EntityCreateOneMutationBuilder {
                         .try_get(&context.entity_create_one_mutation.data_field)?;
                     let input_object = &value_accessor.object()?;
 
-                    for (column, _) in input_object.iter() {
-                        if let GuardAction::Block(reason) =
-                            hooks.field_guard(&ctx, &object_name, column, OperationType::Create)
-                        {
-                            return Err(guard_error(reason, "Field guard triggered."));
-                        }
+                    let fields: Vec<&str> = input_object
+                        .iter()
+                        .map(|(column, _)| column.as_str())
+                        .collect();
+                    if let GuardAction::Block(reason) = resolve_field_guards(
+                        &ctx,
+                        context,
+                        &object_name,
+                        &fields,
+                        OperationType::Create,
+                    )
+                    .await
+                    {
+                        return Err(guard_error(reason, "Field guard triggered."));
                     }
 
                     let db = &ctx
@@ -111,8 +153,9 @@ impl EntityCreateOneMutationBuilder {
                         input_object,
                     )?;
 
-                    if let GuardAction::Block(reason) = hooks.before_active_model_save(
+                    if let GuardAction::Block(reason) = resolve_before_save::<T>(
                         &ctx,
+                        context,
                         &object_name,
                         OperationType::Create,
                         &mut active_model,
@@ -120,20 +163,61 @@ impl EntityCreateOneMutationBuilder {
                         return Err(guard_error(reason, "Blocked by before_active_model_save."));
                     }
 
-                    let result = active_model.insert(db).await?;
+                    let dry_run =
+                        is_dry_run(&ctx, &context.entity_create_one_mutation.dry_run_field)?;
+
+                    let transaction = begin_transaction(&ctx, db).await?;
+
+                    let result = active_model.insert(&transaction).await?;
+
+                    if dry_run {
+                        transaction.rollback().await?;
+                        return Ok(Some(FieldValue::owned_any(result)));
+                    }
+
+                    write_audit_log::<T, _>(
+                        &ctx,
+                        context,
+                        &transaction,
+                        OperationType::Create,
+                        None,
+                        Some(std::slice::from_ref(&result)),
+                    )
+                    .await?;
 
-                    hooks
-                        .entity_watch(&ctx, &object_name, OperationType::Create)
-                        .await;
+                    write_outbox::<T, _>(
+                        context,
+                        &transaction,
+                        &object_name,
+                        OperationType::Create,
+                        std::slice::from_ref(&result),
+                    )
+                    .await?;
+
+                    transaction.commit().await?;
+
+                    notify_entity_change::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Create,
+                        vec![result.clone()],
+                        None,
+                    )
+                    .await;
 
                     Ok(Some(FieldValue::owned_any(result)))
-                })
-            },
-        )
+                }),
+            )
+        })
         .argument(InputValue::new(
             &context.entity_create_one_mutation.data_field,
             TypeRef::named_nn(entity_input_builder.insert_type_name::<T>()),
         ))
+        .argument(InputValue::new(
+            &context.entity_create_one_mutation.dry_run_field,
+            TypeRef::named(TypeRef::BOOLEAN),
+        ))
     }
 }
 
@@ -149,6 +233,8 @@ where
 {
     let mut data = entity_input_builder.parse_object::<T>(input_object)?;
 
+    validate_input::<T>(entity_input_builder.context, &data)?;
+
     let mut active_model = A::default();
 
     for column in T::Column::iter() {
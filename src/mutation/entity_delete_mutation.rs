@@ -1,11 +1,15 @@
 use async_graphql::dynamic::{Field, FieldFuture, InputValue, TypeRef};
 use sea_orm::{
     ActiveModelTrait, DatabaseConnection, DeleteResult, EntityTrait, IntoActiveModel, QueryFilter,
+    QueryTrait,
 };
 
 use crate::{
-    get_filter_conditions, guard_error, BuilderContext, DatabaseContext, EntityObjectBuilder,
-    EntityQueryFieldBuilder, FilterInputBuilder, GuardAction, OperationType, UserContext,
+    begin_transaction, check_affected_rows, get_filter_conditions, guard_error, is_dry_run,
+    notify_entity_change, publishes_entity_changes, reject_masked_fields, reject_unfiltered,
+    resolve_entity_filter, resolve_entity_guard, write_audit_log, write_outbox, BuilderContext,
+    DatabaseContext, EntityObjectBuilder, EntityQueryFieldBuilder, FilterInputBuilder, GuardAction,
+    MutationPayloadBuilder, OperationType, RequestTransaction, UserContext,
 };
 
 /// The configuration structure of EntityDeleteMutationBuilder
@@ -15,6 +19,24 @@ pub struct EntityDeleteMutationConfig {
 
     /// name for `filter` field
     pub filter_field: String,
+
+    /// make the `filter` field required
+    pub require_filter: bool,
+
+    /// reject deletes without any filter condition, including a missing filter
+    pub reject_empty_filter: bool,
+
+    /// roll back and reject deletes affecting more rows
+    pub max_affected_rows: Option<u64>,
+
+    /// name for `dryRun` field, dry runs report the affected count and are rolled back
+    pub dry_run_field: String,
+
+    /// return a `{Entity}DeletePayload` object with the records, the affected count and
+    /// the user errors instead of the affected count
+    pub payload: bool,
+    /// suffix that is appended on the entity name for the payload object
+    pub payload_suffix: String,
 }
 
 impl std::default::Default for EntityDeleteMutationConfig {
@@ -29,6 +51,19 @@ impl std::default::Default for EntityDeleteMutationConfig {
                 .into()
             },
             filter_field: "filter".into(),
+            require_filter: false,
+            reject_empty_filter: false,
+            max_affected_rows: None,
+            dry_run_field: {
+                if cfg!(feature = "field-snake-case") {
+                    "dry_run"
+                } else {
+                    "dryRun"
+                }
+                .into()
+            },
+            payload: false,
+            payload_suffix: "DeletePayload".into(),
         }
     }
 }
@@ -58,6 +93,7 @@ impl EntityDeleteMutationBuilder {
     pub fn to_field<T, A>(&self) -> Field
     where
         T: EntityTrait,
+        <T as EntityTrait>::Model: Sync,
         <T as EntityTrait>::Model: IntoActiveModel<A>,
         A: ActiveModelTrait<Entity = T> + sea_orm::ActiveModelBehavior + Send,
     {
@@ -73,14 +109,29 @@ impl EntityDeleteMutationBuilder {
         let context = self.context;
         let hooks = &self.context.hooks;
 
-        Field::new(
-            self.type_name::<T>(),
-            TypeRef::named_nn(TypeRef::INT),
-            move |ctx| {
-                let object_name = object_name.clone();
+        let payload = context.entity_delete_mutation.payload;
+        let payload_builder = MutationPayloadBuilder { context };
+        let output_type = if payload {
+            TypeRef::named_nn(
+                payload_builder.type_name::<T>(&context.entity_delete_mutation.payload_suffix),
+            )
+        } else {
+            TypeRef::named_nn(TypeRef::INT)
+        };
+
+        Field::new(self.type_name::<T>(), output_type, move |ctx| {
+            let object_name = object_name.clone();
+            payload_builder.resolve::<T>(
+                payload,
+                ctx.data_opt::<RequestTransaction>(),
                 FieldFuture::new(async move {
-                    if let GuardAction::Block(reason) =
-                        hooks.entity_guard(&ctx, &object_name, OperationType::Delete)
+                    if let GuardAction::Block(reason) = resolve_entity_guard::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Delete,
+                    )
+                    .await
                     {
                         return Err(guard_error(reason, "Entity guard triggered."));
                     }
@@ -89,28 +140,118 @@ impl EntityDeleteMutationBuilder {
                         .data::<DatabaseConnection>()?
                         .restricted(ctx.data_opt::<UserContext>())?;
 
+                    reject_masked_fields::<T>(
+                        &ctx,
+                        context,
+                        ctx.args.get(&context.entity_delete_mutation.filter_field),
+                        None,
+                        None,
+                    )
+                    .await?;
+
                     let filters = ctx.args.get(&context.entity_delete_mutation.filter_field);
+                    if context.entity_delete_mutation.reject_empty_filter {
+                        reject_unfiltered(
+                            &object_name,
+                            ctx.args.get(&context.entity_delete_mutation.filter_field),
+                        )?;
+                    }
                     let filter_condition = get_filter_conditions::<T>(context, filters)?;
 
-                    let mut stmt = T::delete_many();
-                    if let Some(filter) =
-                        hooks.entity_filter(&ctx, &object_name, OperationType::Delete)
+                    let dry_run = is_dry_run(&ctx, &context.entity_delete_mutation.dry_run_field)?;
+
+                    let entity_filter = resolve_entity_filter::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Delete,
+                    )
+                    .await;
+
+                    let transaction = begin_transaction(&ctx, db).await?;
+
+                    let pre_images =
+                        hooks.watch_pre_images(&ctx, &object_name, OperationType::Delete);
+
+                    // the deleted rows are read first, they are gone once the change is published
+                    let deleted: Vec<T::Model> = if publishes_entity_changes(&ctx)
+                        || context.outbox.is_enabled()
+                        || context.audit_log.enabled
+                        || pre_images
                     {
-                        stmt = stmt.filter(filter);
+                        T::find()
+                            .apply_if(entity_filter.clone(), |q, f| q.filter(f))
+                            .filter(filter_condition.clone())
+                            .all(&transaction)
+                            .await?
+                    } else {
+                        Vec::new()
+                    };
+
+                    let res: DeleteResult = T::delete_many()
+                        .apply_if(entity_filter, |q, f| q.filter(f))
+                        .filter(filter_condition)
+                        .exec(&transaction)
+                        .await?;
+
+                    check_affected_rows(
+                        &object_name,
+                        context.entity_delete_mutation.max_affected_rows,
+                        res.rows_affected,
+                    )?;
+
+                    if dry_run {
+                        transaction.rollback().await?;
+                        return Ok(Some(async_graphql::Value::from(res.rows_affected)));
                     }
-                    let res: DeleteResult = stmt.filter(filter_condition).exec(db).await?;
 
-                    hooks
-                        .entity_watch(&ctx, &object_name, OperationType::Delete)
-                        .await;
+                    write_audit_log::<T, _>(
+                        &ctx,
+                        context,
+                        &transaction,
+                        OperationType::Delete,
+                        Some(&deleted),
+                        None,
+                    )
+                    .await?;
+
+                    write_outbox::<T, _>(
+                        context,
+                        &transaction,
+                        &object_name,
+                        OperationType::Delete,
+                        &deleted,
+                    )
+                    .await?;
+
+                    transaction.commit().await?;
+
+                    let before = pre_images.then(|| deleted.clone());
+                    notify_entity_change::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Delete,
+                        deleted,
+                        before,
+                    )
+                    .await;
 
                     Ok(Some(async_graphql::Value::from(res.rows_affected)))
-                })
-            },
-        )
+                }),
+            )
+        })
         .argument(InputValue::new(
             &context.entity_delete_mutation.filter_field,
-            TypeRef::named(entity_filter_input_builder.type_name(&object_name_)),
+            if context.entity_delete_mutation.require_filter {
+                TypeRef::named_nn(entity_filter_input_builder.type_name(&object_name_))
+            } else {
+                TypeRef::named(entity_filter_input_builder.type_name(&object_name_))
+            },
+        ))
+        .argument(InputValue::new(
+            &context.entity_delete_mutation.dry_run_field,
+            TypeRef::named(TypeRef::BOOLEAN),
         ))
     }
 }
@@ -0,0 +1,378 @@
+use async_graphql::{
+    dynamic::{Field, FieldFuture, FieldValue, InputValue, TypeRef},
+    ErrorExtensions,
+};
+use sea_orm::{
+    sea_query::Condition, ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    EntityTrait, IntoActiveModel, Iterable, PaginatorTrait, PrimaryKeyToColumn, QueryFilter,
+    QueryTrait,
+};
+
+use crate::{
+    begin_transaction, check_updated_rows, coded_error, conflict_error, guard_error, is_dry_run,
+    next_version_expr, notify_entity_change, prepare_update_active_model, resolve_before_save,
+    resolve_entity_filter, resolve_entity_guard, resolve_field_guards, resolve_update_check,
+    write_audit_log, write_outbox, BuilderContext, DatabaseContext, EntityInputBuilder,
+    EntityObjectBuilder, EntityQueryFieldBuilder, EntityUpdateMutationBuilder, ErrorCode,
+    GuardAction, MutationPayloadBuilder, OperationType, RequestTransaction, TypesMapHelper,
+    UserContext,
+};
+
+/// The configuration structure of EntityUpdateBatchMutationBuilder
+pub struct EntityUpdateBatchMutationConfig {
+    /// if true, a `{Entity}UpdateBatch` mutation is generated for every entity
+    pub enabled: bool,
+    /// suffix that is appended on batch update mutations
+    pub mutation_suffix: String,
+    /// name for `data` field
+    pub data_field: String,
+    /// name for `changes` field of the rows
+    pub changes_field: String,
+    /// name for `dryRun` field, dry runs report the updated records and are rolled back
+    pub dry_run_field: String,
+    /// return a `{Entity}UpdatePayload` object with the records, the affected count and
+    /// the user errors instead of the records
+    pub payload: bool,
+    /// suffix that is appended on the entity name for the payload object
+    pub payload_suffix: String,
+}
+
+impl std::default::Default for EntityUpdateBatchMutationConfig {
+    fn default() -> Self {
+        EntityUpdateBatchMutationConfig {
+            enabled: false,
+            mutation_suffix: {
+                if cfg!(feature = "field-snake-case") {
+                    "_update_batch"
+                } else {
+                    "UpdateBatch"
+                }
+                .into()
+            },
+            data_field: "data".into(),
+            changes_field: "changes".into(),
+            dry_run_field: {
+                if cfg!(feature = "field-snake-case") {
+                    "dry_run"
+                } else {
+                    "dryRun"
+                }
+                .into()
+            },
+            payload: false,
+            payload_suffix: "UpdatePayload".into(),
+        }
+    }
+}
+
+/// This builder produces the batch update mutation for an entity,
+/// where every row is addressed by primary key and carries its own changes
+pub struct EntityUpdateBatchMutationBuilder {
+    pub context: &'static BuilderContext,
+}
+
+impl EntityUpdateBatchMutationBuilder {
+    /// used to get mutation name for a SeaORM entity
+    pub fn type_name<T>(&self) -> String
+    where
+        T: EntityTrait,
+    {
+        let entity_query_field_builder = EntityQueryFieldBuilder {
+            context: self.context,
+        };
+        format!(
+            "{}{}",
+            entity_query_field_builder.type_name::<T>(),
+            self.context.entity_update_batch_mutation.mutation_suffix
+        )
+    }
+
+    /// used to get the batch update mutation field for a SeaORM entity
+    pub fn to_field<T, A>(&self) -> Field
+    where
+        T: EntityTrait<ActiveModel = A>,
+        <T as EntityTrait>::Model: Sync,
+        <T as EntityTrait>::Model: IntoActiveModel<A>,
+        A: ActiveModelTrait<Entity = T> + sea_orm::ActiveModelBehavior + Send + 'static,
+    {
+        let entity_input_builder = EntityInputBuilder {
+            context: self.context,
+        };
+        let entity_object_builder = EntityObjectBuilder {
+            context: self.context,
+        };
+        let entity_update_mutation_builder = EntityUpdateMutationBuilder {
+            context: self.context,
+        };
+
+        let context = self.context;
+
+        let object_name: String = entity_object_builder.type_name::<T>();
+        let hooks = &self.context.hooks;
+
+        let version_column = entity_update_mutation_builder.version_column::<T>();
+
+        let payload = context.entity_update_batch_mutation.payload;
+        let payload_builder = MutationPayloadBuilder { context };
+        let output_type = if payload {
+            TypeRef::named_nn(
+                payload_builder
+                    .type_name::<T>(&context.entity_update_batch_mutation.payload_suffix),
+            )
+        } else {
+            TypeRef::named_nn_list_nn(entity_object_builder.basic_type_name::<T>())
+        };
+
+        Field::new(self.type_name::<T>(), output_type, move |ctx| {
+            let object_name = object_name.clone();
+            payload_builder.resolve::<T>(
+                payload,
+                ctx.data_opt::<RequestTransaction>(),
+                FieldFuture::new(async move {
+                    if let GuardAction::Block(reason) = resolve_entity_guard::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Update,
+                    )
+                    .await
+                    {
+                        return Err(guard_error(reason, "Entity guard triggered."));
+                    }
+
+                    let db = ctx
+                        .data::<DatabaseConnection>()?
+                        .restricted(ctx.data_opt::<UserContext>())?;
+
+                    let dry_run =
+                        is_dry_run(&ctx, &context.entity_update_batch_mutation.dry_run_field)?;
+
+                    // any error below drops the transaction, rolling back every row
+                    let transaction = begin_transaction(&ctx, &db).await?;
+
+                    let entity_input_builder = EntityInputBuilder { context };
+                    let entity_object_builder = EntityObjectBuilder { context };
+                    let types_map_helper = TypesMapHelper { context };
+
+                    let entity_filter = resolve_entity_filter::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Update,
+                    )
+                    .await;
+
+                    let update_check = resolve_update_check::<T>(&ctx, context);
+
+                    let mut results: Vec<T::Model> = Vec::new();
+                    let mut before: Option<Vec<T::Model>> =
+                        if hooks.watch_pre_images(&ctx, &object_name, OperationType::Update)
+                            || context.audit_log.enabled
+                        {
+                            Some(Vec::new())
+                        } else {
+                            None
+                        };
+                    for (index, input) in ctx
+                        .args
+                        .try_get(&context.entity_update_batch_mutation.data_field)?
+                        .list()?
+                        .iter()
+                        .enumerate()
+                    {
+                        let row = input.object()?;
+
+                        let mut key_condition = Condition::all();
+                        for key in T::PrimaryKey::iter() {
+                            let column = key.into_column();
+                            let value =
+                                row.try_get(&entity_object_builder.column_name::<T>(&column))?;
+                            let value = types_map_helper
+                                .async_graphql_value_to_sea_orm_value::<T>(&column, &value)?;
+                            key_condition = key_condition.add(column.eq(value));
+                        }
+
+                        let input_object = &row
+                            .try_get(&context.entity_update_batch_mutation.changes_field)?
+                            .object()?;
+
+                        let fields: Vec<&str> = input_object
+                            .iter()
+                            .map(|(column, _)| column.as_str())
+                            .collect();
+                        if let GuardAction::Block(reason) = resolve_field_guards(
+                            &ctx,
+                            context,
+                            &object_name,
+                            &fields,
+                            OperationType::Update,
+                        )
+                        .await
+                        {
+                            return Err(guard_error(reason, "Field guard triggered."));
+                        }
+
+                        let (mut active_model, operations) = prepare_update_active_model::<T, A>(
+                            &entity_input_builder,
+                            &entity_object_builder,
+                            input_object,
+                        )?;
+                        if let GuardAction::Block(reason) = resolve_before_save::<T>(
+                            &ctx,
+                            context,
+                            &object_name,
+                            OperationType::Update,
+                            &mut active_model,
+                        ) {
+                            return Err(guard_error(
+                                reason,
+                                "Blocked by before_active_model_save.",
+                            ));
+                        }
+
+                        let expected_version = match version_column {
+                            Some(column) => {
+                                let value =
+                                    row.try_get(&context.entity_update_mutation.version_field)?;
+                                let value = types_map_helper
+                                    .async_graphql_value_to_sea_orm_value::<T>(&column, &value)?;
+                                Some((column, value))
+                            }
+                            None => None,
+                        };
+
+                        if let Some(before) = before.as_mut() {
+                            before.extend(
+                                T::find()
+                                    .apply_if(entity_filter.clone(), |q, f| q.filter(f))
+                                    .filter(key_condition.clone())
+                                    .one(&transaction)
+                                    .await?,
+                            );
+                        }
+
+                        let stmt = T::update_many()
+                            .set(active_model)
+                            .apply_if(entity_filter.clone(), |q, f| q.filter(f))
+                            .filter(key_condition.clone());
+
+                        let stmt =
+                            operations
+                                .into_iter()
+                                .fold(stmt, |stmt, (column, operation)| {
+                                    stmt.col_expr(column, operation.into_expr(column))
+                                });
+
+                        let stmt = match expected_version.clone() {
+                            Some((column, value)) => stmt
+                                .col_expr(column, next_version_expr(column))
+                                .filter(column.eq(value)),
+                            None => stmt,
+                        };
+
+                        let result: Vec<T::Model> = if db.support_returning() {
+                            stmt.exec_with_returning(&transaction).await?
+                        } else {
+                            let res = stmt.exec(&transaction).await?;
+
+                            // MySQL counts changed rows only, a missing row is certain
+                            // when the version column was bumped
+                            if res.rows_affected == 0 && expected_version.is_some() {
+                                Vec::new()
+                            } else {
+                                T::find()
+                                    .apply_if(entity_filter.clone(), |q, f| q.filter(f))
+                                    .filter(key_condition.clone())
+                                    .all(&transaction)
+                                    .await?
+                            }
+                        };
+
+                        check_updated_rows::<T, _>(&transaction, update_check.as_ref(), &result)
+                            .await?;
+
+                        let model = match result.into_iter().next() {
+                            Some(model) => model,
+                            None => {
+                                if expected_version.is_some() {
+                                    let exists = T::find()
+                                        .apply_if(entity_filter.clone(), |q, f| q.filter(f))
+                                        .filter(key_condition)
+                                        .count(&transaction)
+                                        .await?
+                                        > 0;
+
+                                    if exists {
+                                        return Err(conflict_error(&object_name));
+                                    }
+                                }
+
+                                return Err(coded_error(
+                                    ErrorCode::NotFound,
+                                    format!("{object_name} at row {index} not found."),
+                                )
+                                .extend_with(|_, extensions| {
+                                    extensions.set("row", index as i64);
+                                }));
+                            }
+                        };
+
+                        A::after_save(model.clone(), &transaction, false).await?;
+
+                        results.push(model);
+                    }
+
+                    if dry_run {
+                        transaction.rollback().await?;
+                        return Ok(Some(FieldValue::list(
+                            results.into_iter().map(FieldValue::owned_any),
+                        )));
+                    }
+
+                    write_audit_log::<T, _>(
+                        &ctx,
+                        context,
+                        &transaction,
+                        OperationType::Update,
+                        before.as_deref(),
+                        Some(&results),
+                    )
+                    .await?;
+
+                    write_outbox::<T, _>(
+                        context,
+                        &transaction,
+                        &object_name,
+                        OperationType::Update,
+                        &results,
+                    )
+                    .await?;
+
+                    transaction.commit().await?;
+
+                    notify_entity_change::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Update,
+                        results.clone(),
+                        before,
+                    )
+                    .await;
+
+                    Ok(Some(FieldValue::list(
+                        results.into_iter().map(FieldValue::owned_any),
+                    )))
+                }),
+            )
+        })
+        .argument(InputValue::new(
+            &context.entity_update_batch_mutation.data_field,
+            TypeRef::named_nn_list_nn(entity_input_builder.update_batch_type_name::<T>()),
+        ))
+        .argument(InputValue::new(
+            &context.entity_update_batch_mutation.dry_run_field,
+            TypeRef::named(TypeRef::BOOLEAN),
+        ))
+    }
+}
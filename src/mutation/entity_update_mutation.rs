@@ -1,13 +1,21 @@
-use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, TypeRef};
+use std::collections::BTreeSet;
+
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, ObjectAccessor, TypeRef};
 use sea_orm::{
-    ActiveModelTrait, ConnectionTrait, DatabaseConnection, EntityTrait, IntoActiveModel,
-    QueryFilter, QueryTrait, TransactionTrait,
+    sea_query::{Expr, ExprTrait},
+    ActiveModelTrait, ColumnTrait, ColumnType, ConnectionTrait, DatabaseConnection, EntityName,
+    EntityTrait, IdenStatic, IntoActiveModel, Iterable, PaginatorTrait, PrimaryKeyToColumn,
+    PrimaryKeyTrait, QueryFilter, QueryTrait,
 };
 
 use crate::{
-    get_filter_conditions, guard_error, prepare_active_model, BuilderContext, DatabaseContext,
-    EntityInputBuilder, EntityObjectBuilder, EntityQueryFieldBuilder, FilterInputBuilder,
-    GuardAction, OperationType, UserContext,
+    begin_transaction, check_affected_rows, check_updated_rows, conflict_error,
+    get_filter_conditions, guard_error, is_dry_run, notify_entity_change, reject_masked_fields,
+    reject_unfiltered, resolve_before_save, resolve_entity_filter, resolve_entity_guard,
+    resolve_field_guards, resolve_update_check, validate_input, write_audit_log, write_outbox,
+    BuilderContext, DatabaseContext, EntityColumnId, EntityInputBuilder, EntityObjectBuilder,
+    EntityQueryFieldBuilder, FilterInputBuilder, GuardAction, MutationPayloadBuilder,
+    OperationType, RequestTransaction, TypesMapHelper, UpdateOperation, UserContext,
 };
 
 /// The configuration structure of EntityUpdateMutationBuilder
@@ -20,6 +28,30 @@ pub struct EntityUpdateMutationConfig {
 
     /// name for `filter` field
     pub filter_field: String,
+
+    /// make the `filter` field required
+    pub require_filter: bool,
+
+    /// reject updates without any filter condition, including a missing filter
+    pub reject_empty_filter: bool,
+
+    /// roll back and reject updates affecting more rows
+    pub max_affected_rows: Option<u64>,
+
+    /// name for `dryRun` field, dry runs report the updated records and are rolled back
+    pub dry_run_field: String,
+
+    /// name for the `expectedVersion` field, only present on entities with a version column
+    pub version_field: String,
+
+    /// columns used for optimistic concurrency control, at most one per entity.
+    /// They are incremented on every update and must be integer columns
+    pub version_columns: BTreeSet<EntityColumnId>,
+    /// return a `{Entity}UpdatePayload` object with the records, the affected count and
+    /// the user errors instead of the records
+    pub payload: bool,
+    /// suffix that is appended on the entity name for the payload object
+    pub payload_suffix: String,
 }
 
 impl std::default::Default for EntityUpdateMutationConfig {
@@ -35,6 +67,28 @@ impl std::default::Default for EntityUpdateMutationConfig {
             },
             data_field: "data".into(),
             filter_field: "filter".into(),
+            require_filter: false,
+            reject_empty_filter: false,
+            max_affected_rows: None,
+            dry_run_field: {
+                if cfg!(feature = "field-snake-case") {
+                    "dry_run"
+                } else {
+                    "dryRun"
+                }
+                .into()
+            },
+            version_field: {
+                if cfg!(feature = "field-snake-case") {
+                    "expected_version"
+                } else {
+                    "expectedVersion"
+                }
+                .into()
+            },
+            version_columns: BTreeSet::new(),
+            payload: false,
+            payload_suffix: "UpdatePayload".into(),
         }
     }
 }
@@ -60,13 +114,39 @@ impl EntityUpdateMutationBuilder {
         )
     }
 
+    /// used to get the version column of a SeaORM entity, if configured.
+    /// Panics if it is not an integer column
+    pub fn version_column<T>(&self) -> Option<T::Column>
+    where
+        T: EntityTrait,
+    {
+        let column = T::Column::iter().find(|column| {
+            self.context
+                .entity_update_mutation
+                .version_columns
+                .contains(&EntityColumnId::of::<T>(column))
+        })?;
+
+        // timestamps are not monotonic, two updates within their resolution keep the version
+        if !is_integer_column(&column) {
+            panic!(
+                "Version column '{}' of '{}' has unsupported type {:?}, only integer columns are supported",
+                column.as_str(),
+                <T as EntityName>::table_name(&T::default()),
+                column.def().get_column_type()
+            );
+        }
+
+        Some(column)
+    }
+
     /// used to get the update mutation field for a SeaORM entity
     pub fn to_field<T, A>(&self) -> Field
     where
-        T: EntityTrait,
+        T: EntityTrait<ActiveModel = A>,
         <T as EntityTrait>::Model: Sync,
         <T as EntityTrait>::Model: IntoActiveModel<A>,
-        A: ActiveModelTrait<Entity = T> + sea_orm::ActiveModelBehavior + Send,
+        A: ActiveModelTrait<Entity = T> + sea_orm::ActiveModelBehavior + Send + 'static,
     {
         let entity_input_builder = EntityInputBuilder {
             context: self.context,
@@ -77,20 +157,40 @@ impl EntityUpdateMutationBuilder {
         let entity_object_builder = EntityObjectBuilder {
             context: self.context,
         };
+        let types_map_helper = TypesMapHelper {
+            context: self.context,
+        };
         let object_name: String = entity_object_builder.type_name::<T>();
         let object_name_ = object_name.clone();
 
         let context = self.context;
         let hooks = &self.context.hooks;
 
-        Field::new(
-            self.type_name::<T>(),
-            TypeRef::named_nn_list_nn(entity_object_builder.basic_type_name::<T>()),
-            move |ctx| {
-                let object_name = object_name.clone();
+        let version_column = self.version_column::<T>();
+
+        let payload = context.entity_update_mutation.payload;
+        let payload_builder = MutationPayloadBuilder { context };
+        let output_type = if payload {
+            TypeRef::named_nn(
+                payload_builder.type_name::<T>(&context.entity_update_mutation.payload_suffix),
+            )
+        } else {
+            TypeRef::named_nn_list_nn(entity_object_builder.basic_type_name::<T>())
+        };
+
+        let field = Field::new(self.type_name::<T>(), output_type, move |ctx| {
+            let object_name = object_name.clone();
+            payload_builder.resolve::<T>(
+                payload,
+                ctx.data_opt::<RequestTransaction>(),
                 FieldFuture::new(async move {
-                    if let GuardAction::Block(reason) =
-                        hooks.entity_guard(&ctx, &object_name, OperationType::Update)
+                    if let GuardAction::Block(reason) = resolve_entity_guard::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Update,
+                    )
+                    .await
                     {
                         return Err(guard_error(reason, "Entity guard triggered."));
                     }
@@ -99,76 +199,329 @@ impl EntityUpdateMutationBuilder {
                         .data::<DatabaseConnection>()?
                         .restricted(ctx.data_opt::<UserContext>())?;
 
-                    let transaction = db.begin().await?;
+                    let transaction = begin_transaction(&ctx, &db).await?;
 
                     let entity_input_builder = EntityInputBuilder { context };
                     let entity_object_builder = EntityObjectBuilder { context };
 
+                    reject_masked_fields::<T>(
+                        &ctx,
+                        context,
+                        ctx.args.get(&context.entity_update_mutation.filter_field),
+                        None,
+                        None,
+                    )
+                    .await?;
+
                     let filters = ctx.args.get(&context.entity_update_mutation.filter_field);
+                    if context.entity_update_mutation.reject_empty_filter {
+                        reject_unfiltered(
+                            &object_name,
+                            ctx.args.get(&context.entity_update_mutation.filter_field),
+                        )?;
+                    }
                     let filter_condition = get_filter_conditions::<T>(context, filters)?;
 
+                    let dry_run = is_dry_run(&ctx, &context.entity_update_mutation.dry_run_field)?;
+
                     let value_accessor = ctx
                         .args
                         .try_get(&context.entity_update_mutation.data_field)?;
                     let input_object = &value_accessor.object()?;
 
-                    for (column, _) in input_object.iter() {
-                        if let GuardAction::Block(reason) =
-                            hooks.field_guard(&ctx, &object_name, column, OperationType::Update)
-                        {
-                            return Err(guard_error(reason, "Field guard triggered."));
-                        }
+                    let fields: Vec<&str> = input_object
+                        .iter()
+                        .map(|(column, _)| column.as_str())
+                        .collect();
+                    if let GuardAction::Block(reason) = resolve_field_guards(
+                        &ctx,
+                        context,
+                        &object_name,
+                        &fields,
+                        OperationType::Update,
+                    )
+                    .await
+                    {
+                        return Err(guard_error(reason, "Field guard triggered."));
                     }
 
-                    let active_model = prepare_active_model::<T, A>(
+                    let (mut active_model, operations) = prepare_update_active_model::<T, A>(
                         &entity_input_builder,
                         &entity_object_builder,
                         input_object,
                     )?;
+                    if let GuardAction::Block(reason) = resolve_before_save::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Update,
+                        &mut active_model,
+                    ) {
+                        return Err(guard_error(reason, "Blocked by before_active_model_save."));
+                    }
 
-                    let entity_filter =
-                        hooks.entity_filter(&ctx, &object_name, OperationType::Update);
+                    let entity_filter = resolve_entity_filter::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Update,
+                    )
+                    .await;
+
+                    let expected_version = match version_column {
+                        Some(column) => {
+                            let value = ctx
+                                .args
+                                .try_get(&context.entity_update_mutation.version_field)?;
+                            let value = TypesMapHelper { context }
+                                .async_graphql_value_to_sea_orm_value::<T>(&column, &value)?;
+                            Some((column, value))
+                        }
+                        None => None,
+                    };
+
+                    // pre-images are read within the transaction, before the update
+                    let before: Option<Vec<T::Model>> =
+                        if hooks.watch_pre_images(&ctx, &object_name, OperationType::Update)
+                            || context.audit_log.enabled
+                        {
+                            Some(
+                                T::find()
+                                    .apply_if(entity_filter.clone(), |q, f| q.filter(f))
+                                    .filter(filter_condition.clone())
+                                    .all(&transaction)
+                                    .await?,
+                            )
+                        } else {
+                            None
+                        };
+
+                    // without RETURNING, rows are checked by the keys they had before the update
+                    let update_check = resolve_update_check::<T>(&ctx, context);
+                    let targets: Option<Vec<T::Model>> =
+                        if update_check.is_some() && before.is_none() && !db.support_returning() {
+                            Some(
+                                T::find()
+                                    .apply_if(entity_filter.clone(), |q, f| q.filter(f))
+                                    .filter(filter_condition.clone())
+                                    .all(&transaction)
+                                    .await?,
+                            )
+                        } else {
+                            None
+                        };
 
                     let stmt = T::update_many()
                         .set(active_model)
                         .apply_if(entity_filter.clone(), |q, f| q.filter(f))
                         .filter(filter_condition.clone());
 
-                    let result: Vec<T::Model> = if db.support_returning() {
-                        stmt.exec_with_returning(&transaction).await?
+                    let stmt = operations
+                        .into_iter()
+                        .fold(stmt, |stmt, (column, operation)| {
+                            stmt.col_expr(column, operation.into_expr(column))
+                        });
+
+                    let stmt = match expected_version.clone() {
+                        Some((column, value)) => stmt
+                            .col_expr(column, next_version_expr(column))
+                            .filter(column.eq(value)),
+                        None => stmt,
+                    };
+
+                    let (result, affected_rows): (Vec<T::Model>, u64) = if db.support_returning() {
+                        let result = stmt.exec_with_returning(&transaction).await?;
+                        let affected_rows = result.len() as u64;
+                        (result, affected_rows)
                     } else {
-                        stmt.exec(&transaction).await?;
+                        let res = stmt.exec(&transaction).await?;
 
-                        T::find()
+                        if res.rows_affected == 0 && expected_version.is_some() {
+                            (Vec::new(), 0)
+                        } else {
+                            let result = T::find()
+                                .apply_if(entity_filter.clone(), |q, f| q.filter(f))
+                                .filter(filter_condition.clone())
+                                .all(&transaction)
+                                .await?;
+                            (result, res.rows_affected)
+                        }
+                    };
+
+                    check_affected_rows(
+                        &object_name,
+                        context.entity_update_mutation.max_affected_rows,
+                        affected_rows,
+                    )?;
+
+                    let updated = if db.support_returning() {
+                        result.as_slice()
+                    } else if affected_rows == 0 {
+                        &[]
+                    } else {
+                        before.as_deref().or(targets.as_deref()).unwrap_or_default()
+                    };
+                    check_updated_rows::<T, _>(&transaction, update_check.as_ref(), updated)
+                        .await?;
+
+                    if result.is_empty() && expected_version.is_some() {
+                        // nothing matched the expected version, check whether the record
+                        // exists at all to tell a conflict apart from a plain miss
+                        let exists = T::find()
                             .apply_if(entity_filter, |q, f| q.filter(f))
                             .filter(filter_condition)
-                            .all(&transaction)
+                            .count(&transaction)
                             .await?
-                    };
+                            > 0;
+
+                        if exists {
+                            return Err(conflict_error(&object_name));
+                        }
+                    }
 
                     for model in result.iter() {
                         A::after_save(model.clone(), &transaction, false).await?;
                     }
 
+                    if dry_run {
+                        transaction.rollback().await?;
+                        return Ok(Some(FieldValue::list(
+                            result.into_iter().map(FieldValue::owned_any),
+                        )));
+                    }
+
+                    write_audit_log::<T, _>(
+                        &ctx,
+                        context,
+                        &transaction,
+                        OperationType::Update,
+                        before.as_deref(),
+                        Some(&result),
+                    )
+                    .await?;
+
+                    write_outbox::<T, _>(
+                        context,
+                        &transaction,
+                        &object_name,
+                        OperationType::Update,
+                        &result,
+                    )
+                    .await?;
+
                     transaction.commit().await?;
 
-                    hooks
-                        .entity_watch(&ctx, &object_name, OperationType::Update)
-                        .await;
+                    notify_entity_change::<T>(
+                        &ctx,
+                        context,
+                        &object_name,
+                        OperationType::Update,
+                        result.clone(),
+                        before,
+                    )
+                    .await;
 
                     Ok(Some(FieldValue::list(
                         result.into_iter().map(FieldValue::owned_any),
                     )))
-                })
-            },
-        )
+                }),
+            )
+        })
         .argument(InputValue::new(
             &context.entity_update_mutation.data_field,
             TypeRef::named_nn(entity_input_builder.update_type_name::<T>()),
         ))
         .argument(InputValue::new(
             &context.entity_update_mutation.filter_field,
-            TypeRef::named(entity_filter_input_builder.type_name(&object_name_)),
+            if context.entity_update_mutation.require_filter {
+                TypeRef::named_nn(entity_filter_input_builder.type_name(&object_name_))
+            } else {
+                TypeRef::named(entity_filter_input_builder.type_name(&object_name_))
+            },
         ))
+        .argument(InputValue::new(
+            &context.entity_update_mutation.dry_run_field,
+            TypeRef::named(TypeRef::BOOLEAN),
+        ));
+
+        match version_column {
+            Some(column) => {
+                let version_type = types_map_helper
+                    .input_type_for_column::<T>(&column, &EntityColumnId::of::<T>(&column), true)
+                    .expect("version column to be supported");
+                field.argument(InputValue::new(
+                    &context.entity_update_mutation.version_field,
+                    version_type,
+                ))
+            }
+            None => field,
+        }
     }
 }
+
+/// used to compute the next value of a version column
+pub(crate) fn next_version_expr<C>(column: C) -> Expr
+where
+    C: ColumnTrait,
+{
+    Expr::col(column).add(1)
+}
+
+/// used to check whether a column can be used as version column
+fn is_integer_column<C>(column: &C) -> bool
+where
+    C: ColumnTrait,
+{
+    matches!(
+        column.def().get_column_type(),
+        ColumnType::TinyInteger
+            | ColumnType::SmallInteger
+            | ColumnType::Integer
+            | ColumnType::BigInteger
+            | ColumnType::TinyUnsigned
+            | ColumnType::SmallUnsigned
+            | ColumnType::Unsigned
+            | ColumnType::BigUnsigned
+    )
+}
+
+/// used to prepare the active model of an update, together with the
+/// column operations that have to be applied as expressions
+#[allow(clippy::type_complexity)]
+pub fn prepare_update_active_model<T, A>(
+    entity_input_builder: &EntityInputBuilder,
+    entity_object_builder: &EntityObjectBuilder,
+    input_object: &ObjectAccessor<'_>,
+) -> async_graphql::Result<(A, Vec<(T::Column, UpdateOperation)>)>
+where
+    T: EntityTrait,
+    <T as EntityTrait>::Model: IntoActiveModel<A>,
+    A: ActiveModelTrait<Entity = T> + sea_orm::ActiveModelBehavior + Send,
+{
+    let (mut data, operations) = entity_input_builder.parse_update_object::<T>(input_object)?;
+
+    validate_input::<T>(entity_input_builder.context, &data)?;
+
+    let mut active_model = A::default();
+
+    for column in T::Column::iter() {
+        // used to skip auto created primary keys
+        let auto_increment = match <T::PrimaryKey as PrimaryKeyToColumn>::from_column(column) {
+            Some(_) => T::PrimaryKey::auto_increment(),
+            None => false,
+        };
+
+        if auto_increment {
+            continue;
+        }
+
+        match data.remove(&entity_object_builder.column_name::<T>(&column)) {
+            Some(value) => {
+                active_model.try_set(column, value)?;
+            }
+            None => continue,
+        }
+    }
+
+    Ok((active_model, operations))
+}
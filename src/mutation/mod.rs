@@ -7,5 +7,20 @@ pub use entity_create_batch_mutation::*;
 pub mod entity_update_mutation;
 pub use entity_update_mutation::*;
 
+pub mod entity_update_batch_mutation;
+pub use entity_update_batch_mutation::*;
+
 pub mod entity_delete_mutation;
 pub use entity_delete_mutation::*;
+
+mod bulk_safety;
+pub(crate) use bulk_safety::*;
+
+pub mod dry_run;
+pub use dry_run::*;
+
+pub mod mutation_payload;
+pub use mutation_payload::*;
+
+pub mod request_transaction;
+pub use request_transaction::*;
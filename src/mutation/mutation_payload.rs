@@ -0,0 +1,358 @@
+use async_graphql::{
+    dynamic::{Field, FieldFuture, FieldValue, Object, TypeRef},
+    Value,
+};
+use sea_orm::EntityTrait;
+
+use crate::{
+    classify_db_error, hidden_message, BuilderContext, EntityObjectBuilder, ErrorCode,
+    RequestTransaction, SeaographyError,
+};
+
+/// The configuration structure of MutationPayloadBuilder, payloads are enabled
+/// per mutation with the `payload` option of its configuration
+pub struct MutationPayloadConfig {
+    /// name of the object reporting a problem with the input
+    pub user_error_type_name: String,
+    /// name for `records` field
+    pub records_field: String,
+    /// name for `affectedCount` field
+    pub affected_count_field: String,
+    /// name for `userErrors` field
+    pub user_errors_field: String,
+}
+
+impl std::default::Default for MutationPayloadConfig {
+    fn default() -> Self {
+        Self {
+            user_error_type_name: "UserError".into(),
+            records_field: "records".into(),
+            affected_count_field: {
+                if cfg!(feature = "field-snake-case") {
+                    "affected_count"
+                } else {
+                    "affectedCount"
+                }
+                .into()
+            },
+            user_errors_field: {
+                if cfg!(feature = "field-snake-case") {
+                    "user_errors"
+                } else {
+                    "userErrors"
+                }
+                .into()
+            },
+        }
+    }
+}
+
+/// The codes of the errors reported as user errors of a payload,
+/// all other errors are still returned as GraphQL errors
+const USER_ERROR_CODES: [ErrorCode; 8] = [
+    ErrorCode::BadUserInput,
+    ErrorCode::ValidationFailed,
+    ErrorCode::Conflict,
+    ErrorCode::UniqueViolation,
+    ErrorCode::ForeignKeyViolation,
+    ErrorCode::NotNullViolation,
+    ErrorCode::CheckViolation,
+    ErrorCode::TooManyAffectedRows,
+];
+
+/// A problem with the input of a mutation, reported in its payload
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserError {
+    pub field: Option<String>,
+    pub message: String,
+    pub code: String,
+}
+
+/// The result of a mutation returning a payload. Mutations failing with
+/// user errors are rolled back and affect no records
+#[derive(Clone, Debug, PartialEq)]
+pub struct MutationPayload<M> {
+    pub records: Vec<M>,
+    pub affected_count: u64,
+    pub user_errors: Vec<UserError>,
+}
+
+/// This builder produces the payload objects of the mutations
+pub struct MutationPayloadBuilder {
+    pub context: &'static BuilderContext,
+}
+
+impl MutationPayloadBuilder {
+    /// used to get the payload object name for a SeaORM entity
+    pub fn type_name<T>(&self, suffix: &str) -> String
+    where
+        T: EntityTrait,
+    {
+        let entity_object_builder = EntityObjectBuilder {
+            context: self.context,
+        };
+        format!("{}{}", entity_object_builder.type_name::<T>(), suffix)
+    }
+
+    /// used to get the payload object for a SeaORM entity, payloads without
+    /// records only report the affected count
+    pub fn to_object<T>(&self, suffix: &str, records: bool) -> Object
+    where
+        T: EntityTrait,
+        <T as EntityTrait>::Model: Sync,
+    {
+        let config = &self.context.mutation_payload;
+        let entity_object_builder = EntityObjectBuilder {
+            context: self.context,
+        };
+
+        let object = Object::new(self.type_name::<T>(suffix));
+        let object = if records {
+            object.field(Field::new(
+                &config.records_field,
+                TypeRef::named_nn_list_nn(entity_object_builder.basic_type_name::<T>()),
+                |ctx| {
+                    FieldFuture::new(async move {
+                        let payload = ctx
+                            .parent_value
+                            .try_downcast_ref::<MutationPayload<T::Model>>()?;
+                        Ok(Some(FieldValue::list(
+                            payload
+                                .records
+                                .iter()
+                                .map(|record| FieldValue::borrowed_any(record)),
+                        )))
+                    })
+                },
+            ))
+        } else {
+            object
+        };
+
+        object
+            .field(Field::new(
+                &config.affected_count_field,
+                TypeRef::named_nn(TypeRef::INT),
+                |ctx| {
+                    FieldFuture::new(async move {
+                        let payload = ctx
+                            .parent_value
+                            .try_downcast_ref::<MutationPayload<T::Model>>()?;
+                        Ok(Some(FieldValue::value(payload.affected_count)))
+                    })
+                },
+            ))
+            .field(Field::new(
+                &config.user_errors_field,
+                TypeRef::named_nn_list_nn(&config.user_error_type_name),
+                |ctx| {
+                    FieldFuture::new(async move {
+                        let payload = ctx
+                            .parent_value
+                            .try_downcast_ref::<MutationPayload<T::Model>>()?;
+                        Ok(Some(FieldValue::list(
+                            payload
+                                .user_errors
+                                .iter()
+                                .map(|user_error| FieldValue::borrowed_any(user_error)),
+                        )))
+                    })
+                },
+            ))
+    }
+
+    /// used to get the user error object
+    pub fn user_error_object(&self) -> Object {
+        Object::new(&self.context.mutation_payload.user_error_type_name)
+            .field(Field::new(
+                "field",
+                TypeRef::named(TypeRef::STRING),
+                |ctx| {
+                    FieldFuture::new(async move {
+                        let user_error = ctx.parent_value.try_downcast_ref::<UserError>()?;
+                        Ok(user_error.field.clone().map(FieldValue::value))
+                    })
+                },
+            ))
+            .field(Field::new(
+                "message",
+                TypeRef::named_nn(TypeRef::STRING),
+                |ctx| {
+                    FieldFuture::new(async move {
+                        let user_error = ctx.parent_value.try_downcast_ref::<UserError>()?;
+                        Ok(Some(FieldValue::value(user_error.message.clone())))
+                    })
+                },
+            ))
+            .field(Field::new(
+                "code",
+                TypeRef::named_nn(TypeRef::STRING),
+                |ctx| {
+                    FieldFuture::new(async move {
+                        let user_error = ctx.parent_value.try_downcast_ref::<UserError>()?;
+                        Ok(Some(FieldValue::value(user_error.code.clone())))
+                    })
+                },
+            ))
+    }
+
+    /// used to check whether any mutation returns payloads
+    pub fn enabled(&self) -> bool {
+        let context = self.context;
+        context.entity_create_one_mutation.payload
+            || context.entity_create_batch_mutation.payload
+            || context.entity_update_mutation.payload
+            || (context.entity_update_batch_mutation.enabled
+                && context.entity_update_batch_mutation.payload)
+            || context.entity_delete_mutation.payload
+    }
+
+    /// used to turn the result of a mutation resolver into a payload, if enabled. The
+    /// returned records, or the count of a delete, and the user errors are reported.
+    /// User errors roll back the request transaction, if any
+    pub fn resolve<'a, T>(
+        &self,
+        enabled: bool,
+        request_transaction: Option<&'a RequestTransaction>,
+        future: FieldFuture<'a>,
+    ) -> FieldFuture<'a>
+    where
+        T: EntityTrait,
+        <T as EntityTrait>::Model: Sync,
+    {
+        if !enabled {
+            return future;
+        }
+
+        let context = self.context;
+        FieldFuture::new(async move {
+            let result = match future {
+                FieldFuture::Value(value) => Ok(value),
+                FieldFuture::Future(future) => future.await,
+            };
+
+            let payload = match result {
+                Ok(value) => {
+                    let records: Vec<T::Model> = match &value {
+                        Some(value) => match value.as_list() {
+                            Some(list) => list
+                                .iter()
+                                .filter_map(|item| item.downcast_ref::<T::Model>())
+                                .cloned()
+                                .collect(),
+                            None => value
+                                .downcast_ref::<T::Model>()
+                                .cloned()
+                                .into_iter()
+                                .collect(),
+                        },
+                        None => Vec::new(),
+                    };
+                    let affected_count = match value.as_ref().and_then(FieldValue::as_value) {
+                        Some(Value::Number(count)) => count.as_u64().unwrap_or_default(),
+                        _ => records.len() as u64,
+                    };
+
+                    MutationPayload {
+                        records,
+                        affected_count,
+                        user_errors: Vec::new(),
+                    }
+                }
+                Err(error) => match user_errors(context, &error) {
+                    Some(user_errors) => {
+                        if let Some(request_transaction) = request_transaction {
+                            request_transaction.fail();
+                        }
+                        MutationPayload {
+                            records: Vec::new(),
+                            affected_count: 0,
+                            user_errors,
+                        }
+                    }
+                    None => return Err(error),
+                },
+            };
+
+            Ok(Some(FieldValue::owned_any(payload)))
+        })
+    }
+}
+
+/// used to get the user errors reported by an error, `None` if it is not caused by the input
+pub fn user_errors(
+    context: &'static BuilderContext,
+    error: &async_graphql::Error,
+) -> Option<Vec<UserError>> {
+    // coded errors wrapped by conversions
+    let (message, extensions) = match error_source::<SeaographyError>(error) {
+        Some(SeaographyError::AsyncGraphQLError(inner)) if inner.extensions.is_some() => {
+            (&inner.message, inner.extensions.as_ref())
+        }
+        _ => (&error.message, error.extensions.as_ref()),
+    };
+
+    let code = match extensions.and_then(|extensions| extensions.get("code")) {
+        Some(Value::String(code)) => *USER_ERROR_CODES
+            .iter()
+            .find(|user_code| user_code.as_str() == code)?,
+        Some(_) => return None,
+        None => {
+            if let Some(db_err) = error_source::<sea_orm::DbErr>(error) {
+                let (code, _) = classify_db_error(db_err);
+                *USER_ERROR_CODES
+                    .iter()
+                    .find(|user_code| **user_code == code)?
+            } else if error_source::<SeaographyError>(error).is_some() {
+                ErrorCode::BadUserInput
+            } else {
+                return None;
+            }
+        }
+    };
+
+    // one user error per failing field of a validation
+    if let Some(Value::List(fields)) = extensions.and_then(|extensions| extensions.get("fields")) {
+        return Some(
+            fields
+                .iter()
+                .filter_map(|field| match field {
+                    Value::Object(field) => Some(UserError {
+                        field: match field.get("field") {
+                            Some(Value::String(name)) => Some(name.clone()),
+                            _ => None,
+                        },
+                        message: match field.get("message") {
+                            Some(Value::String(message)) => message.clone(),
+                            _ => String::new(),
+                        },
+                        code: code.as_str().into(),
+                    }),
+                    _ => None,
+                })
+                .collect(),
+        );
+    }
+
+    let message = match hidden_message(code) {
+        Some(hidden) if context.errors.hide_internal => hidden.into(),
+        _ => message.clone(),
+    };
+
+    Some(vec![UserError {
+        field: match extensions.and_then(|extensions| extensions.get("field")) {
+            Some(Value::String(field)) => Some(field.clone()),
+            _ => None,
+        },
+        message,
+        code: code.as_str().into(),
+    }])
+}
+
+/// used to get the source of an error, kept by the conversions of `?`
+fn error_source<T: std::any::Any>(error: &async_graphql::Error) -> Option<&T> {
+    error
+        .source
+        .as_ref()
+        .and_then(|source| source.downcast_ref::<T>())
+}
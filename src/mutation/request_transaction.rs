@@ -0,0 +1,177 @@
+use std::sync::{Arc, Mutex};
+
+use async_graphql::{
+    dynamic::ResolverContext,
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute, NextPrepareRequest},
+    Request, Response, ServerError, ServerResult,
+};
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
+
+use crate::DatabaseContext;
+
+/// The transaction of the restricted connection, as opened by the mutations
+pub type ConnectionTransaction =
+    <<DatabaseConnection as DatabaseContext>::Connection as TransactionTrait>::Transaction;
+
+/// The configuration of the transaction shared by the mutation fields of a request
+#[derive(Default)]
+pub struct RequestTransactionConfig {
+    /// run all the mutation fields of a request in one transaction, committed after the last
+    /// field or rolled back if any field fails. Each field runs in a savepoint of it
+    pub enabled: bool,
+}
+
+/// The transaction shared by the mutation fields of a request, stored in the request data.
+/// It is opened by the first mutation, from the restricted connection of its user
+#[derive(Default)]
+pub struct RequestTransaction {
+    state: Mutex<RequestTransactionState>,
+}
+
+#[derive(Default)]
+struct RequestTransactionState {
+    transaction: Option<Arc<ConnectionTransaction>>,
+    failed: bool,
+    changes: Vec<crate::EntityChangeNotification>,
+}
+
+impl RequestTransaction {
+    /// used to open a savepoint of the request transaction, opening it on first use
+    pub async fn begin<C>(&self, db: &C) -> Result<ConnectionTransaction, DbErr>
+    where
+        C: TransactionTrait<Transaction = ConnectionTransaction>,
+    {
+        let transaction = self.lock().transaction.clone();
+        let transaction = match transaction {
+            Some(transaction) => transaction,
+            None => {
+                let transaction = Arc::new(db.begin().await?);
+                self.lock().transaction = Some(transaction.clone());
+                transaction
+            }
+        };
+
+        transaction.begin().await
+    }
+
+    /// used to roll the request transaction back at the end of the request,
+    /// for failures not returned as errors
+    pub fn fail(&self) {
+        self.lock().failed = true;
+    }
+
+    /// used to hold back a change notification until the request transaction is committed,
+    /// returns the notification if no request transaction is open
+    pub(crate) fn defer(
+        &self,
+        change: crate::EntityChangeNotification,
+    ) -> Option<crate::EntityChangeNotification> {
+        let mut state = self.lock();
+        if state.transaction.is_none() {
+            return Some(change);
+        }
+        state.changes.push(change);
+        None
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RequestTransactionState> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// used to get the request transaction once a mutation has opened it. The reads of the
+/// rest of the request have to go through it, custom resolvers included
+pub fn open_request_transaction(
+    ctx: &async_graphql::Context<'_>,
+) -> Option<Arc<ConnectionTransaction>> {
+    ctx.data_opt::<RequestTransaction>()?
+        .lock()
+        .transaction
+        .clone()
+}
+
+/// used to begin the transaction of a mutation, a savepoint of the request transaction if any
+pub async fn begin_transaction<C>(
+    ctx: &ResolverContext<'_>,
+    db: &C,
+) -> Result<ConnectionTransaction, DbErr>
+where
+    C: TransactionTrait<Transaction = ConnectionTransaction>,
+{
+    match ctx.data_opt::<RequestTransaction>() {
+        Some(request_transaction) => request_transaction.begin(db).await,
+        None => db.begin().await,
+    }
+}
+
+/// This extension shares one transaction across the mutation fields of every request,
+/// committing it after the operation unless a field failed. Change events are published and
+/// `entity_watch_changes` hooks run after the commit, `entity_watch` hooks as each field completes
+pub struct RequestTransactionExtension;
+
+impl ExtensionFactory for RequestTransactionExtension {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(RequestTransactionExtension)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for RequestTransactionExtension {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        next.run(ctx, request.data(RequestTransaction::default()))
+            .await
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let mut response = next.run(ctx, operation_name).await;
+
+        let Some(request_transaction) = ctx.data_opt::<RequestTransaction>() else {
+            return response;
+        };
+        let state = std::mem::take(&mut *request_transaction.lock());
+        let Some(transaction) = state.transaction else {
+            return response;
+        };
+        // the savepoints of the fields have been released, the request holds the last reference
+        let Ok(transaction) = Arc::try_unwrap(transaction) else {
+            response.errors.push(ServerError::new(
+                "Request transaction is still in use, rolled back.",
+                None,
+            ));
+            return response;
+        };
+
+        if state.failed || response.is_err() {
+            if let Err(error) = transaction.rollback().await {
+                response
+                    .errors
+                    .push(ServerError::new(error.to_string(), None));
+            }
+            return response;
+        }
+
+        if let Err(error) = transaction.commit().await {
+            response
+                .errors
+                .push(ServerError::new(error.to_string(), None));
+            return response;
+        }
+
+        let watch_context = crate::WatchContext::extension(ctx);
+        for change in state.changes {
+            change.dispatch(&watch_context).await;
+        }
+
+        response
+    }
+}
@@ -1,5 +1,5 @@
 use async_graphql::{
-    dynamic::{Field, FieldFuture, Object, ObjectAccessor},
+    dynamic::{Field, FieldFuture, FieldValue, Object, ObjectAccessor},
     Value,
 };
 use heck::{ToLowerCamelCase, ToSnakeCase, ToUpperCamelCase};
@@ -8,7 +8,9 @@ use sea_orm::{
     ModelTrait, TryIntoModel,
 };
 
-use crate::{guard_error, EntityColumnId, OperationType, SeaResult, SeaographyError};
+use crate::{
+    guard_error, read_field_guard, EntityColumnId, OperationType, SeaResult, SeaographyError,
+};
 
 /// The configuration structure for EntityObjectBuilder
 pub struct EntityObjectConfig {
@@ -18,6 +20,8 @@ pub struct EntityObjectConfig {
     pub column_name: crate::ComplexNamingFn,
     /// suffix that is appended on basic version of entity type
     pub basic_type_suffix: String,
+    /// names of "{entity}.{column}" field guards may mask, they are nullable in the output
+    pub maskable_fields: Vec<String>,
 }
 
 impl std::default::Default for EntityObjectConfig {
@@ -34,6 +38,7 @@ impl std::default::Default for EntityObjectConfig {
                 }
             }),
             basic_type_suffix: "Basic".into(),
+            maskable_fields: Vec::new(),
         }
     }
 }
@@ -113,18 +118,35 @@ impl EntityObjectBuilder {
             context: self.context,
         };
 
+        let entity_name = self.type_name::<T>();
+
         T::Column::iter().fold(
             Object::new(&object_name),
             move |object, column: T::Column| {
                 let object_name = object_name.clone();
+                let entity_name = entity_name.clone();
                 let column_name = self.column_name::<T>(&column);
                 let entity_column_id = EntityColumnId::of::<T>(&column);
 
+                if self.context.visibility.is_field_hidden(
+                    &entity_name,
+                    &column_name,
+                    OperationType::Read,
+                ) {
+                    return object;
+                }
+
                 let column_def = column.def();
+                let maskable = self
+                    .context
+                    .entity_object
+                    .maskable_fields
+                    .contains(&format!("{entity_name}.{column_name}"));
+                let not_null = !column_def.is_null() && !maskable;
                 let graphql_type = match types_map_helper.output_type_for_column::<T>(
                     &column,
                     &entity_column_id,
-                    !column_def.is_null(),
+                    not_null,
                 ) {
                     Some(type_name) => type_name,
                     None => return object,
@@ -153,41 +175,56 @@ impl EntityObjectBuilder {
                     .get(&entity_column_id)
                     .and_then(|options| options.output_conversion.as_ref());
 
-                let hooks = &self.context.hooks;
                 let context = self.context;
 
                 let field = Field::new(column_name.clone(), graphql_type, move |ctx| {
-                    if let GuardAction::Block(reason) =
-                        hooks.field_guard(&ctx, &object_name, &column_name, OperationType::Read)
-                    {
-                        return FieldFuture::new(async move {
-                            Err::<Option<()>, _>(guard_error(reason, "Field guard triggered."))
-                        });
-                    }
-
-                    // convert SeaQL value to GraphQL value
-                    let object = match ctx.parent_value.try_downcast_ref::<T::Model>() {
-                        Ok(object) => object,
-                        Err(_) => {
-                            let object_name = object_name.clone();
-                            return FieldFuture::new(async move {
-                                Err::<Option<()>, _>(async_graphql::Error::new(format!(
-                                    "Failed to downcast object to {object_name}"
-                                )))
-                            });
+                    let object_name = object_name.clone();
+                    let entity_name = entity_name.clone();
+                    let column_name = column_name.clone();
+                    FieldFuture::new(async move {
+                        if !context.visibility.allows_user(
+                            &ctx,
+                            &entity_name,
+                            Some(&column_name),
+                            OperationType::Read,
+                        ) {
+                            return Err(guard_error(None, "Field guard triggered."));
                         }
-                    };
-
-                    if let Some(conversion_fn) = conversion_fn {
-                        let result = conversion_fn(&object.get(column));
-                        return FieldFuture::new(async move { result });
-                    }
-
-                    FieldFuture::from_value(sea_query_value_to_graphql_value(
-                        context,
-                        object.get(column),
-                        is_enum,
-                    ))
+
+                        let guard =
+                            read_field_guard::<T>(&ctx, context, &object_name, &column_name).await;
+                        match guard {
+                            GuardAction::Block(reason) => {
+                                return Err(guard_error(reason, "Field guard triggered."));
+                            }
+                            GuardAction::Mask if not_null => {
+                                return Err(async_graphql::Error::new(format!(
+                                    "Field `{entity_name}.{column_name}` is not in `maskable_fields`."
+                                )));
+                            }
+                            GuardAction::Mask => return Ok(None),
+                            GuardAction::Replace(value) => {
+                                return Ok(Some(FieldValue::value(value)));
+                            }
+                            GuardAction::Allow => {}
+                        }
+
+                        // convert SeaQL value to GraphQL value
+                        let Ok(object) = ctx.parent_value.try_downcast_ref::<T::Model>() else {
+                            return Err(async_graphql::Error::new(format!(
+                                "Failed to downcast object to {object_name}"
+                            )));
+                        };
+
+                        if let Some(conversion_fn) = conversion_fn {
+                            return conversion_fn(&object.get(column));
+                        }
+
+                        Ok(
+                            sea_query_value_to_graphql_value(context, object.get(column), is_enum)
+                                .map(FieldValue::value),
+                        )
+                    })
                 });
 
                 object.field(field)
@@ -1,15 +1,14 @@
-use async_graphql::{
-    dataloader::DataLoader,
-    dynamic::{Field, FieldFuture, FieldValue, InputValue, TypeRef},
-};
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, TypeRef};
 use heck::{ToLowerCamelCase, ToSnakeCase};
 use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, QueryTrait, RelationDef};
 
 use crate::{
-    apply_memory_pagination, get_filter_conditions, guard_error, loader_impl, pluralize_unique,
+    apply_memory_pagination, get_filter_conditions, guard_error, load_many, load_one, loader_impl,
+    pluralize_unique, record_entity_read, reject_masked_fields, resolve_after_read,
+    resolve_after_read_one, resolve_entity_filter, resolve_entity_guard, resolve_read_field_guard,
     BuilderContext, Connection, ConnectionObjectBuilder, DatabaseContext, EntityObjectBuilder,
-    FilterInputBuilder, GuardAction, HashableGroupKey, KeyComplex, OneToManyLoader, OneToOneLoader,
-    OperationType, OrderInputBuilder, PaginationInputBuilder, UserContext,
+    FilterInputBuilder, GuardAction, HashableGroupKey, KeyComplex, OperationType,
+    OrderInputBuilder, PaginationInputBuilder, UserContext,
 };
 
 /// This builder produces a GraphQL field for an SeaORM entity relationship
@@ -59,7 +58,6 @@ impl EntityObjectRelationBuilder {
         let parent_name: String = entity_object_builder.type_name::<T>();
         let object_name: String = entity_object_builder.type_name::<R>();
         let object_name_ = object_name.clone();
-        let hooks = &self.context.hooks;
         let relation_definition_is_owner = relation_definition.is_owner;
 
         let field_name = name.clone();
@@ -71,14 +69,31 @@ impl EntityObjectRelationBuilder {
                 let relation_definition = relation_definition.clone();
                 FieldFuture::new(async move {
                     if let GuardAction::Block(reason) =
-                        hooks.entity_guard(&ctx, &object_name, OperationType::Read)
+                        resolve_entity_guard::<R>(&ctx, context, &object_name, OperationType::Read)
+                            .await
                     {
                         return Err(guard_error(reason, "Entity guard triggered."));
                     }
-                    if let GuardAction::Block(reason) =
-                        hooks.field_guard(&ctx, &parent_name, &field_name, OperationType::Read)
+
+                    record_entity_read(&ctx, &object_name);
+
+                    let guard =
+                        resolve_read_field_guard::<T>(&ctx, context, &parent_name, &field_name)
+                            .await;
+                    match guard {
+                        GuardAction::Block(reason) => {
+                            return Err(guard_error(reason, "Field guard triggered."));
+                        }
+                        GuardAction::Mask | GuardAction::Replace(_) => return Ok(None),
+                        GuardAction::Allow => {}
+                    }
+
+                    let mut stmt = R::find();
+                    if let Some(filter) =
+                        resolve_entity_filter::<R>(&ctx, context, &object_name, OperationType::Read)
+                            .await
                     {
-                        return Err(guard_error(reason, "Field guard triggered."));
+                        stmt = stmt.filter(filter);
                     }
 
                     let Ok(parent) = ctx.parent_value.try_downcast_ref::<T::Model>() else {
@@ -88,15 +103,6 @@ impl EntityObjectRelationBuilder {
                         )));
                     };
 
-                    let loader = ctx.data_unchecked::<DataLoader<OneToOneLoader<R>>>();
-
-                    let mut stmt = R::find();
-                    if let Some(filter) =
-                        hooks.entity_filter(&ctx, &object_name, OperationType::Read)
-                    {
-                        stmt = stmt.filter(filter);
-                    }
-
                     let db = ctx
                         .data::<DatabaseConnection>()?
                         .restricted(ctx.data_opt::<UserContext>())?;
@@ -123,7 +129,8 @@ impl EntityObjectRelationBuilder {
                         },
                     };
 
-                    let data = loader.load_one(key).await?;
+                    let data = load_one(&ctx, key).await?;
+                    let data = resolve_after_read_one::<R>(&ctx, context, &object_name, data);
 
                     if let Some(data) = data {
                         Ok(Some(FieldValue::owned_any(data)))
@@ -143,15 +150,58 @@ impl EntityObjectRelationBuilder {
                     let relation_definition = relation_definition.clone();
                     let context: &'static BuilderContext = context;
                     FieldFuture::new(async move {
-                        if let GuardAction::Block(reason) =
-                            hooks.entity_guard(&ctx, &object_name, OperationType::Read)
+                        if let GuardAction::Block(reason) = resolve_entity_guard::<R>(
+                            &ctx,
+                            context,
+                            &object_name,
+                            OperationType::Read,
+                        )
+                        .await
                         {
                             return Err(guard_error(reason, "Entity guard triggered."));
                         }
-                        if let GuardAction::Block(reason) =
-                            hooks.field_guard(&ctx, &parent_name, &field_name, OperationType::Read)
+
+                        record_entity_read(&ctx, &object_name);
+
+                        let guard =
+                            resolve_read_field_guard::<T>(&ctx, context, &parent_name, &field_name)
+                                .await;
+                        match guard {
+                            GuardAction::Block(reason) => {
+                                return Err(guard_error(reason, "Field guard triggered."));
+                            }
+                            // masked relations resolve to an empty connection
+                            GuardAction::Mask | GuardAction::Replace(_) => {
+                                let pagination =
+                                    ctx.args.get(&context.entity_query_field.pagination);
+                                let pagination =
+                                    PaginationInputBuilder { context }.parse_object(pagination)?;
+                                let connection: Connection<R> =
+                                    apply_memory_pagination(context, None, pagination)?;
+                                return Ok(Some(FieldValue::owned_any(connection)));
+                            }
+                            GuardAction::Allow => {}
+                        }
+
+                        reject_masked_fields::<R>(
+                            &ctx,
+                            context,
+                            ctx.args.get(&context.entity_query_field.filters),
+                            ctx.args.get(&context.entity_query_field.order_by),
+                            None,
+                        )
+                        .await?;
+
+                        let mut stmt = R::find();
+                        if let Some(filter) = resolve_entity_filter::<R>(
+                            &ctx,
+                            context,
+                            &object_name,
+                            OperationType::Read,
+                        )
+                        .await
                         {
-                            return Err(guard_error(reason, "Field guard triggered."));
+                            stmt = stmt.filter(filter);
                         }
 
                         let Ok(parent) = ctx.parent_value.try_downcast_ref::<T::Model>() else {
@@ -161,15 +211,6 @@ impl EntityObjectRelationBuilder {
                             )));
                         };
 
-                        let loader = ctx.data_unchecked::<DataLoader<OneToManyLoader<R>>>();
-
-                        let mut stmt = R::find();
-                        if let Some(filter) =
-                            hooks.entity_filter(&ctx, &object_name, OperationType::Read)
-                        {
-                            stmt = stmt.filter(filter);
-                        }
-
                         let db = &ctx
                             .data::<DatabaseConnection>()?
                             .restricted(ctx.data_opt::<UserContext>())?;
@@ -196,7 +237,10 @@ impl EntityObjectRelationBuilder {
                             },
                         };
 
-                        let values = loader.load_one(key).await?;
+                        let mut values = load_many(&ctx, key).await?;
+                        if let Some(values) = values.as_mut() {
+                            resolve_after_read::<R>(&ctx, context, &object_name, values);
+                        }
 
                         let pagination = ctx.args.get(&context.entity_query_field.pagination);
                         let pagination =
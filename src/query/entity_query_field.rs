@@ -4,9 +4,11 @@ use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter};
 
 use crate::{
     apply_order, apply_pagination, get_filter_conditions, get_having_conditions, guard_error,
-    pluralize_unique, BuilderContext, ConnectionObjectBuilder, DatabaseContext, EntityColumnId,
-    EntityObjectBuilder, FilterInputBuilder, GuardAction, HavingInputBuilder, OperationType,
-    OrderInputBuilder, PaginationInput, PaginationInputBuilder, UserContext,
+    pluralize_unique, record_entity_read, reject_masked_fields, resolve_after_read_connection,
+    resolve_after_read_one, resolve_entity_filter, resolve_entity_guard, BuilderContext,
+    ConnectionObjectBuilder, DatabaseContext, EntityColumnId, EntityObjectBuilder,
+    FilterInputBuilder, GuardAction, HavingInputBuilder, OperationType, OrderInputBuilder,
+    PaginationInput, PaginationInputBuilder, UserContext,
 };
 
 /// The configuration structure for EntityQueryFieldBuilder
@@ -74,6 +76,14 @@ impl EntityQueryFieldBuilder {
         self.context.entity_query_field.type_name.as_ref()(&object_name)
     }
 
+    /// used to get the name of the connection field for a SeaORM entity
+    pub fn connection_field_name<T>(&self) -> String
+    where
+        T: EntityTrait,
+    {
+        pluralize_unique(&self.type_name_vanilla::<T>(), true)
+    }
+
     fn type_name_vanilla<T>(&self) -> String
     where
         T: EntityTrait,
@@ -100,7 +110,6 @@ impl EntityQueryFieldBuilder {
         };
 
         let object_name = entity_object.type_name::<T>();
-        let hooks = &self.context.hooks;
 
         let column = T::PrimaryKey::iter()
             .map(|variant| variant.into_column())
@@ -124,12 +133,21 @@ impl EntityQueryFieldBuilder {
                 let object_name = object_name.clone();
                 FieldFuture::new(async move {
                     if let GuardAction::Block(reason) =
-                        hooks.entity_guard(&ctx, &object_name, OperationType::Read)
+                        resolve_entity_guard::<T>(&ctx, context, &object_name, OperationType::Read)
+                            .await
                     {
                         return Err(guard_error(reason, "Entity guard triggered."));
                     }
 
+                    record_entity_read(&ctx, &object_name);
+
                     let mut stmt = T::find();
+                    if let Some(filter) =
+                        resolve_entity_filter::<T>(&ctx, context, &object_name, OperationType::Read)
+                            .await
+                    {
+                        stmt = stmt.filter(filter);
+                    }
                     let mapper = TypesMapHelper { context };
                     let column = T::PrimaryKey::iter()
                         .map(|variant| variant.into_column())
@@ -146,6 +164,7 @@ impl EntityQueryFieldBuilder {
                         .restricted(ctx.data_opt::<UserContext>())?;
 
                     let r = stmt.one(db).await?;
+                    let r = resolve_after_read_one::<T>(&ctx, context, &object_name, r);
 
                     Ok(r.map(FieldValue::owned_any))
                 })
@@ -183,19 +202,30 @@ impl EntityQueryFieldBuilder {
         let object_name_ = object_name.clone();
         let type_name = connection_object_builder.type_name(&object_name);
 
-        let hooks = &self.context.hooks;
         let context: &'static BuilderContext = self.context;
-        let connection_name = pluralize_unique(&self.type_name_vanilla::<T>(), true);
+        let connection_name = self.connection_field_name::<T>();
 
         Field::new(connection_name, TypeRef::named_nn(type_name), move |ctx| {
             let object_name = object_name.clone();
             FieldFuture::new(async move {
                 if let GuardAction::Block(reason) =
-                    hooks.entity_guard(&ctx, &object_name, OperationType::Read)
+                    resolve_entity_guard::<T>(&ctx, context, &object_name, OperationType::Read)
+                        .await
                 {
                     return Err(guard_error(reason, "Entity guard triggered."));
                 }
 
+                record_entity_read(&ctx, &object_name);
+
+                reject_masked_fields::<T>(
+                    &ctx,
+                    context,
+                    ctx.args.get(&context.entity_query_field.filters),
+                    ctx.args.get(&context.entity_query_field.order_by),
+                    ctx.args.get(&context.entity_query_field.having),
+                )
+                .await?;
+
                 let filters = ctx.args.get(&context.entity_query_field.filters);
                 let filters = get_filter_conditions::<T>(context, filters)?;
                 let having = ctx.args.get(&context.entity_query_field.having);
@@ -207,7 +237,10 @@ impl EntityQueryFieldBuilder {
                     PaginationInputBuilder { context }.parse_object(pagination)?;
 
                 let mut stmt = T::find();
-                if let Some(filter) = hooks.entity_filter(&ctx, &object_name, OperationType::Read) {
+                if let Some(filter) =
+                    resolve_entity_filter::<T>(&ctx, context, &object_name, OperationType::Read)
+                        .await
+                {
                     stmt = stmt.filter(filter);
                 }
                 stmt = stmt.filter(filters);
@@ -217,7 +250,9 @@ impl EntityQueryFieldBuilder {
                     .data::<DatabaseConnection>()?
                     .restricted(ctx.data_opt::<UserContext>())?;
 
-                let connection = apply_pagination::<T, _>(context, db, stmt, pagination).await?;
+                let mut connection =
+                    apply_pagination::<T, _>(context, db, stmt, pagination).await?;
+                resolve_after_read_connection::<T>(&ctx, context, &object_name, &mut connection);
 
                 Ok(Some(FieldValue::owned_any(connection)))
             })
@@ -3,11 +3,12 @@ use sea_orm::{
     sea_query::Expr, Condition, EntityTrait, Iterable, QueryFilter, QuerySelect, QueryTrait,
     Related, RelationDef,
 };
-use std::marker::PhantomData;
+use std::{collections::BTreeSet, marker::PhantomData};
 
 use crate::{
-    recursive_prepare_condition, BuilderContext, EntityObjectBuilder, FilterInputBuilder,
-    RelationBuilder, SeaResult,
+    coded_error, collect_column_fields, recursive_prepare_condition, relation_is_hidden,
+    tenant_condition, BuilderContext, EntityObjectBuilder, ErrorCode, FilterInputBuilder,
+    OperationType, RelationBuilder, SeaResult, SeaographyError,
 };
 
 /// utility function used to create the query filter condition
@@ -24,7 +25,7 @@ where
     if let Some(having) = having {
         let having = having.object()?;
         let related = ctx.data_unchecked::<RelatedEntityFilter<T>>();
-        related.apply(context, condition, &having)
+        related.apply(context, ctx, condition, &having)
     } else {
         Ok(condition)
     }
@@ -34,8 +35,11 @@ pub struct RelatedEntityFilterBuilder {
     pub context: &'static BuilderContext,
 }
 
-type FnFilterCondition =
-    Box<dyn Fn(&'static BuilderContext, &ObjectAccessor) -> SeaResult<Option<Expr>> + Send + Sync>;
+type FnFilterCondition = Box<
+    dyn Fn(&'static BuilderContext, &ResolverContext, &ObjectAccessor) -> SeaResult<Option<Expr>>
+        + Send
+        + Sync,
+>;
 
 pub struct RelatedEntityFilter<E>
 where
@@ -47,10 +51,28 @@ where
 
 pub struct RelatedEntityFilterField {
     name: String,
+    /// name of the relation field of the entity object
+    relation_name: String,
+    related_type_name: String,
+    /// column fields of the related entity
+    related_columns: BTreeSet<String>,
     filter_input: String,
     filter_condition_fn: FnFilterCondition,
 }
 
+/// used for entities without relations
+impl<E> std::default::Default for RelatedEntityFilter<E>
+where
+    E: EntityTrait,
+{
+    fn default() -> Self {
+        Self {
+            fields: Vec::new(),
+            entity: PhantomData,
+        }
+    }
+}
+
 impl<E> RelatedEntityFilter<E>
 where
     E: EntityTrait,
@@ -61,7 +83,11 @@ where
     {
         Self {
             fields: T::iter()
-                .map(|rel| rel.get_related_entity_filter(context))
+                .filter(|rel| !relation_is_hidden::<E, T>(context, rel))
+                .map(|rel| RelatedEntityFilterField {
+                    relation_name: rel.get_relation_name(context),
+                    ..rel.get_related_entity_filter(context)
+                })
                 .collect(),
             entity: PhantomData,
         }
@@ -75,16 +101,62 @@ where
             .collect()
     }
 
+    /// used to get the relations filtered on by a having input: the relation field,
+    /// the related object type and the column fields of the related entity filtered on
+    pub(crate) fn filtered_fields(
+        &self,
+        having: &async_graphql::Value,
+    ) -> Vec<(&str, &str, BTreeSet<String>)> {
+        let async_graphql::Value::Object(having) = having else {
+            return Vec::new();
+        };
+
+        self.fields
+            .iter()
+            .filter_map(|field| {
+                let filter = having.get(field.name.as_str())?;
+                let mut fields = BTreeSet::new();
+                collect_column_fields(&field.related_columns, filter, &mut fields);
+                Some((
+                    field.relation_name.as_str(),
+                    field.related_type_name.as_str(),
+                    fields,
+                ))
+            })
+            .collect()
+    }
+
     fn apply(
         &self,
         context: &'static BuilderContext,
+        ctx: &ResolverContext,
         mut condition: Condition,
         having: &ObjectAccessor,
     ) -> SeaResult<Condition> {
+        let entity_name = EntityObjectBuilder { context }.type_name::<E>();
+
         for field in &self.fields {
             if let Some(filter) = having.get(&field.name) {
+                let visibility = &context.visibility;
+                if !visibility.allows_user(
+                    ctx,
+                    &entity_name,
+                    Some(&field.relation_name),
+                    OperationType::Read,
+                ) || !visibility.allows_user(
+                    ctx,
+                    &field.related_type_name,
+                    None,
+                    OperationType::Read,
+                ) {
+                    return Err(SeaographyError::AsyncGraphQLError(coded_error(
+                        ErrorCode::Forbidden,
+                        format!("Filtering on relation `{}` is not allowed.", field.name),
+                    )));
+                }
+
                 let filter = filter.object()?;
-                if let Some(additional) = (field.filter_condition_fn)(context, &filter)? {
+                if let Some(additional) = (field.filter_condition_fn)(context, ctx, &filter)? {
                     condition = condition.add(additional);
                 }
             }
@@ -117,6 +189,11 @@ impl RelatedEntityFilterBuilder {
 }
 
 impl RelatedEntityFilterField {
+    /// used to get the object type name of the related entity
+    pub fn related_type_name(&self) -> &str {
+        &self.related_type_name
+    }
+
     fn new<R>(
         context: &'static BuilderContext,
         name: String,
@@ -126,17 +203,31 @@ impl RelatedEntityFilterField {
     where
         R: EntityTrait,
     {
+        let entity_object_builder = EntityObjectBuilder { context };
+        let related_type_name = entity_object_builder.type_name::<R>();
+        let related_columns = R::Column::iter()
+            .map(|column| entity_object_builder.column_name::<R>(&column))
+            .collect();
+
         Self {
+            relation_name: name.clone(),
             name,
-            filter_input: {
-                let entity_object_builder = EntityObjectBuilder { context };
-                let filter_input_builder = FilterInputBuilder { context };
-                let object_name: String = entity_object_builder.type_name::<R>();
-                filter_input_builder.type_name(&object_name)
-            },
-            filter_condition_fn: Box::new(move |context, filter| -> SeaResult<Option<Expr>> {
+            related_columns,
+            filter_input: FilterInputBuilder { context }.type_name(&related_type_name),
+            related_type_name,
+            filter_condition_fn: Box::new(move |context, ctx, filter| -> SeaResult<Option<Expr>> {
                 let mut condition = recursive_prepare_condition::<R>(context, filter)?;
                 if !condition.is_empty() {
+                    // related rows outside the row policies or the tenant must not be matched either
+                    if let Some(policy) = context
+                        .row_policies
+                        .condition::<R>(ctx, OperationType::Read)
+                    {
+                        condition = condition.add(policy);
+                    }
+                    if let Some(tenant) = tenant_condition::<R>(ctx, context) {
+                        condition = condition.add(tenant);
+                    }
                     // WHERE EXISTS(
                     // SELECT 1 FROM "actor"
                     // INNER JOIN "film_actor" ON "film_actor"."actor_id" = "actor"."actor_id" <- junction table, if applicable
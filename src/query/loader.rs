@@ -3,10 +3,13 @@ pub(crate) mod loader_impl;
 
 use loader_impl::*;
 
-use sea_orm::{sea_query::ValueTuple, EntityTrait, QueryFilter, RelationDef};
-use std::{collections::HashMap, hash::Hash, marker::PhantomData};
+use async_graphql::{dataloader::DataLoader, dynamic::ResolverContext};
+use sea_orm::{
+    sea_query::ValueTuple, ConnectionTrait, DbErr, EntityTrait, QueryFilter, RelationDef,
+};
+use std::{collections::HashMap, hash::Hash, marker::PhantomData, sync::Arc};
 
-use crate::apply_order;
+use crate::{apply_order, open_request_transaction};
 
 #[derive(Clone, Debug)]
 pub struct KeyComplex<T>
@@ -65,36 +68,7 @@ where
         &self,
         groups: &[KeyComplex<T>],
     ) -> Result<HashMap<KeyComplex<T>, Self::Value>, Self::Error> {
-        let groups = consolidate_groups(groups);
-
-        let mut results: HashMap<KeyComplex<T>, Vec<T::Model>> = HashMap::new();
-
-        for (group, keys) in groups {
-            let g = group.clone();
-            let mut stmt = g.stmt;
-            stmt = stmt.filter(g.filters);
-            stmt = apply_order(stmt, g.order_by);
-            let models: HashMap<ValueTuple, Vec<T::Model>> = loader_impl(
-                keys,
-                g.junction_fields,
-                stmt,
-                g.rel_def,
-                g.via_def,
-                &self.connection,
-            )
-            .await?;
-            for (key, models) in models {
-                results.insert(
-                    KeyComplex {
-                        key,
-                        meta: group.clone(),
-                    },
-                    models,
-                );
-            }
-        }
-
-        Ok(results)
+        load_one_to_many(groups, &self.connection).await
     }
 }
 
@@ -131,38 +105,139 @@ where
         &self,
         groups: &[KeyComplex<T>],
     ) -> Result<HashMap<KeyComplex<T>, Self::Value>, Self::Error> {
-        let groups = consolidate_groups(groups);
-
-        let mut results: HashMap<KeyComplex<T>, T::Model> = HashMap::new();
-
-        for (group, keys) in groups {
-            let g = group.clone();
-            let mut stmt = g.stmt;
-            stmt = stmt.filter(g.filters);
-            stmt = apply_order(stmt, g.order_by);
-            let models: HashMap<ValueTuple, Option<T::Model>> = loader_impl(
-                keys,
-                g.junction_fields,
-                stmt,
-                g.rel_def,
-                g.via_def,
-                &self.connection,
-            )
-            .await?;
-            for (key, model) in models {
-                if let Some(model) = model {
-                    results.insert(
-                        KeyComplex {
-                            key,
-                            meta: group.clone(),
-                        },
-                        model,
-                    );
-                }
+        load_one_to_one(groups, &self.connection).await
+    }
+}
+
+/// used to load the related models of the keys with a connection
+async fn load_one_to_many<T, C>(
+    groups: &[KeyComplex<T>],
+    connection: &C,
+) -> Result<HashMap<KeyComplex<T>, Vec<T::Model>>, Arc<DbErr>>
+where
+    T: EntityTrait,
+    T::Model: Sync,
+    C: ConnectionTrait,
+{
+    let groups = consolidate_groups(groups);
+
+    let mut results: HashMap<KeyComplex<T>, Vec<T::Model>> = HashMap::new();
+
+    for (group, keys) in groups {
+        let g = group.clone();
+        let mut stmt = g.stmt;
+        stmt = stmt.filter(g.filters);
+        stmt = apply_order(stmt, g.order_by);
+        let models: HashMap<ValueTuple, Vec<T::Model>> = loader_impl(
+            keys,
+            g.junction_fields,
+            stmt,
+            g.rel_def,
+            g.via_def,
+            connection,
+        )
+        .await?;
+        for (key, models) in models {
+            results.insert(
+                KeyComplex {
+                    key,
+                    meta: group.clone(),
+                },
+                models,
+            );
+        }
+    }
+
+    Ok(results)
+}
+
+/// used to load the related model of the keys with a connection
+async fn load_one_to_one<T, C>(
+    groups: &[KeyComplex<T>],
+    connection: &C,
+) -> Result<HashMap<KeyComplex<T>, T::Model>, Arc<DbErr>>
+where
+    T: EntityTrait,
+    T::Model: Sync,
+    C: ConnectionTrait,
+{
+    let groups = consolidate_groups(groups);
+
+    let mut results: HashMap<KeyComplex<T>, T::Model> = HashMap::new();
+
+    for (group, keys) in groups {
+        let g = group.clone();
+        let mut stmt = g.stmt;
+        stmt = stmt.filter(g.filters);
+        stmt = apply_order(stmt, g.order_by);
+        let models: HashMap<ValueTuple, Option<T::Model>> = loader_impl(
+            keys,
+            g.junction_fields,
+            stmt,
+            g.rel_def,
+            g.via_def,
+            connection,
+        )
+        .await?;
+        for (key, model) in models {
+            if let Some(model) = model {
+                results.insert(
+                    KeyComplex {
+                        key,
+                        meta: group.clone(),
+                    },
+                    model,
+                );
             }
         }
+    }
 
-        Ok(results)
+    Ok(results)
+}
+
+/// used to load the related models of a key, within the request transaction once a mutation
+/// has opened it: the data loaders read with their own connection
+pub(crate) async fn load_many<T>(
+    ctx: &ResolverContext<'_>,
+    key: KeyComplex<T>,
+) -> async_graphql::Result<Option<Vec<T::Model>>>
+where
+    T: EntityTrait,
+    T::Model: Sync,
+{
+    match open_request_transaction(ctx) {
+        Some(transaction) => Ok(
+            load_one_to_many(std::slice::from_ref(&key), transaction.as_ref())
+                .await?
+                .remove(&key),
+        ),
+        None => Ok(ctx
+            .data_unchecked::<DataLoader<OneToManyLoader<T>>>()
+            .load_one(key)
+            .await?),
+    }
+}
+
+/// used to load the related model of a key, within the request transaction once a mutation
+/// has opened it
+pub(crate) async fn load_one<T>(
+    ctx: &ResolverContext<'_>,
+    key: KeyComplex<T>,
+) -> async_graphql::Result<Option<T::Model>>
+where
+    T: EntityTrait,
+    T::Model: Sync,
+{
+    match open_request_transaction(ctx) {
+        Some(transaction) => Ok(
+            load_one_to_one(std::slice::from_ref(&key), transaction.as_ref())
+                .await?
+                .remove(&key),
+        ),
+        None => Ok(ctx
+            .data_unchecked::<DataLoader<OneToOneLoader<T>>>()
+            .load_one(key)
+            .await?),
     }
 }
 
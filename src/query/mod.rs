@@ -4,6 +4,9 @@ pub use loader::*;
 pub mod entity_query_field;
 pub use entity_query_field::*;
 
+pub mod permissions_query;
+pub use permissions_query::*;
+
 pub mod ordering;
 pub use ordering::*;
 
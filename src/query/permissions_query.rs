@@ -0,0 +1,323 @@
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, Object, TypeRef};
+use sea_orm::{
+    sea_query::Query, ColumnTrait, DatabaseConnection, EntityTrait, Iterable, PrimaryKeyToColumn,
+    QueryFilter, QueryTrait,
+};
+
+use crate::{
+    resolve_entity_filter, resolve_entity_guard, resolve_field_guard, resolve_field_guards,
+    BuilderContext, DatabaseContext, EntityColumnId, EntityObjectBuilder, EntityQueryFieldBuilder,
+    GuardAction, OperationType, TypesMapHelper, UserContext,
+};
+
+/// The configuration structure for PermissionsQueryBuilder
+pub struct PermissionsQueryConfig {
+    /// add the permissions query, evaluating the guards, the visibility rules and RBAC
+    /// for the `UserContext` of the request
+    pub enabled: bool,
+    /// name of the query field
+    pub query_name: String,
+    /// name of the object holding one field per entity
+    pub type_name: String,
+    /// name of the object with the permissions of an entity
+    pub entity_type_name: String,
+    /// name of the object with the permissions of a field
+    pub field_type_name: String,
+}
+
+impl std::default::Default for PermissionsQueryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            query_name: "_permissions".into(),
+            type_name: "Permissions".into(),
+            entity_type_name: "EntityPermissions".into(),
+            field_type_name: "FieldPermissions".into(),
+        }
+    }
+}
+
+/// The operations the current user may perform on an entity,
+/// or on a record if queried by primary key
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EntityPermissions {
+    pub entity: String,
+    pub read: bool,
+    pub create: bool,
+    pub update: bool,
+    pub delete: bool,
+    pub fields: Vec<FieldPermissions>,
+}
+
+/// The operations the current user may perform on a field
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FieldPermissions {
+    pub name: String,
+    pub read: bool,
+    pub create: bool,
+    pub update: bool,
+}
+
+/// This builder produces the `_permissions` query
+pub struct PermissionsQueryBuilder {
+    pub context: &'static BuilderContext,
+}
+
+impl PermissionsQueryBuilder {
+    /// used to get the field of the permissions object for a SeaORM entity, taking the
+    /// primary key of a record as optional `id`, unless the primary key is composite.
+    /// Entities without mutations only allow reads
+    pub fn entity_field<T>(&self, mutations: bool) -> Field
+    where
+        T: EntityTrait,
+        <T as EntityTrait>::Model: Sync,
+    {
+        let context = self.context;
+
+        let columns: Vec<T::Column> = T::PrimaryKey::iter()
+            .map(|variant| variant.into_column())
+            .collect();
+        let column = match columns[..] {
+            [column] => Some(column),
+            _ => None,
+        };
+
+        let field = Field::new(
+            EntityQueryFieldBuilder { context }.type_name::<T>(),
+            TypeRef::named_nn(&context.permissions_query.entity_type_name),
+            move |ctx| {
+                FieldFuture::new(async move {
+                    let object_name = EntityObjectBuilder { context }.type_name::<T>();
+
+                    let id = match (column, ctx.args.get("id")) {
+                        (Some(column), Some(id)) if !id.is_null() => Some((
+                            column,
+                            TypesMapHelper { context }
+                                .async_graphql_value_to_sea_orm_value::<T>(&column, &id)?,
+                        )),
+                        _ => None,
+                    };
+
+                    let db = ctx
+                        .data::<DatabaseConnection>()?
+                        .restricted(ctx.data_opt::<UserContext>())?;
+
+                    let table = T::default().table_ref();
+                    let rbac = [
+                        db.user_can_run(T::find().as_query()).is_ok(),
+                        db.user_can_run(&Query::insert().into_table(table).to_owned())
+                            .is_ok(),
+                        db.user_can_run(T::update_many().as_query()).is_ok(),
+                        db.user_can_run(T::delete_many().as_query()).is_ok(),
+                    ];
+
+                    let mut allowed = [false; 4];
+                    for (i, action) in [
+                        OperationType::Read,
+                        OperationType::Create,
+                        OperationType::Update,
+                        OperationType::Delete,
+                    ]
+                    .into_iter()
+                    .enumerate()
+                    {
+                        if !rbac[i]
+                            || (action != OperationType::Read && !mutations)
+                            || context.visibility.is_hidden(&object_name, None, action)
+                            || matches!(
+                                resolve_entity_guard::<T>(&ctx, context, &object_name, action)
+                                    .await,
+                                GuardAction::Block(_)
+                            )
+                        {
+                            continue;
+                        }
+
+                        // a record has to match the filters the operation would apply,
+                        // records the user cannot select count as not allowed
+                        if let (Some((column, id)), false) = (&id, action == OperationType::Create)
+                        {
+                            let mut stmt = T::find().filter(column.eq(id.clone()));
+                            if let Some(filter) =
+                                resolve_entity_filter::<T>(&ctx, context, &object_name, action)
+                                    .await
+                            {
+                                stmt = stmt.filter(filter);
+                            }
+                            if stmt.one(&db).await?.is_none() {
+                                continue;
+                            }
+                        }
+
+                        allowed[i] = true;
+                    }
+                    let [read, create, update, delete] = allowed;
+
+                    let entity_object_builder = EntityObjectBuilder { context };
+                    let names: Vec<String> = T::Column::iter()
+                        .filter(|column| !column.def().seaography().ignore)
+                        .map(|column| entity_object_builder.column_name::<T>(&column))
+                        .filter(|name| {
+                            !context.visibility.is_field_hidden(
+                                &object_name,
+                                name,
+                                OperationType::Read,
+                            )
+                        })
+                        .collect();
+
+                    let mut fields = Vec::new();
+                    for name in names {
+                        let field_allowed = |action| {
+                            let name = &name;
+                            let object_name = &object_name;
+                            let ctx = &ctx;
+                            async move {
+                                !matches!(
+                                    resolve_field_guards(
+                                        ctx,
+                                        context,
+                                        object_name,
+                                        &[name],
+                                        action
+                                    )
+                                    .await,
+                                    GuardAction::Block(_)
+                                )
+                            }
+                        };
+
+                        fields.push(FieldPermissions {
+                            // masked and replaced fields cannot be read either
+                            read: read
+                                && resolve_field_guard(
+                                    &ctx,
+                                    context,
+                                    &object_name,
+                                    &name,
+                                    OperationType::Read,
+                                )
+                                .await
+                                    == GuardAction::Allow,
+                            create: create && field_allowed(OperationType::Create).await,
+                            update: update && field_allowed(OperationType::Update).await,
+                            name,
+                        });
+                    }
+
+                    Ok(Some(FieldValue::owned_any(EntityPermissions {
+                        entity: object_name,
+                        read,
+                        create,
+                        update,
+                        delete,
+                        fields,
+                    })))
+                })
+            },
+        );
+
+        match column {
+            Some(column) => field.argument(InputValue::new(
+                "id",
+                TypesMapHelper { context }
+                    .input_type_for_column::<T>(&column, &EntityColumnId::of::<T>(&column), false)
+                    .expect("primary key to be supported"),
+            )),
+            None => field,
+        }
+    }
+
+    /// used to get the permissions object from the fields of the entities
+    pub fn to_object(&self, fields: impl IntoIterator<Item = Field>) -> Object {
+        fields.into_iter().fold(
+            Object::new(&self.context.permissions_query.type_name),
+            |object, field| object.field(field),
+        )
+    }
+
+    /// used to get the objects of the permissions of an entity and of a field
+    pub fn to_permission_objects(&self) -> [Object; 2] {
+        let config = &self.context.permissions_query;
+
+        let entity = Object::new(&config.entity_type_name)
+            .field(Field::new(
+                "entity",
+                TypeRef::named_nn(TypeRef::STRING),
+                |ctx| {
+                    FieldFuture::new(async move {
+                        let permissions =
+                            ctx.parent_value.try_downcast_ref::<EntityPermissions>()?;
+                        Ok(Some(FieldValue::value(permissions.entity.clone())))
+                    })
+                },
+            ))
+            .field(entity_flag("read", |permissions| permissions.read))
+            .field(entity_flag("create", |permissions| permissions.create))
+            .field(entity_flag("update", |permissions| permissions.update))
+            .field(entity_flag("delete", |permissions| permissions.delete))
+            .field(Field::new(
+                "fields",
+                TypeRef::named_nn_list_nn(&config.field_type_name),
+                |ctx| {
+                    FieldFuture::new(async move {
+                        let permissions =
+                            ctx.parent_value.try_downcast_ref::<EntityPermissions>()?;
+                        Ok(Some(FieldValue::list(
+                            permissions
+                                .fields
+                                .iter()
+                                .map(|field| FieldValue::borrowed_any(field)),
+                        )))
+                    })
+                },
+            ));
+
+        let field = Object::new(&config.field_type_name)
+            .field(Field::new(
+                "name",
+                TypeRef::named_nn(TypeRef::STRING),
+                |ctx| {
+                    FieldFuture::new(async move {
+                        let permissions =
+                            ctx.parent_value.try_downcast_ref::<FieldPermissions>()?;
+                        Ok(Some(FieldValue::value(permissions.name.clone())))
+                    })
+                },
+            ))
+            .field(field_flag("read", |permissions| permissions.read))
+            .field(field_flag("create", |permissions| permissions.create))
+            .field(field_flag("update", |permissions| permissions.update));
+
+        [entity, field]
+    }
+
+    /// used to get the query field
+    pub fn to_field(&self) -> Field {
+        let config = &self.context.permissions_query;
+
+        Field::new(
+            &config.query_name,
+            TypeRef::named_nn(&config.type_name),
+            |_| FieldFuture::new(async move { Ok(Some(FieldValue::value(true))) }),
+        )
+    }
+}
+
+fn entity_flag(name: &str, flag: fn(&EntityPermissions) -> bool) -> Field {
+    Field::new(name, TypeRef::named_nn(TypeRef::BOOLEAN), move |ctx| {
+        FieldFuture::new(async move {
+            let permissions = ctx.parent_value.try_downcast_ref::<EntityPermissions>()?;
+            Ok(Some(FieldValue::value(flag(permissions))))
+        })
+    })
+}
+
+fn field_flag(name: &str, flag: fn(&FieldPermissions) -> bool) -> Field {
+    Field::new(name, TypeRef::named_nn(TypeRef::BOOLEAN), move |ctx| {
+        FieldFuture::new(async move {
+            let permissions = ctx.parent_value.try_downcast_ref::<FieldPermissions>()?;
+            Ok(Some(FieldValue::value(flag(permissions))))
+        })
+    })
+}
@@ -1,8 +1,112 @@
-use sea_orm::{DatabaseConnection, DbErr, StatementBuilder};
+use std::collections::BTreeMap;
 
-#[derive(Default)]
+use sea_orm::{
+    sea_query::value::sea_value_to_json_value, DatabaseConnection, DbErr, StatementBuilder, Value,
+};
+
+/// The user of the request, added to the request data. Hooks read it with
+/// `ctx.data_opt::<UserContext>()`, queries are restricted with it
+/// ```ignore
+/// let user = UserContext::new("auth0|42")
+///     .with_roles(["editor"])
+///     .with_claim("org", json!("acme"))
+///     .rbac_user_by_role(&[("admin", 1), ("editor", 2)]);
+/// schema.execute(request.data(user)).await
+/// ```
+#[derive(Clone, Debug)]
 pub struct UserContext {
-    pub user_id: i64,
+    /// of any type, e.g. a string subject or an integer key
+    pub user_id: Value,
+    pub roles: Vec<String>,
+    /// arbitrary claims, e.g. of a JWT
+    pub claims: BTreeMap<String, serde_json::Value>,
+    /// the `RbacUserId` queries are restricted with, defaults to an integer `user_id`.
+    /// Queries of users without one are rejected
+    pub rbac_user_id: Option<i64>,
+}
+
+impl Default for UserContext {
+    fn default() -> Self {
+        Self::new(0i64)
+    }
+}
+
+impl UserContext {
+    pub fn new<V>(user_id: V) -> Self
+    where
+        V: Into<Value>,
+    {
+        Self {
+            user_id: user_id.into(),
+            roles: Vec::new(),
+            claims: BTreeMap::new(),
+            rbac_user_id: None,
+        }
+    }
+
+    pub fn with_roles<I, S>(mut self, roles: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.roles.extend(roles.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_claim<S>(mut self, name: S, value: serde_json::Value) -> Self
+    where
+        S: Into<String>,
+    {
+        self.claims.insert(name.into(), value);
+        self
+    }
+
+    /// used to restrict queries as the given RBAC user
+    pub fn rbac_user(mut self, rbac_user_id: i64) -> Self {
+        self.rbac_user_id = Some(rbac_user_id);
+        self
+    }
+
+    /// used to restrict queries as the RBAC user of the first role of `role_users`
+    /// the user has, e.g. when users are not known to the RBAC tables but their roles are
+    pub fn rbac_user_by_role(mut self, role_users: &[(&str, i64)]) -> Self {
+        if let Some((_, rbac_user_id)) = role_users.iter().find(|(role, _)| self.has_role(role)) {
+            self.rbac_user_id = Some(*rbac_user_id);
+        }
+        self
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    pub fn claim(&self, name: &str) -> Option<&serde_json::Value> {
+        self.claims.get(name)
+    }
+
+    /// used to get the `RbacUserId` of the user: the one set explicitly
+    /// or by role, else an integer `user_id`
+    pub fn rbac_user_id(&self) -> Option<i64> {
+        self.rbac_user_id.or(match self.user_id {
+            Value::TinyInt(Some(id)) => Some(id.into()),
+            Value::SmallInt(Some(id)) => Some(id.into()),
+            Value::Int(Some(id)) => Some(id.into()),
+            Value::BigInt(Some(id)) => Some(id),
+            Value::TinyUnsigned(Some(id)) => Some(id.into()),
+            Value::SmallUnsigned(Some(id)) => Some(id.into()),
+            Value::Unsigned(Some(id)) => Some(id.into()),
+            Value::BigUnsigned(Some(id)) => i64::try_from(id).ok(),
+            _ => None,
+        })
+    }
+
+    /// used to display the user id, e.g. as the actor of the audit log
+    pub fn user_id_string(&self) -> String {
+        match sea_value_to_json_value(&self.user_id) {
+            serde_json::Value::String(user_id) => user_id,
+            user_id => user_id.to_string(),
+        }
+    }
 }
 
 pub trait DatabaseContext {
@@ -34,10 +138,18 @@ impl DatabaseContext for DatabaseConnection {
     ) -> Result<sea_orm::RestrictedConnection, DbErr> {
         use sea_orm::rbac::RbacUserId;
 
-        self.restricted_for(match user_ctx {
-            Some(user_ctx) => RbacUserId(user_ctx.user_id),
-            None => RbacUserId(0),
-        })
+        // requests without user are restricted as the anonymous user 0
+        let rbac_user_id = match user_ctx {
+            Some(user_ctx) => user_ctx.rbac_user_id().ok_or_else(|| {
+                DbErr::RbacError(format!(
+                    "user `{}` has no RBAC user id",
+                    user_ctx.user_id_string()
+                ))
+            })?,
+            None => 0,
+        };
+
+        self.restricted_for(RbacUserId(rbac_user_id))
     }
 
     fn user_can_run<S: StatementBuilder>(&self, _: &S) -> Result<(), DbErr> {
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use async_graphql::futures_util::stream::BoxStream;
+use sea_orm::entity::prelude::async_trait;
+
+use crate::EntityChangeEvent;
+
+/// The bus shared by mutations and subscriptions, stored in the schema data
+pub type SharedChangeEventBus = Arc<dyn ChangeEventBus>;
+
+/// A publish / subscribe channel for entity change events.
+/// Implement it to plug in an external broker shared by all replicas
+#[async_trait::async_trait]
+pub trait ChangeEventBus: Send + Sync {
+    /// publishes an event to all subscribers, including those of other processes
+    async fn publish(&self, event: EntityChangeEvent);
+
+    /// receives all events published from now on
+    fn subscribe(&self) -> BoxStream<'static, EntityChangeEvent>;
+}
+
+/// used to get the change event bus of the schema, custom hooks can publish through it too.
+/// The generated mutations notify it together with the `entity_watch_changes` hooks
+pub fn change_event_bus<'a>(
+    ctx: &'a async_graphql::dynamic::ResolverContext,
+) -> Option<&'a SharedChangeEventBus> {
+    ctx.data_opt::<SharedChangeEventBus>()
+}
+
+/// An in-process bus, the default when change subscriptions are enabled
+#[derive(Clone)]
+pub struct InMemoryChangeEventBus {
+    sender: tokio::sync::broadcast::Sender<EntityChangeEvent>,
+}
+
+impl InMemoryChangeEventBus {
+    /// used to create a bus that buffers up to `capacity` events per slow subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = tokio::sync::broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// used to deliver an event to the subscribers of this process
+    pub fn send(&self, event: EntityChangeEvent) {
+        // no receivers is not an error, nobody is listening
+        let _ = self.sender.send(event);
+    }
+}
+
+#[async_trait::async_trait]
+impl ChangeEventBus for InMemoryChangeEventBus {
+    async fn publish(&self, event: EntityChangeEvent) {
+        self.send(event);
+    }
+
+    fn subscribe(&self) -> BoxStream<'static, EntityChangeEvent> {
+        use tokio::sync::broadcast::error::RecvError;
+
+        let receiver = self.sender.subscribe();
+
+        Box::pin(async_graphql::futures_util::stream::unfold(
+            receiver,
+            |mut receiver| async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) => return Some((event, receiver)),
+                        // slow subscribers miss the oldest events
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            },
+        ))
+    }
+}
+
+#[cfg(feature = "subscriptions-postgres")]
+pub use postgres::*;
+
+#[cfg(feature = "subscriptions-postgres")]
+mod postgres {
+    use async_graphql::futures_util::stream::BoxStream;
+    use sea_orm::{
+        entity::prelude::async_trait, sqlx::postgres::PgListener, ConnectionTrait,
+        DatabaseConnection, DbBackend, DbErr, RuntimeErr, Statement,
+    };
+    use std::sync::Arc;
+
+    use super::{ChangeEventBus, InMemoryChangeEventBus};
+    use crate::EntityChangeEvent;
+
+    /// NOTIFY payloads have to be shorter than 8000 bytes
+    const MAX_PAYLOAD: usize = 7900;
+
+    /// A bus over Postgres `LISTEN` / `NOTIFY`, shared by every process listening
+    /// on the same channel. Events of this process are delivered locally with
+    /// their models, events of other processes carry keys only.
+    /// The listener stops when the bus is dropped
+    pub struct PostgresChangeEventBus {
+        db: DatabaseConnection,
+        channel: String,
+        source: String,
+        local: InMemoryChangeEventBus,
+        listener: tokio::task::AbortHandle,
+    }
+
+    impl PostgresChangeEventBus {
+        /// used to start listening on `channel` with a Postgres connection
+        pub async fn new(
+            db: &DatabaseConnection,
+            channel: &str,
+            capacity: usize,
+        ) -> Result<Self, DbErr> {
+            let sqlx_err = |err| DbErr::Conn(RuntimeErr::SqlxError(Arc::new(err)));
+
+            let mut listener = PgListener::connect_with(db.get_postgres_connection_pool())
+                .await
+                .map_err(sqlx_err)?;
+            listener.listen(channel).await.map_err(sqlx_err)?;
+
+            let source = format!(
+                "{}-{}",
+                std::process::id(),
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|duration| duration.as_nanos())
+                    .unwrap_or_default()
+            );
+            let local = InMemoryChangeEventBus::new(capacity);
+
+            let own_source = source.clone();
+            let forward = local.clone();
+            let listener = tokio::spawn(async move {
+                loop {
+                    match listener.recv().await {
+                        Ok(notification) => {
+                            let payload: serde_json::Value =
+                                match serde_json::from_str(notification.payload()) {
+                                    Ok(payload) => payload,
+                                    Err(_) => continue,
+                                };
+                            if payload.get("source").and_then(|source| source.as_str())
+                                == Some(own_source.as_str())
+                            {
+                                continue;
+                            }
+                            if let Some(event) = EntityChangeEvent::from_json(&payload) {
+                                forward.send(event);
+                            }
+                        }
+                        // the listener reconnects on the next call
+                        Err(_) => {
+                            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                        }
+                    }
+                }
+            });
+
+            Ok(Self {
+                db: db.clone(),
+                channel: channel.into(),
+                source,
+                local,
+                listener: listener.abort_handle(),
+            })
+        }
+
+        /// used to split an event into NOTIFY payloads below the size limit
+        fn payloads(&self, event: &EntityChangeEvent) -> Vec<String> {
+            let payload = |keys: &[String]| {
+                let mut payload = EntityChangeEvent {
+                    keys: keys.to_vec(),
+                    models: None,
+                    ..event.clone()
+                }
+                .to_json();
+                payload["source"] = self.source.clone().into();
+                payload.to_string()
+            };
+
+            let overhead = payload(&[]).len();
+            let mut payloads = Vec::new();
+            let mut start = 0;
+            let mut size = overhead;
+            for (index, key) in event.keys.iter().enumerate() {
+                let key_size = key.len() + 3;
+                if index > start && size + key_size > MAX_PAYLOAD {
+                    payloads.push(payload(&event.keys[start..index]));
+                    start = index;
+                    size = overhead;
+                }
+                size += key_size;
+            }
+            payloads.push(payload(&event.keys[start..]));
+
+            payloads
+        }
+    }
+
+    impl Drop for PostgresChangeEventBus {
+        fn drop(&mut self) {
+            self.listener.abort();
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ChangeEventBus for PostgresChangeEventBus {
+        async fn publish(&self, event: EntityChangeEvent) {
+            for payload in self.payloads(&event) {
+                // the change is already committed, a failed notification can only be dropped
+                let _ = self
+                    .db
+                    .execute_raw(Statement::from_sql_and_values(
+                        DbBackend::Postgres,
+                        "SELECT pg_notify($1, $2)",
+                        [self.channel.clone().into(), payload.into()],
+                    ))
+                    .await;
+            }
+
+            self.local.send(event);
+        }
+
+        fn subscribe(&self) -> BoxStream<'static, EntityChangeEvent> {
+            self.local.subscribe()
+        }
+    }
+}
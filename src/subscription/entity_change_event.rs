@@ -0,0 +1,221 @@
+use std::{any::Any, sync::Arc};
+
+use async_graphql::dynamic::ResolverContext;
+use sea_orm::{sea_query::ValueTuple, EntityTrait, Iterable, ModelTrait, PrimaryKeyToColumn};
+
+use crate::{BuilderContext, EntityChanges, OperationType, WatchContext};
+
+/// A change to rows of an entity, published after the mutation has been committed
+#[derive(Clone)]
+pub struct EntityChangeEvent {
+    /// the GraphQL object name of the entity
+    pub entity: String,
+    /// the mutation that caused the change
+    pub operation: OperationType,
+    /// the primary keys of the affected rows, encoded as cursors
+    pub keys: Vec<String>,
+    /// the affected models as `Vec<Model>`, only available in-process.
+    /// Events received from other processes carry keys only
+    pub models: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+impl std::fmt::Debug for EntityChangeEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntityChangeEvent")
+            .field("entity", &self.entity)
+            .field("operation", &self.operation)
+            .field("keys", &self.keys)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EntityChangeEvent {
+    /// used to serialize the event for an external broker, models are not included
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "entity": self.entity,
+            "operation": operation_name(self.operation),
+            "keys": self.keys,
+        })
+    }
+
+    /// used to deserialize an event produced by `to_json`
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let entity = value.get("entity")?.as_str()?.to_owned();
+        let operation = match value.get("operation")?.as_str()? {
+            "READ" => OperationType::Read,
+            "CREATE" => OperationType::Create,
+            "UPDATE" => OperationType::Update,
+            "DELETE" => OperationType::Delete,
+            _ => return None,
+        };
+        let keys = value
+            .get("keys")?
+            .as_array()?
+            .iter()
+            .map(|key| key.as_str().map(ToOwned::to_owned))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            entity,
+            operation,
+            keys,
+            models: None,
+        })
+    }
+}
+
+/// used to encode the primary key of a changed model as cursor
+pub(crate) fn entity_change_key<T>(model: &T::Model) -> String
+where
+    T: EntityTrait,
+{
+    crate::encode_cursor(ValueTuple::Many(
+        T::PrimaryKey::iter()
+            .map(|key| model.get(key.into_column()))
+            .collect(),
+    ))
+}
+
+/// used to encode the primary keys of changed models as cursors
+pub(crate) fn entity_change_keys<T>(models: &[T::Model]) -> Vec<String>
+where
+    T: EntityTrait,
+{
+    models.iter().map(entity_change_key::<T>).collect()
+}
+
+/// used to get the name of an operation in serialized events
+pub(crate) fn operation_name(operation: OperationType) -> &'static str {
+    match operation {
+        OperationType::Read => "READ",
+        OperationType::Create => "CREATE",
+        OperationType::Update => "UPDATE",
+        OperationType::Delete => "DELETE",
+    }
+}
+
+/// used to check whether mutations of this request publish change events
+#[cfg(feature = "subscriptions")]
+pub(crate) fn publishes_entity_changes(ctx: &ResolverContext) -> bool {
+    ctx.data_opt::<crate::SharedChangeEventBus>().is_some()
+}
+
+#[cfg(not(feature = "subscriptions"))]
+pub(crate) fn publishes_entity_changes(_ctx: &ResolverContext) -> bool {
+    false
+}
+
+/// used to notify the hooks and the change event bus of a committed mutation: `models` are
+/// the created, updated or deleted rows, `before` the pre-images loaded for the hooks.
+/// Within a request transaction, `entity_watch_changes` and the bus are notified once it is committed
+pub(crate) async fn notify_entity_change<T>(
+    ctx: &ResolverContext<'_>,
+    context: &'static BuilderContext,
+    entity: &str,
+    operation: OperationType,
+    models: Vec<T::Model>,
+    before: Option<Vec<T::Model>>,
+) where
+    T: EntityTrait,
+    <T as EntityTrait>::Model: Sync,
+{
+    context.hooks.entity_watch(ctx, entity, operation).await;
+
+    let change =
+        EntityChangeNotification::new::<T>(ctx, context, entity, operation, models, before);
+
+    let change = match ctx.data_opt::<crate::RequestTransaction>() {
+        Some(request_transaction) => match request_transaction.defer(change) {
+            Some(change) => change,
+            None => return,
+        },
+        None => change,
+    };
+
+    change.dispatch(&WatchContext::resolver(ctx)).await;
+}
+
+/// The change of a committed mutation, to be passed to the `entity_watch_changes` hooks
+/// and the change event bus
+pub(crate) struct EntityChangeNotification {
+    context: &'static BuilderContext,
+    entity: String,
+    operation: OperationType,
+    keys: Vec<String>,
+    before: Option<Arc<dyn Any + Send + Sync>>,
+    after: Option<Arc<dyn Any + Send + Sync>>,
+    #[cfg(feature = "subscriptions")]
+    event: Option<EntityChangeEvent>,
+}
+
+impl EntityChangeNotification {
+    fn new<T>(
+        ctx: &ResolverContext<'_>,
+        context: &'static BuilderContext,
+        entity: &str,
+        operation: OperationType,
+        models: Vec<T::Model>,
+        before: Option<Vec<T::Model>>,
+    ) -> Self
+    where
+        T: EntityTrait,
+        <T as EntityTrait>::Model: Sync,
+    {
+        let models: Arc<Vec<T::Model>> = Arc::new(models);
+        let after = (operation != OperationType::Delete).then(|| models.clone());
+        let keys = entity_change_keys::<T>(
+            after
+                .as_deref()
+                .or(before.as_ref())
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+        );
+
+        #[cfg(feature = "subscriptions")]
+        let event =
+            (publishes_entity_changes(ctx) && !models.is_empty()).then(|| EntityChangeEvent {
+                entity: entity.into(),
+                operation,
+                keys: entity_change_keys::<T>(&models),
+                models: Some(models.clone()),
+            });
+        #[cfg(not(feature = "subscriptions"))]
+        let _ = ctx;
+
+        Self {
+            context,
+            entity: entity.into(),
+            operation,
+            keys,
+            before: before.map(|models| Arc::new(models) as Arc<dyn Any + Send + Sync>),
+            after: after.map(|models| models as Arc<dyn Any + Send + Sync>),
+            #[cfg(feature = "subscriptions")]
+            event,
+        }
+    }
+
+    /// used to publish the event on the bus and run the `entity_watch_changes` hooks
+    pub(crate) async fn dispatch(self, ctx: &WatchContext<'_>) {
+        #[cfg(feature = "subscriptions")]
+        if let (Some(bus), Some(event)) =
+            (ctx.data_opt::<crate::SharedChangeEventBus>(), self.event)
+        {
+            bus.publish(event).await;
+        }
+
+        self.context
+            .hooks
+            .entity_watch_changes(
+                ctx,
+                &self.entity,
+                self.operation,
+                &EntityChanges {
+                    keys: self.keys,
+                    before: self.before.as_deref(),
+                    after: self.after.as_deref(),
+                },
+            )
+            .await;
+    }
+}
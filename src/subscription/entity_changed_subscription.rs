@@ -0,0 +1,340 @@
+/// The configuration structure of EntityChangedSubscriptionBuilder
+pub struct EntityChangedSubscriptionConfig {
+    /// if true, a `{entity}Changed` subscription is generated for every entity
+    /// (requires the `subscriptions` feature)
+    pub enabled: bool,
+    /// suffix that is appended on change subscriptions
+    pub subscription_suffix: String,
+    /// name for `filter` field
+    pub filter_field: String,
+    /// suffix that is appended on the change event object name
+    pub event_suffix: String,
+    /// name for `operation` field
+    pub operation_field: String,
+    /// name for `keys` field
+    pub keys_field: String,
+    /// name for `models` field
+    pub models_field: String,
+    /// name of the change operation enumeration
+    pub operation_enum: String,
+    /// number of events buffered for slow subscribers, older events are dropped
+    pub capacity: usize,
+}
+
+impl std::default::Default for EntityChangedSubscriptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            subscription_suffix: {
+                if cfg!(feature = "field-snake-case") {
+                    "_changed"
+                } else {
+                    "Changed"
+                }
+                .into()
+            },
+            filter_field: "filter".into(),
+            event_suffix: "ChangedEvent".into(),
+            operation_field: "operation".into(),
+            keys_field: "keys".into(),
+            models_field: "models".into(),
+            operation_enum: "ChangeOperation".into(),
+            capacity: 1024,
+        }
+    }
+}
+
+#[cfg(feature = "subscriptions")]
+pub use builder::*;
+
+#[cfg(feature = "subscriptions")]
+mod builder {
+    use async_graphql::dynamic::{
+        Enum, EnumItem, Field, FieldFuture, FieldValue, InputValue, Object, SubscriptionField,
+        SubscriptionFieldFuture, TypeRef,
+    };
+    use async_graphql::futures_util::StreamExt;
+    use sea_orm::{
+        sea_query::Condition, ColumnTrait, DatabaseConnection, EntityTrait, Iterable,
+        PrimaryKeyToColumn, QueryFilter, QueryTrait,
+    };
+
+    use crate::{
+        decode_cursor, get_filter_conditions, guard_error, reject_masked_fields,
+        resolve_after_read, resolve_entity_filter, resolve_entity_guard, BuilderContext,
+        DatabaseContext, EntityObjectBuilder, EntityQueryFieldBuilder, FilterInputBuilder,
+        GuardAction, OperationType, SharedChangeEventBus, UserContext,
+    };
+
+    /// The payload of a change event object
+    pub struct EntityChange<M> {
+        pub operation: OperationType,
+        pub keys: Vec<String>,
+        pub models: Vec<M>,
+    }
+
+    /// This builder produces the change subscription of an entity
+    pub struct EntityChangedSubscriptionBuilder {
+        pub context: &'static BuilderContext,
+    }
+
+    impl EntityChangedSubscriptionBuilder {
+        /// used to get subscription name for a SeaORM entity
+        pub fn type_name<T>(&self) -> String
+        where
+            T: EntityTrait,
+        {
+            let entity_query_field_builder = EntityQueryFieldBuilder {
+                context: self.context,
+            };
+            format!(
+                "{}{}",
+                entity_query_field_builder.type_name::<T>(),
+                self.context.entity_changed_subscription.subscription_suffix
+            )
+        }
+
+        /// used to get the change event object name for a SeaORM entity
+        pub fn event_type_name<T>(&self) -> String
+        where
+            T: EntityTrait,
+        {
+            let entity_object_builder = EntityObjectBuilder {
+                context: self.context,
+            };
+            format!(
+                "{}{}",
+                entity_object_builder.type_name::<T>(),
+                self.context.entity_changed_subscription.event_suffix
+            )
+        }
+
+        /// used to get the change operation enumeration
+        pub fn operation_enumeration(&self) -> Enum {
+            Enum::new(&self.context.entity_changed_subscription.operation_enum)
+                .item(EnumItem::new("CREATE"))
+                .item(EnumItem::new("UPDATE"))
+                .item(EnumItem::new("DELETE"))
+        }
+
+        /// used to get the change event object for a SeaORM entity
+        pub fn to_object<T>(&self) -> Object
+        where
+            T: EntityTrait,
+            <T as EntityTrait>::Model: Sync,
+        {
+            let config = &self.context.entity_changed_subscription;
+            let entity_object_builder = EntityObjectBuilder {
+                context: self.context,
+            };
+
+            Object::new(self.event_type_name::<T>())
+                .field(Field::new(
+                    &config.operation_field,
+                    TypeRef::named_nn(&config.operation_enum),
+                    |ctx| {
+                        FieldFuture::new(async move {
+                            let change = ctx
+                                .parent_value
+                                .try_downcast_ref::<EntityChange<<T as EntityTrait>::Model>>()?;
+                            let operation = match change.operation {
+                                OperationType::Create => "CREATE",
+                                OperationType::Update => "UPDATE",
+                                OperationType::Delete => "DELETE",
+                                OperationType::Read => unreachable!("reads are not published"),
+                            };
+                            Ok(Some(FieldValue::value(async_graphql::Value::Enum(
+                                async_graphql::Name::new(operation),
+                            ))))
+                        })
+                    },
+                ))
+                .field(Field::new(
+                    &config.keys_field,
+                    TypeRef::named_nn_list_nn(TypeRef::STRING),
+                    |ctx| {
+                        FieldFuture::new(async move {
+                            let change = ctx
+                                .parent_value
+                                .try_downcast_ref::<EntityChange<<T as EntityTrait>::Model>>()?;
+                            Ok(Some(FieldValue::list(
+                                change
+                                    .keys
+                                    .iter()
+                                    .map(|key| FieldValue::value(key.as_str())),
+                            )))
+                        })
+                    },
+                ))
+                .field(Field::new(
+                    &config.models_field,
+                    TypeRef::named_nn_list_nn(entity_object_builder.basic_type_name::<T>()),
+                    |ctx| {
+                        FieldFuture::new(async move {
+                            let change = ctx
+                                .parent_value
+                                .try_downcast_ref::<EntityChange<<T as EntityTrait>::Model>>()?;
+                            Ok(Some(FieldValue::list(
+                                change.models.iter().cloned().map(FieldValue::owned_any),
+                            )))
+                        })
+                    },
+                ))
+        }
+
+        /// used to get the change subscription field for a SeaORM entity.
+        /// Created and updated rows are re-read through `entity_filter` and the
+        /// subscription filter. Deleted rows can no longer be matched against a
+        /// filter, so deletes are only delivered to unfiltered subscriptions
+        pub fn to_field<T>(&self) -> SubscriptionField
+        where
+            T: EntityTrait,
+            <T as EntityTrait>::Model: Sync,
+        {
+            let entity_object_builder = EntityObjectBuilder {
+                context: self.context,
+            };
+            let filter_input_builder = FilterInputBuilder {
+                context: self.context,
+            };
+
+            let context = self.context;
+
+            let object_name: String = entity_object_builder.type_name::<T>();
+            let filter_type_name = filter_input_builder.type_name(&object_name);
+
+            SubscriptionField::new(
+                self.type_name::<T>(),
+                TypeRef::named_nn(self.event_type_name::<T>()),
+                move |ctx| {
+                    let object_name = object_name.clone();
+                    SubscriptionFieldFuture::new(async move {
+                        if let GuardAction::Block(reason) = resolve_entity_guard::<T>(
+                            &ctx,
+                            context,
+                            &object_name,
+                            OperationType::Read,
+                        )
+                        .await
+                        {
+                            return Err(guard_error(reason, "Entity guard triggered."));
+                        }
+
+                        let bus = ctx.data::<SharedChangeEventBus>()?;
+                        let db = ctx
+                            .data::<DatabaseConnection>()?
+                            .restricted(ctx.data_opt::<UserContext>())?;
+
+                        reject_masked_fields::<T>(
+                            &ctx,
+                            context,
+                            ctx.args
+                                .get(&context.entity_changed_subscription.filter_field),
+                            None,
+                            None,
+                        )
+                        .await?;
+
+                        let filters = ctx
+                            .args
+                            .get(&context.entity_changed_subscription.filter_field);
+                        let is_filtered = filters.is_some();
+                        let filter_condition = get_filter_conditions::<T>(context, filters)?;
+
+                        let entity_filter = resolve_entity_filter::<T>(
+                            &ctx,
+                            context,
+                            &object_name,
+                            OperationType::Read,
+                        )
+                        .await;
+                        let is_filtered = is_filtered || entity_filter.is_some();
+
+                        let mut events = bus.subscribe();
+
+                        Ok(async_graphql::async_stream::stream! {
+                            while let Some(event) = events.next().await {
+                                if event.entity != object_name {
+                                    continue;
+                                }
+
+                                if event.operation == OperationType::Delete {
+                                    if is_filtered {
+                                        continue;
+                                    }
+
+                                    let models = event
+                                        .models
+                                        .as_ref()
+                                        .and_then(|models| {
+                                            models.downcast_ref::<Vec<<T as EntityTrait>::Model>>()
+                                        })
+                                        .cloned()
+                                        .unwrap_or_default();
+
+                                    yield Ok(FieldValue::owned_any(EntityChange {
+                                        operation: event.operation,
+                                        keys: event.keys,
+                                        models,
+                                    }));
+                                    continue;
+                                }
+
+                                let keys_condition = match keys_condition::<T>(&event.keys) {
+                                    Ok(condition) => condition,
+                                    Err(err) => {
+                                        yield Err(err.into());
+                                        continue;
+                                    }
+                                };
+
+                                let models = T::find()
+                                    .filter(keys_condition)
+                                    .apply_if(entity_filter.clone(), |q, f| q.filter(f))
+                                    .filter(filter_condition.clone())
+                                    .all(&db)
+                                    .await;
+
+                                match models {
+                                    Ok(models) if models.is_empty() => continue,
+                                    Ok(mut models) => {
+                                        resolve_after_read::<T>(&ctx, context, &object_name, &mut models);
+                                        if models.is_empty() {
+                                            continue;
+                                        }
+                                        yield Ok(FieldValue::owned_any(EntityChange {
+                                            operation: event.operation,
+                                            keys: event.keys,
+                                            models,
+                                        }));
+                                    }
+                                    Err(err) => yield Err(err.into()),
+                                }
+                            }
+                        })
+                    })
+                },
+            )
+            .argument(InputValue::new(
+                &context.entity_changed_subscription.filter_field,
+                TypeRef::named(filter_type_name),
+            ))
+        }
+    }
+
+    /// used to turn encoded primary keys into a condition matching these rows
+    fn keys_condition<T>(keys: &[String]) -> Result<Condition, sea_orm::DbErr>
+    where
+        T: EntityTrait,
+    {
+        keys.iter().try_fold(Condition::any(), |condition, key| {
+            let values = decode_cursor(key)?;
+            let key_condition = T::PrimaryKey::iter()
+                .zip(values)
+                .fold(Condition::all(), |condition, (column, value)| {
+                    condition.add(column.into_column().eq(value))
+                });
+            Ok(condition.add(key_condition))
+        })
+    }
+}
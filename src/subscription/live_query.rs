@@ -0,0 +1,299 @@
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+};
+
+use async_graphql::dynamic::ResolverContext;
+
+/// Records the entities read while executing a request, used by live queries
+/// to decide which change events make a result stale
+#[derive(Clone, Default)]
+pub struct ReadTracker {
+    entities: Arc<Mutex<BTreeSet<String>>>,
+}
+
+impl ReadTracker {
+    /// used to record a read of an entity, by GraphQL object name
+    pub fn insert(&self, entity: &str) {
+        let mut entities = self.entities.lock().expect("read tracker lock");
+        if !entities.contains(entity) {
+            entities.insert(entity.into());
+        }
+    }
+
+    /// used to get the entities read so far
+    pub fn entities(&self) -> BTreeSet<String> {
+        self.entities.lock().expect("read tracker lock").clone()
+    }
+}
+
+/// used by query and relation resolvers to record the entity they read
+pub(crate) fn record_entity_read(ctx: &ResolverContext, entity: &str) {
+    if let Some(tracker) = ctx.data_opt::<ReadTracker>() {
+        tracker.insert(entity);
+    }
+}
+
+#[cfg(feature = "subscriptions")]
+pub use executor::*;
+
+#[cfg(feature = "subscriptions")]
+mod executor {
+    use std::{
+        any::{Any, TypeId},
+        sync::Arc,
+        time::Duration,
+    };
+
+    use async_graphql::{
+        futures_util::{stream::BoxStream, FutureExt, StreamExt},
+        parser::types::{DocumentOperations, OperationType},
+        BatchRequest, BatchResponse, Data, Executor, Request, Response, ServerError,
+    };
+
+    use super::ReadTracker;
+    use crate::{DryRun, SharedChangeEventBus, TenantContext, UserContext};
+
+    /// used to copy one type of request data into a re-execution
+    type DataCarrier = Arc<dyn Fn(&Data, &mut Data) + Send + Sync>;
+
+    /// Wraps a schema to serve `query @live { .. }` operations as streams: the query
+    /// is re-executed whenever an entity it read has changed, and the fresh result
+    /// is pushed. Re-executions receive the session data and the request data that is
+    /// carried, `UserContext`, `TenantContext` and `DryRun` by default; live queries
+    /// with other request data are rejected. All other operations are passed through unchanged
+    #[derive(Clone)]
+    pub struct LiveQueryExecutor<E> {
+        inner: E,
+        bus: SharedChangeEventBus,
+        directive: String,
+        debounce: Duration,
+        carried: Vec<(TypeId, DataCarrier)>,
+    }
+
+    impl<E> LiveQueryExecutor<E>
+    where
+        E: Executor,
+    {
+        /// used to wrap an executor, `bus` has to be the bus the schema publishes to
+        pub fn new(inner: E, bus: SharedChangeEventBus) -> Self {
+            Self {
+                inner,
+                bus,
+                directive: "live".into(),
+                debounce: Duration::from_millis(100),
+                carried: vec![
+                    data_carrier::<UserContext>(),
+                    data_carrier::<TenantContext>(),
+                    data_carrier::<DryRun>(),
+                ],
+            }
+        }
+
+        /// used to carry request data of type `D` into the re-executions of live queries
+        pub fn carry_data<D>(mut self) -> Self
+        where
+            D: Any + Clone + Send + Sync,
+        {
+            self.carried.push(data_carrier::<D>());
+            self
+        }
+
+        /// used to set how long changes are collected before the query is re-executed
+        pub fn debounce(mut self, debounce: Duration) -> Self {
+            self.debounce = debounce;
+            self
+        }
+
+        /// used to rename the `@live` directive
+        pub fn directive(mut self, directive: &str) -> Self {
+            self.directive = directive.into();
+            self
+        }
+    }
+
+    impl<E> Executor for LiveQueryExecutor<E>
+    where
+        E: Executor,
+    {
+        async fn execute(&self, request: Request) -> Response {
+            self.inner.execute(request).await
+        }
+
+        async fn execute_batch(&self, batch_request: BatchRequest) -> BatchResponse {
+            self.inner.execute_batch(batch_request).await
+        }
+
+        fn execute_stream(
+            &self,
+            request: Request,
+            session_data: Option<Arc<Data>>,
+        ) -> BoxStream<'static, Response> {
+            let query = match strip_live_directive(
+                &request.query,
+                request.operation_name.as_deref(),
+                &self.directive,
+            ) {
+                Some(query) => query,
+                None => return self.inner.execute_stream(request, session_data),
+            };
+
+            // request data is consumed by each execution, only clonable data can be carried
+            if !request
+                .data
+                .keys()
+                .all(|type_id| self.carried.iter().any(|(carried, _)| carried == type_id))
+            {
+                let response = Response::from_errors(vec![ServerError::new(
+                    "Live queries only carry request data registered with `carry_data`, \
+                     other data has to be provided with the session.",
+                    None,
+                )]);
+                return Box::pin(async_graphql::futures_util::stream::once(async move {
+                    response
+                }));
+            }
+
+            let carriers: Vec<DataCarrier> = self
+                .carried
+                .iter()
+                .map(|(_, carrier)| carrier.clone())
+                .collect();
+            let mut request_data = Data::default();
+            for carrier in &carriers {
+                carrier(&request.data, &mut request_data);
+            }
+
+            let inner = self.inner.clone();
+            let bus = self.bus.clone();
+            let debounce = self.debounce;
+
+            let operation_name = request.operation_name.clone();
+            let variables = request.variables.clone();
+            let extensions = request.extensions.clone();
+
+            let mut first_request = Some(request);
+
+            Box::pin(async_graphql::async_stream::stream! {
+                // subscribed before the first execution, so no change is missed
+                let mut events = bus.subscribe();
+
+                loop {
+                    let reads = ReadTracker::default();
+
+                    let request = match first_request.take() {
+                        Some(mut request) => {
+                            request.query = query.clone();
+                            request
+                        }
+                        None => {
+                            let mut request = Request::new(query.clone()).variables(variables.clone());
+                            request.operation_name = operation_name.clone();
+                            request.extensions = extensions.clone();
+                            for carrier in &carriers {
+                                carrier(&request_data, &mut request.data);
+                            }
+                            request
+                        }
+                    };
+
+                    let response = inner
+                        .execute_stream(request.data(reads.clone()), session_data.clone())
+                        .next()
+                        .await;
+
+                    match response {
+                        Some(response) => yield response,
+                        None => break,
+                    }
+
+                    let entities = reads.entities();
+
+                    // wait for a change of an entity the query has read
+                    loop {
+                        match events.next().await {
+                            Some(event) if entities.contains(&event.entity) => break,
+                            Some(_) => continue,
+                            None => return,
+                        }
+                    }
+
+                    // changes usually come in bursts, collect them before re-executing
+                    tokio::time::sleep(debounce).await;
+                    while let Some(Some(_)) = events.next().now_or_never() {}
+                }
+            })
+        }
+    }
+
+    /// used to get the carrier copying request data of type `D`
+    fn data_carrier<D>() -> (TypeId, DataCarrier)
+    where
+        D: Any + Clone + Send + Sync,
+    {
+        (
+            TypeId::of::<D>(),
+            Arc::new(|from: &Data, to: &mut Data| {
+                if let Some(data) = from
+                    .get(&TypeId::of::<D>())
+                    .and_then(|data| data.downcast_ref::<D>())
+                {
+                    to.insert(data.clone());
+                }
+            }),
+        )
+    }
+
+    /// used to remove the live directive from the executed query operation,
+    /// None if the operation is not a live query
+    fn strip_live_directive(
+        query: &str,
+        operation_name: Option<&str>,
+        directive: &str,
+    ) -> Option<String> {
+        let document = async_graphql::parser::parse_query(query).ok()?;
+
+        let operation = match &document.operations {
+            DocumentOperations::Single(operation) => operation,
+            DocumentOperations::Multiple(operations) => operations
+                .iter()
+                .find(|(name, _)| Some(name.as_str()) == operation_name)
+                .map(|(_, operation)| operation)?,
+        };
+
+        if operation.node.ty != OperationType::Query {
+            return None;
+        }
+
+        let live = operation
+            .node
+            .directives
+            .iter()
+            .find(|d| d.node.name.node.as_str() == directive)?;
+
+        // positions are one-based lines and columns in characters
+        let offset = query
+            .split_inclusive('\n')
+            .take(live.pos.line - 1)
+            .map(str::len)
+            .sum::<usize>()
+            + query
+                .lines()
+                .nth(live.pos.line - 1)?
+                .chars()
+                .take(live.pos.column - 1)
+                .map(char::len_utf8)
+                .sum::<usize>();
+
+        let token = format!("@{directive}");
+        if !query[offset..].starts_with(&token) {
+            return None;
+        }
+
+        Some(format!(
+            "{}{}",
+            &query[..offset],
+            &query[offset + token.len()..]
+        ))
+    }
+}
@@ -0,0 +1,16 @@
+pub mod entity_change_event;
+pub use entity_change_event::*;
+
+#[cfg(feature = "subscriptions")]
+pub mod change_event_bus;
+#[cfg(feature = "subscriptions")]
+pub use change_event_bus::*;
+
+pub mod entity_changed_subscription;
+pub use entity_changed_subscription::*;
+
+pub mod live_query;
+pub use live_query::*;
+
+pub mod outbox;
+pub use outbox::*;
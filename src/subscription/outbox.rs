@@ -0,0 +1,208 @@
+use std::future::Future;
+
+use sea_orm::{
+    sea_query::{Alias, ColumnDef, Expr, ExprTrait, Order, Query, Table, TableCreateStatement},
+    ConnectionTrait, DbErr, EntityTrait,
+};
+
+use crate::{entity_change_keys, operation_name, BuilderContext, EntityChangeEvent, OperationType};
+
+/// The configuration of the transactional outbox. When enabled, generated mutations
+/// write one event row per change within their transaction, so an event is stored
+/// if and only if the change it describes has been committed
+pub struct OutboxConfig {
+    /// name of the outbox table, `None` disables the outbox
+    pub table: Option<String>,
+    /// auto increment column, defines the order of events
+    pub id_column: String,
+    /// column holding the GraphQL object name of the entity
+    pub entity_column: String,
+    /// column holding the operation, `CREATE`, `UPDATE` or `DELETE`
+    pub operation_column: String,
+    /// text column holding the event as produced by `EntityChangeEvent::to_json`
+    pub payload_column: String,
+}
+
+impl std::default::Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            table: None,
+            id_column: "id".into(),
+            entity_column: "entity".into(),
+            operation_column: "operation".into(),
+            payload_column: "payload".into(),
+        }
+    }
+}
+
+impl OutboxConfig {
+    /// used to check whether mutations write to the outbox
+    pub fn is_enabled(&self) -> bool {
+        self.table.is_some()
+    }
+
+    /// used to get the statement creating the outbox table, if enabled
+    pub fn table_create_statement(&self) -> Option<TableCreateStatement> {
+        let table = self.table.as_ref()?;
+
+        Some(
+            Table::create()
+                .table(Alias::new(table))
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(Alias::new(&self.id_column))
+                        .big_integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(Alias::new(&self.entity_column))
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(Alias::new(&self.operation_column))
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(Alias::new(&self.payload_column))
+                        .text()
+                        .not_null(),
+                )
+                .to_owned(),
+        )
+    }
+}
+
+/// An event read from the outbox
+#[derive(Clone, Debug)]
+pub struct OutboxEvent {
+    /// the position of the event in the outbox, usable to deduplicate deliveries
+    pub id: i64,
+    pub event: EntityChangeEvent,
+}
+
+/// used to write the change of a mutation to the outbox, on the connection
+/// of the mutation's transaction
+pub(crate) async fn write_outbox<T, C>(
+    context: &BuilderContext,
+    connection: &C,
+    entity: &str,
+    operation: OperationType,
+    models: &[T::Model],
+) -> Result<(), DbErr>
+where
+    T: EntityTrait,
+    C: ConnectionTrait,
+{
+    let config = &context.outbox;
+
+    let table = match &config.table {
+        Some(table) => table,
+        None => return Ok(()),
+    };
+
+    if models.is_empty() {
+        return Ok(());
+    }
+
+    let payload = EntityChangeEvent {
+        entity: entity.into(),
+        operation,
+        keys: entity_change_keys::<T>(models),
+        models: None,
+    }
+    .to_json()
+    .to_string();
+
+    let stmt = Query::insert()
+        .into_table(Alias::new(table))
+        .columns([
+            Alias::new(&config.entity_column),
+            Alias::new(&config.operation_column),
+            Alias::new(&config.payload_column),
+        ])
+        .values_panic([
+            entity.into(),
+            operation_name(operation).into(),
+            payload.into(),
+        ])
+        .to_owned();
+
+    connection.execute(&stmt).await?;
+
+    Ok(())
+}
+
+/// used to deliver the events of the outbox in id order, `batch_size` events are read
+/// per query. An event is removed once `handler` succeeded on it; delivery stops at the
+/// first failure, which is returned and leaves that event and all later ones in place.
+///
+/// Events are delivered at least once: a crash between handling and removal delivers
+/// the event again, so consumers should deduplicate by id. Concurrent drains of the same
+/// outbox are not coordinated and have to be prevented by the caller.
+///
+/// Returns the number of delivered events
+pub async fn drain_outbox<C, F, Fut, E>(
+    context: &BuilderContext,
+    connection: &C,
+    batch_size: u64,
+    mut handler: F,
+) -> Result<usize, E>
+where
+    C: ConnectionTrait,
+    F: FnMut(OutboxEvent) -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+    E: From<DbErr>,
+{
+    let config = &context.outbox;
+
+    let table = match &config.table {
+        Some(table) => Alias::new(table),
+        None => return Ok(0),
+    };
+
+    let batch_size = Ord::max(batch_size, 1);
+    let mut delivered = 0;
+
+    loop {
+        let stmt = Query::select()
+            .columns([
+                Alias::new(&config.id_column),
+                Alias::new(&config.payload_column),
+            ])
+            .from(table.clone())
+            .order_by(Alias::new(&config.id_column), Order::Asc)
+            .limit(batch_size)
+            .to_owned();
+
+        let rows = connection.query_all(&stmt).await?;
+        let complete = (rows.len() as u64) < batch_size;
+
+        for row in rows {
+            let id: i64 = row.try_get("", &config.id_column)?;
+            let payload: String = row.try_get("", &config.payload_column)?;
+
+            let event = serde_json::from_str(&payload)
+                .ok()
+                .and_then(|payload| EntityChangeEvent::from_json(&payload))
+                .ok_or_else(|| DbErr::Custom(format!("Invalid outbox payload of event {id}")))?;
+
+            handler(OutboxEvent { id, event }).await?;
+
+            let stmt = Query::delete()
+                .from_table(table.clone())
+                .and_where(Expr::col(Alias::new(&config.id_column)).eq(id))
+                .to_owned();
+            connection.execute(&stmt).await?;
+
+            delivered += 1;
+        }
+
+        if complete {
+            return Ok(delivered);
+        }
+    }
+}